@@ -25,14 +25,42 @@ pub fn derive_deep_size(input: proc_macro::TokenStream) -> proc_macro::TokenStre
     let generics = add_trait_bounds(input.generics);
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
+    // The active field of a union isn't known, so it can't be measured.
+    if let Data::Union(ref inner) = input.data {
+        let error =
+            syn::Error::new_spanned(inner.union_token, "DeepSizeOf cannot be derived for unions");
+        return proc_macro::TokenStream::from(error.to_compile_error());
+    }
+
+    // Generate an expression to add each field to a size tree.
+    let fields = deepsize_fields(&input.data, &name);
+
     // Generate an expression to sum up the size of each field.
     let sum = deepsize_sum(&input.data, &name);
 
+    // Generate an expression to sum up the bounds of each field.
+    let approx = deepsize_approx(&input.data, &name);
+
     let expanded = quote! {
         // The generated impl.
         impl #impl_generics ::deepsize::DeepSizeOf for #name #ty_generics #where_clause {
             fn deep_size_of_children(&self, context: &mut ::deepsize::Context) -> usize {
-                #sum
+                context.descend(|context| #sum)
+            }
+
+            fn deep_size_of_children_approx(
+                &self,
+                context: &mut ::deepsize::Context,
+            ) -> (usize, usize) {
+                context.descend(|context| #approx)
+            }
+
+            fn deep_size_of_fields(
+                &self,
+                context: &mut ::deepsize::Context,
+                tree: &mut ::deepsize::SizeTree,
+            ) -> usize {
+                context.descend(|context| #fields)
             }
         }
     };
@@ -161,6 +189,171 @@ fn deepsize_sum(data: &Data, struct_name: &proc_macro2::Ident) -> TokenStream {
                 }
             }
         }
-        Data::Union(_) => unimplemented!(),
+        Data::Union(_) => unreachable!("unions are rejected by `derive_deep_size`"),
+    }
+}
+
+/// Sums the bounds of each field.  The accumulators use mixed site hygiene,
+/// so that they can't shadow the fields bound by an enum variant's matcher.
+fn sum_bounds(recurse: impl Iterator<Item = TokenStream>) -> TokenStream {
+    let bounds = syn::Ident::new("bounds", proc_macro2::Span::mixed_site());
+    let lower = syn::Ident::new("lower", proc_macro2::Span::mixed_site());
+    let upper = syn::Ident::new("upper", proc_macro2::Span::mixed_site());
+    quote! {{
+        let #bounds = (0, 0);
+        #(
+            let (#lower, #upper) = #recurse;
+            let #bounds = (#bounds.0 + #lower, #bounds.1 + #upper);
+        )*
+        #bounds
+    }}
+}
+
+fn match_fields_approx(fields: &syn::Fields) -> TokenStream {
+    match fields {
+        Fields::Named(ref fields) => sum_bounds(fields.named.iter().map(|f| {
+            let name = &f.ident;
+            quote_spanned! {f.span()=>
+                ::deepsize::DeepSizeOf::deep_size_of_children_approx(&self.#name, context)
+            }
+        })),
+        Fields::Unnamed(ref fields) => {
+            sum_bounds(fields.unnamed.iter().enumerate().map(|(i, f)| {
+                let index = Index::from(i);
+                quote_spanned! {f.span()=>
+                    ::deepsize::DeepSizeOf::deep_size_of_children_approx(&self.#index, context)
+                }
+            }))
+        }
+        Fields::Unit => quote!((0, 0)),
+    }
+}
+
+fn match_enum_fields_approx(fields: &syn::Fields) -> TokenStream {
+    match fields {
+        Fields::Named(ref fields) => sum_bounds(fields.named.iter().map(|f| {
+            let name = &f.ident;
+            quote_spanned! {f.span()=>
+                ::deepsize::DeepSizeOf::deep_size_of_children_approx(#name, context)
+            }
+        })),
+        Fields::Unnamed(ref fields) => {
+            sum_bounds(fields.unnamed.iter().enumerate().map(|(i, f)| {
+                let i = syn::Ident::new(&format!("_{}", i), proc_macro2::Span::call_site());
+                quote_spanned! {f.span()=>
+                    ::deepsize::DeepSizeOf::deep_size_of_children_approx(#i, context)
+                }
+            }))
+        }
+        Fields::Unit => quote!((0, 0)),
+    }
+}
+
+/// Generate an expression to sum up the bounds of each field.
+fn deepsize_approx(data: &Data, struct_name: &proc_macro2::Ident) -> TokenStream {
+    match *data {
+        Data::Struct(ref inner) => match_fields_approx(&inner.fields),
+        Data::Enum(ref inner) => {
+            let arms = inner.variants.iter().map(|var| {
+                let matcher = get_matcher(var);
+                let output = match_enum_fields_approx(&var.fields);
+                let name = &var.ident;
+                let ident = quote!(#struct_name::#name);
+                quote!(#ident #matcher => #output,)
+            });
+
+            quote! {
+                match self {
+                    #(#arms)*
+                    _ => (0, 0) // This is needed for empty enums
+                }
+            }
+        }
+        Data::Union(_) => unreachable!("unions are rejected by `derive_deep_size`"),
+    }
+}
+
+fn match_fields_tree(fields: &syn::Fields) -> TokenStream {
+    match fields {
+        Fields::Named(ref fields) => {
+            let recurse = fields.named.iter().map(|f| {
+                let name = &f.ident;
+                let label = name.as_ref().unwrap().to_string();
+                quote_spanned! {f.span()=>
+                    tree.add_field(#label, &self.#name, context)
+                }
+            });
+            quote! {
+                0 #(+ #recurse)*
+            }
+        }
+        Fields::Unnamed(ref fields) => {
+            let recurse = fields.unnamed.iter().enumerate().map(|(i, f)| {
+                let index = Index::from(i);
+                let label = i.to_string();
+                quote_spanned! {f.span()=>
+                    tree.add_field(#label, &self.#index, context)
+                }
+            });
+            quote! {
+                0 #(+ #recurse)*
+            }
+        }
+        Fields::Unit => quote!(0),
+    }
+}
+
+fn match_enum_fields_tree(fields: &syn::Fields) -> TokenStream {
+    match fields {
+        Fields::Named(ref fields) => {
+            let recurse = fields.named.iter().map(|f| {
+                let name = &f.ident;
+                let label = name.as_ref().unwrap().to_string();
+                quote_spanned! {f.span()=>
+                    tree.add_field(#label, #name, context)
+                }
+            });
+            quote! {
+                0 #(+ #recurse)*
+            }
+        }
+        Fields::Unnamed(ref fields) => {
+            let recurse = fields.unnamed.iter().enumerate().map(|(i, f)| {
+                let label = i.to_string();
+                let i = syn::Ident::new(&format!("_{}", i), proc_macro2::Span::call_site());
+                quote_spanned! {f.span()=>
+                    tree.add_field(#label, #i, context)
+                }
+            });
+            quote! {
+                0 #(+ #recurse)*
+            }
+        }
+        Fields::Unit => quote!(0),
+    }
+}
+
+/// Generate an expression to add each field to a size tree, and sum up
+/// the size of their children.
+fn deepsize_fields(data: &Data, struct_name: &proc_macro2::Ident) -> TokenStream {
+    match *data {
+        Data::Struct(ref inner) => match_fields_tree(&inner.fields),
+        Data::Enum(ref inner) => {
+            let arms = inner.variants.iter().map(|var| {
+                let matcher = get_matcher(var);
+                let output = match_enum_fields_tree(&var.fields);
+                let name = &var.ident;
+                let ident = quote!(#struct_name::#name);
+                quote!(#ident #matcher => #output,)
+            });
+
+            quote! {
+                match self {
+                    #(#arms)*
+                    _ => 0 // This is needed for empty enums
+                }
+            }
+        }
+        Data::Union(_) => unreachable!("unions are rejected by `derive_deep_size`"),
     }
 }
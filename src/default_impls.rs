@@ -1,4 +1,4 @@
-use crate::{Context, DeepSizeOf};
+use crate::{sum_bounds, Context, DeepSizeOf};
 
 /// A macro to generate an impl for types with known inner allocation sizes.
 ///
@@ -32,6 +32,53 @@ macro_rules! known_deep_size (
     );
 );
 
+/// A macro to generate an impl for structs by summing the children of
+/// the listed fields.
+///
+/// This is useful for types from other crates that can't use the derive,
+/// such as messages generated by `prost`.  Every field that owns memory
+/// should be listed; unlisted fields are ignored.
+///
+/// Usage:
+/// ```rust
+/// # #[macro_use] extern crate deepsize; fn main() {
+/// struct Message {
+///     id: u64,
+///     name: String,
+///     tags: Vec<String>,
+/// }
+/// struct Wrapper<T>(T, Vec<u8>);
+///
+/// deep_size_fields!(Message { id, name, tags });
+/// deep_size_fields!({T: deepsize::DeepSizeOf} Wrapper<T> { 0, 1 });
+/// # }
+/// ```
+#[macro_export]
+macro_rules! deep_size_fields (
+    ($($({$($gen:tt)*})? $type:ty { $($field:tt),* $(,)? }),+ $(,)?) => (
+        $(
+            impl$(<$($gen)*>)? $crate::DeepSizeOf for $type {
+                fn deep_size_of_children(&self, context: &mut $crate::Context) -> usize {
+                    0 $( + $crate::DeepSizeOf::deep_size_of_children(&self.$field, context))*
+                }
+
+                fn deep_size_of_children_approx(
+                    &self,
+                    context: &mut $crate::Context,
+                ) -> (usize, usize) {
+                    let bounds = (0, 0);
+                    $(
+                        let (lower, upper) =
+                            $crate::DeepSizeOf::deep_size_of_children_approx(&self.$field, context);
+                        let bounds = (bounds.0 + lower, bounds.1 + upper);
+                    )*
+                    bounds
+                }
+            }
+        )+
+    );
+);
+
 use core::num;
 use core::sync::atomic;
 
@@ -79,13 +126,25 @@ known_deep_size!(0;
     {T} alloc::rc::Weak<T>,
 );
 
+known_deep_size!(0; core::ffi::CStr, core::time::Duration);
+
+impl DeepSizeOf for alloc::ffi::CString {
+    fn deep_size_of_children(&self, _: &mut Context) -> usize {
+        // This may cause a length check at runtime, but that
+        // doesn't seem avoidable.  This assumes that the allocation
+        // is the exact length of the string and the added null
+        // terminator.
+        self.as_bytes().len() + 1
+    }
+}
+
 #[cfg(feature = "std")]
 mod strings {
     use super::{Context, DeepSizeOf};
-    use std::ffi::{CStr, CString, OsStr, OsString};
+    use std::ffi::{OsStr, OsString};
     use std::path::{Path, PathBuf};
 
-    known_deep_size!(0; Path, OsStr, CStr);
+    known_deep_size!(0; Path, OsStr);
 
     impl DeepSizeOf for PathBuf {
         fn deep_size_of_children(&self, _: &mut Context) -> usize {
@@ -97,15 +156,6 @@ mod strings {
             self.capacity()
         }
     }
-    impl DeepSizeOf for CString {
-        fn deep_size_of_children(&self, _: &mut Context) -> usize {
-            // This may cause a length check at runtime, but that
-            // doesn't seem avoidable.  This assumes that the allocation
-            // is the exact length of the string and the added null
-            // terminator.
-            self.as_bytes().len() + 1
-        }
-    }
 }
 
 impl DeepSizeOf for alloc::string::String {
@@ -121,6 +171,13 @@ impl<T: DeepSizeOf> DeepSizeOf for core::option::Option<T> {
             None => 0,
         }
     }
+
+    fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+        match &self {
+            Some(t) => t.deep_size_of_children_approx(context),
+            None => (0, 0),
+        }
+    }
 }
 
 impl<R: DeepSizeOf, E: DeepSizeOf> DeepSizeOf for core::result::Result<R, E> {
@@ -130,11 +187,49 @@ impl<R: DeepSizeOf, E: DeepSizeOf> DeepSizeOf for core::result::Result<R, E> {
             Err(e) => e.deep_size_of_children(context),
         }
     }
+
+    fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+        match &self {
+            Ok(r) => r.deep_size_of_children_approx(context),
+            Err(e) => e.deep_size_of_children_approx(context),
+        }
+    }
+}
+
+impl<B> DeepSizeOf for alloc::borrow::Cow<'_, B>
+where
+    B: alloc::borrow::ToOwned + ?Sized,
+    B::Owned: DeepSizeOf,
+{
+    /// Borrowed data is treated like a reference and isn't counted
+    fn deep_size_of_children(&self, context: &mut Context) -> usize {
+        match self {
+            alloc::borrow::Cow::Borrowed(_) => 0,
+            alloc::borrow::Cow::Owned(owned) => owned.deep_size_of_children(context),
+        }
+    }
+
+    fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+        match self {
+            alloc::borrow::Cow::Borrowed(_) => (0, 0),
+            alloc::borrow::Cow::Owned(owned) => owned.deep_size_of_children_approx(context),
+        }
+    }
 }
 
 impl<T: DeepSizeOf> DeepSizeOf for core::cell::RefCell<T> {
+    /// If the `RefCell` is currently mutably borrowed, its contents can't
+    /// be read, so this returns 0 and the size may be underreported
     fn deep_size_of_children(&self, context: &mut Context) -> usize {
-        self.borrow().deep_size_of_children(context)
+        self.try_borrow()
+            .map(|s| s.deep_size_of_children(context))
+            .unwrap_or(0)
+    }
+
+    fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+        self.try_borrow()
+            .map(|s| s.deep_size_of_children_approx(context))
+            .unwrap_or((0, 0))
     }
 }
 
@@ -157,10 +252,10 @@ mod std_net {
 
 #[cfg(feature = "std")]
 mod std_time {
-    use std::time::{Duration, Instant, SystemTime, SystemTimeError};
+    use std::time::{Instant, SystemTime, SystemTimeError};
 
     known_deep_size!(0;
-        Instant, Duration, SystemTime, SystemTimeError
+        Instant, SystemTime, SystemTimeError
     );
 }
 
@@ -176,6 +271,23 @@ mod std_sync {
                 .map(|s| s.deep_size_of_children(context))
                 .unwrap_or(0)
         }
+
+        fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+            self.lock()
+                .map(|s| s.deep_size_of_children_approx(context))
+                .unwrap_or((0, 0))
+        }
+    }
+
+    impl<T: DeepSizeOf + ?Sized> DeepSizeOf for std::sync::MutexGuard<'_, T> {
+        /// The value is owned by the `Mutex`, so only its children are counted
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            (**self).deep_size_of_children(context)
+        }
+
+        fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+            (**self).deep_size_of_children_approx(context)
+        }
     }
 
     impl<T: DeepSizeOf> DeepSizeOf for std::sync::RwLock<T> {
@@ -186,13 +298,70 @@ mod std_sync {
                 .map(|s| s.deep_size_of_children(context))
                 .unwrap_or(0)
         }
+
+        fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+            self.read()
+                .map(|s| s.deep_size_of_children_approx(context))
+                .unwrap_or((0, 0))
+        }
     }
 }
 
+#[cfg(feature = "std")]
+mod std_io {
+    use crate::{Context, DeepSizeOf};
+
+    impl<T: DeepSizeOf, U: DeepSizeOf> DeepSizeOf for std::io::Chain<T, U> {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            let (first, second) = self.get_ref();
+            first.deep_size_of_children(context) + second.deep_size_of_children(context)
+        }
+
+        fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+            let (first, second) = self.get_ref();
+            crate::pair_bounds(first, second, context)
+        }
+    }
+}
+
+// `Any` trait objects are opaque, like `Error` trait objects below, so
+// only the size of the value itself is counted.
+known_deep_size!(0;
+    dyn core::any::Any,
+    dyn core::any::Any + Send,
+    dyn core::any::Any + Send + Sync,
+);
+
+#[cfg(feature = "std")]
+mod std_error {
+    use std::error::Error;
+
+    // Error trait objects are opaque, so only the size of the error value
+    // itself is counted (by the `Box` or other pointer holding it).
+    known_deep_size!(0;
+        dyn Error, dyn Error + Send, dyn Error + Send + Sync,
+    );
+}
+
 impl<T: DeepSizeOf, const N: usize> DeepSizeOf for [T; N] {
     fn deep_size_of_children(&self, context: &mut Context) -> usize {
         self.as_ref().deep_size_of_children(context)
     }
+
+    fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+        self.as_ref().deep_size_of_children_approx(context)
+    }
+}
+
+impl<T: DeepSizeOf, const N: usize> DeepSizeOf for core::array::IntoIter<T, N> {
+    /// Only the elements that haven't been yielded yet are counted
+    fn deep_size_of_children(&self, context: &mut Context) -> usize {
+        self.as_slice().deep_size_of_children(context)
+    }
+
+    fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+        self.as_slice().deep_size_of_children_approx(context)
+    }
 }
 
 macro_rules! deep_size_tuple {
@@ -203,6 +372,10 @@ macro_rules! deep_size_tuple {
             fn deep_size_of_children(&self, context: &mut Context) -> usize {
                 0 $( + self.$n.deep_size_of_children(context))+
             }
+
+            fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+                sum_bounds([$(self.$n.deep_size_of_children_approx(context)),+].iter().copied())
+            }
         }
     };
 }
@@ -247,3 +420,43 @@ deep_size_tuple!(
     (8, I),
     (9, J)
 );
+
+// Function pointers are a single pointer and never own anything, but a
+// blanket impl over all function pointer types isn't possible, so they
+// are implemented for each arity, for both safe and `unsafe` pointers with
+// the Rust and "C" ABIs.
+//
+// Higher-ranked pointers such as `fn(&str) -> bool` (which is really
+// `for<'a> fn(&'a str) -> bool`) are distinct types that these impls don't
+// cover, and adding impls for them would overlap with the ones below (see
+// the `coherence_leak_check` lint). Such callbacks need a newtype with a
+// manual impl.
+macro_rules! deep_size_fn_ptr {
+    ($($T:ident),*) => {
+        deep_size_fn_ptr!(@impl fn($($T,)*) -> R; $($T),*);
+        deep_size_fn_ptr!(@impl unsafe fn($($T,)*) -> R; $($T),*);
+        deep_size_fn_ptr!(@impl extern "C" fn($($T,)*) -> R; $($T),*);
+        deep_size_fn_ptr!(@impl unsafe extern "C" fn($($T,)*) -> R; $($T),*);
+    };
+    (@impl $ptr:ty; $($T:ident),*) => {
+        impl<R, $($T,)*> DeepSizeOf for $ptr {
+            fn deep_size_of_children(&self, _: &mut Context) -> usize {
+                0
+            }
+        }
+    };
+}
+
+deep_size_fn_ptr!();
+deep_size_fn_ptr!(A);
+deep_size_fn_ptr!(A, B);
+deep_size_fn_ptr!(A, B, C);
+deep_size_fn_ptr!(A, B, C, D);
+deep_size_fn_ptr!(A, B, C, D, E);
+deep_size_fn_ptr!(A, B, C, D, E, F);
+deep_size_fn_ptr!(A, B, C, D, E, F, G);
+deep_size_fn_ptr!(A, B, C, D, E, F, G, H);
+deep_size_fn_ptr!(A, B, C, D, E, F, G, H, I);
+deep_size_fn_ptr!(A, B, C, D, E, F, G, H, I, J);
+deep_size_fn_ptr!(A, B, C, D, E, F, G, H, I, J, K);
+deep_size_fn_ptr!(A, B, C, D, E, F, G, H, I, J, K, L);
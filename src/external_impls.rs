@@ -1,19 +1,150 @@
 #[cfg(feature = "slotmap")]
 mod slotmap_impl {
-    use crate::{known_deep_size, Context, DeepSizeOf};
-    use core::mem::size_of;
+    use crate::{hash_table_upper_bound, known_deep_size, sum_bounds, Context, DeepSizeOf};
+    use core::mem::{size_of, ManuallyDrop};
+    use core::num::NonZeroU32;
+    use slotmap::{DenseSlotMap, Key, SecondaryMap, SlotMap, SparseSecondaryMap};
 
     known_deep_size!(0; slotmap::KeyData, slotmap::DefaultKey);
 
-    impl<K, V> DeepSizeOf for slotmap::SlotMap<K, V>
+    // Mirrors `slotmap`'s internal slot for `SlotMap`
+    #[allow(dead_code)]
+    union MockSlotUnion<T> {
+        value: ManuallyDrop<T>,
+        next_free: u32,
+    }
+    struct MockSlot<T> {
+        _u: MockSlotUnion<T>,
+        _version: u32,
+    }
+
+    // Mirrors `slotmap`'s internal slot for `HopSlotMap`
+    #[allow(dead_code)]
+    union MockHopSlotUnion<T> {
+        value: ManuallyDrop<T>,
+        free: (u32, u32, u32),
+    }
+    struct MockHopSlot<T> {
+        _u: MockHopSlotUnion<T>,
+        _version: u32,
+    }
+
+    // Mirrors `slotmap`'s internal slot for `SecondaryMap`
+    enum MockSecondarySlot<T> {
+        _Occupied { value: T, version: NonZeroU32 },
+        _Vacant,
+    }
+
+    // Both `SlotMap` and `HopSlotMap` reserve an extra sentinel slot, which
+    // is not included in `capacity()`.  Vacant slots still take up the space
+    // of a value, but only occupied values can own any children.
+    impl<K, V> DeepSizeOf for SlotMap<K, V>
+    where
+        K: Key,
+        V: DeepSizeOf,
+    {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                let child_sizes = self
+                    .values()
+                    .fold(0, |sum, val| sum + val.deep_size_of_children(context));
+                child_sizes + (self.capacity() + 1) * size_of::<MockSlot<V>>()
+            })
+        }
+    }
+
+    #[allow(deprecated)]
+    impl<K, V> DeepSizeOf for slotmap::HopSlotMap<K, V>
+    where
+        K: Key,
+        V: DeepSizeOf,
+    {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                let child_sizes = self
+                    .values()
+                    .fold(0, |sum, val| sum + val.deep_size_of_children(context));
+                child_sizes + (self.capacity() + 1) * size_of::<MockHopSlot<V>>()
+            })
+        }
+    }
+
+    // `DenseSlotMap` keeps separate vectors of keys and values, plus a
+    // vector of (version, index) slots.  The slots vector can be larger
+    // than `capacity()` after removals, so this may underestimate slightly.
+    impl<K, V> DeepSizeOf for DenseSlotMap<K, V>
+    where
+        K: Key,
+        V: DeepSizeOf,
+    {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                let child_sizes = self
+                    .values()
+                    .fold(0, |sum, val| sum + val.deep_size_of_children(context));
+                let slot_size = size_of::<K>() + size_of::<V>() + size_of::<(u32, u32)>();
+                child_sizes + self.capacity() * slot_size
+            })
+        }
+
+        /// The length of the slots vector isn't exposed, so the upper bound
+        /// can't include the extra slots either.
+        fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+            context.descend(|context| {
+                let (lower, upper) = sum_bounds(
+                    self.values()
+                        .map(|val| val.deep_size_of_children_approx(context)),
+                );
+                let slot_size = size_of::<K>() + size_of::<V>() + size_of::<(u32, u32)>();
+                let slots = self.capacity() * slot_size;
+                (lower + slots, upper + slots)
+            })
+        }
+    }
+
+    impl<K, V> DeepSizeOf for SecondaryMap<K, V>
+    where
+        K: Key,
+        V: DeepSizeOf,
+    {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                let child_sizes = self
+                    .values()
+                    .fold(0, |sum, val| sum + val.deep_size_of_children(context));
+                child_sizes + (self.capacity() + 1) * size_of::<MockSecondarySlot<V>>()
+            })
+        }
+    }
+
+    // Backed by a `HashMap<u32, Slot<V>>`, where a slot is a version and a value
+    impl<K, V, S> DeepSizeOf for SparseSecondaryMap<K, V, S>
     where
-        K: DeepSizeOf + slotmap::Key,
-        V: DeepSizeOf + slotmap::Slottable,
+        K: Key,
+        V: DeepSizeOf,
+        S: core::hash::BuildHasher,
     {
         fn deep_size_of_children(&self, context: &mut Context) -> usize {
-            self.iter().fold(0, |sum, (key, val)| {
-                sum + key.deep_size_of_children(context) + val.deep_size_of_children(context)
-            }) + self.capacity() * size_of::<(u32, V)>()
+            context.descend(|context| {
+                let child_sizes = self
+                    .values()
+                    .fold(0, |sum, val| sum + val.deep_size_of_children(context));
+                child_sizes + self.capacity() * size_of::<(u32, (u32, V))>()
+            })
+        }
+
+        fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+            context.descend(|context| {
+                let (lower, upper) = sum_bounds(
+                    self.values()
+                        .map(|val| val.deep_size_of_children_approx(context)),
+                );
+                let slot_size = size_of::<(u32, (u32, V))>();
+                (
+                    lower + self.capacity() * slot_size,
+                    upper + hash_table_upper_bound(self.capacity(), slot_size + 1),
+                )
+            })
         }
     }
 }
@@ -34,11 +165,13 @@ mod slab_impl {
         T: DeepSizeOf,
     {
         fn deep_size_of_children(&self, context: &mut Context) -> usize {
-            let capacity_size = self.capacity() * size_of::<MockEntry<T>>();
-            let owned_size = self
-                .iter()
-                .fold(0, |sum, (_, val)| sum + val.deep_size_of_children(context));
-            capacity_size + owned_size
+            context.descend(|context| {
+                let capacity_size = self.capacity() * size_of::<MockEntry<T>>();
+                let owned_size = self
+                    .iter()
+                    .fold(0, |sum, (_, val)| sum + val.deep_size_of_children(context));
+                capacity_size + owned_size
+            })
         }
     }
 }
@@ -72,13 +205,16 @@ mod smallvec_impl {
         <A as smallvec::Array>::Item: DeepSizeOf,
     {
         fn deep_size_of_children(&self, context: &mut Context) -> usize {
-            let child_size = self
-                .iter()
-                .fold(0, |sum, elem| sum + elem.deep_size_of_children(context));
+            let children = |context: &mut Context| {
+                self.iter()
+                    .fold(0, |sum, elem| sum + elem.deep_size_of_children(context))
+            };
             if self.spilled() {
-                child_size + self.capacity() * size_of::<<A as smallvec::Array>::Item>()
+                context.descend(|context| {
+                    children(context) + self.capacity() * size_of::<<A as smallvec::Array>::Item>()
+                })
             } else {
-                child_size
+                children(context)
             }
         }
     }
@@ -86,44 +222,70 @@ mod smallvec_impl {
 
 #[cfg(feature = "hashbrown")]
 mod hashbrown_impl {
-    use crate::{Context, DeepSizeOf};
+    use crate::{hash_table_upper_bound, pair_bounds, sum_bounds, Context, DeepSizeOf};
     use core::mem::size_of;
 
-    // This is probably still incorrect, but it's better than before
+    // Like the std `HashMap`, each slot of the table holds a key and a
+    // value, and has one extra control byte.
     impl<K, V, S> DeepSizeOf for hashbrown::HashMap<K, V, S>
     where
-        K: DeepSizeOf + Eq + std::hash::Hash,
+        K: DeepSizeOf + Eq + core::hash::Hash,
         V: DeepSizeOf,
-        S: std::hash::BuildHasher,
+        S: core::hash::BuildHasher,
     {
         fn deep_size_of_children(&self, context: &mut Context) -> usize {
-            self.iter().fold(0, |sum, (key, val)| {
-                sum + key.deep_size_of_children(context) + val.deep_size_of_children(context)
-            }) + self.capacity() * size_of::<(K, V)>()
-            // Buckets would be the more correct value, but there isn't
-            // an API for accessing that with hashbrown.
-            // I believe that hashbrown's HashTable is represented as
-            // an array of (K, V), with control bytes at the start/end
-            // that mark used/uninitialized buckets (?)
+            context.descend(|context| {
+                self.iter().fold(0, |sum, (key, val)| {
+                    sum + key.deep_size_of_children(context) + val.deep_size_of_children(context)
+                }) + self.capacity() * (size_of::<K>() + size_of::<V>() + 1)
+            })
+        }
+
+        fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+            context.descend(|context| {
+                let (lower, upper) =
+                    sum_bounds(self.iter().map(|(key, val)| pair_bounds(key, val, context)));
+                let slot_size = size_of::<K>() + size_of::<V>() + 1;
+                (
+                    lower + self.capacity() * slot_size,
+                    upper + hash_table_upper_bound(self.capacity(), slot_size),
+                )
+            })
         }
     }
 
     impl<K, S> DeepSizeOf for hashbrown::HashSet<K, S>
     where
-        K: DeepSizeOf + Eq + std::hash::Hash,
-        S: std::hash::BuildHasher,
+        K: DeepSizeOf + Eq + core::hash::Hash,
+        S: core::hash::BuildHasher,
     {
         fn deep_size_of_children(&self, context: &mut Context) -> usize {
-            self.iter()
-                .fold(0, |sum, key| sum + key.deep_size_of_children(context))
-                + self.capacity() * size_of::<K>()
+            context.descend(|context| {
+                self.iter()
+                    .fold(0, |sum, key| sum + key.deep_size_of_children(context))
+                    + self.capacity() * (size_of::<K>() + 1)
+            })
+        }
+
+        fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+            context.descend(|context| {
+                let (lower, upper) = sum_bounds(
+                    self.iter()
+                        .map(|key| key.deep_size_of_children_approx(context)),
+                );
+                let slot_size = size_of::<K>() + 1;
+                (
+                    lower + self.capacity() * slot_size,
+                    upper + hash_table_upper_bound(self.capacity(), slot_size),
+                )
+            })
         }
     }
 }
 
 #[cfg(feature = "indexmap")]
 mod indexmap_impl {
-    use crate::{Context, DeepSizeOf};
+    use crate::{hash_table_upper_bound, pair_bounds, sum_bounds, Context, DeepSizeOf};
     use core::mem::size_of;
     use indexmap::{IndexMap, IndexSet};
 
@@ -131,18 +293,35 @@ mod indexmap_impl {
     // a hashbrown::RawTable<usize> for lookups.  This method will
     // consistently underestimate, because IndexMap::capacity will
     // return the min of the capacity of the buckets list and the
-    // capacity of the raw table.
+    // capacity of the raw table.  The upper bound counts every bucket of
+    // the raw table, including its control bytes.
     impl<K, V, S> DeepSizeOf for IndexMap<K, V, S>
     where
         K: DeepSizeOf,
         V: DeepSizeOf,
     {
         fn deep_size_of_children(&self, context: &mut Context) -> usize {
-            let child_sizes = self.iter().fold(0, |sum, (key, val)| {
-                sum + key.deep_size_of_children(context) + val.deep_size_of_children(context)
-            });
-            let map_size = self.capacity() * (size_of::<(usize, K, V)>() + size_of::<usize>());
-            child_sizes + map_size
+            context.descend(|context| {
+                let child_sizes = self.iter().fold(0, |sum, (key, val)| {
+                    sum + key.deep_size_of_children(context) + val.deep_size_of_children(context)
+                });
+                let map_size = self.capacity() * (size_of::<(usize, K, V)>() + size_of::<usize>());
+                child_sizes + map_size
+            })
+        }
+
+        fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+            context.descend(|context| {
+                let (lower, upper) =
+                    sum_bounds(self.iter().map(|(key, val)| pair_bounds(key, val, context)));
+                let entries = self.capacity() * size_of::<(usize, K, V)>();
+                (
+                    lower + entries + self.capacity() * size_of::<usize>(),
+                    upper
+                        + entries
+                        + hash_table_upper_bound(self.capacity(), size_of::<usize>() + 1),
+                )
+            })
         }
     }
     impl<K, S> DeepSizeOf for IndexSet<K, S>
@@ -150,11 +329,29 @@ mod indexmap_impl {
         K: DeepSizeOf,
     {
         fn deep_size_of_children(&self, context: &mut Context) -> usize {
-            let child_sizes = self
-                .iter()
-                .fold(0, |sum, key| sum + key.deep_size_of_children(context));
-            let map_size = self.capacity() * (size_of::<(usize, K, ())>() + size_of::<usize>());
-            child_sizes + map_size
+            context.descend(|context| {
+                let child_sizes = self
+                    .iter()
+                    .fold(0, |sum, key| sum + key.deep_size_of_children(context));
+                let map_size = self.capacity() * (size_of::<(usize, K, ())>() + size_of::<usize>());
+                child_sizes + map_size
+            })
+        }
+
+        fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+            context.descend(|context| {
+                let (lower, upper) = sum_bounds(
+                    self.iter()
+                        .map(|key| key.deep_size_of_children_approx(context)),
+                );
+                let entries = self.capacity() * size_of::<(usize, K, ())>();
+                (
+                    lower + entries + self.capacity() * size_of::<usize>(),
+                    upper
+                        + entries
+                        + hash_table_upper_bound(self.capacity(), size_of::<usize>() + 1),
+                )
+            })
         }
     }
 }
@@ -183,6 +380,80 @@ mod tokio_net_impl {
     );
 }
 
+#[cfg(feature = "tokio_sync")]
+mod tokio_sync_impl {
+    use crate::{known_deep_size, Context, DeepSizeOf};
+    use core::mem::{size_of, size_of_val};
+    use tokio::sync::{broadcast, mpsc, oneshot, watch, Mutex, RwLock};
+
+    impl<T: DeepSizeOf> DeepSizeOf for Mutex<T> {
+        /// This uses `try_lock`, so if the `Mutex` is currently locked
+        /// the contents can't be measured, and this returns 0
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                self.try_lock()
+                    .map(|s| s.deep_size_of_children(context))
+                    .unwrap_or(0)
+            })
+        }
+    }
+
+    impl<T: DeepSizeOf> DeepSizeOf for RwLock<T> {
+        /// This uses `try_read`, so if the `RwLock` is currently locked
+        /// for writing the contents can't be measured, and this returns 0
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                self.try_read()
+                    .map(|s| s.deep_size_of_children(context))
+                    .unwrap_or(0)
+            })
+        }
+    }
+
+    // Channel handles share their buffer with the other end of the
+    // channel, and the queued messages can't be accessed, so only the
+    // handles themselves are counted.
+    known_deep_size!(0;
+        {T} mpsc::Sender<T>, {T} mpsc::UnboundedSender<T>,
+        {T} mpsc::UnboundedReceiver<T>,
+        {T} oneshot::Sender<T>, {T} oneshot::Receiver<T>,
+        {T} broadcast::Sender<T>, {T} broadcast::Receiver<T>,
+    );
+
+    impl<T> DeepSizeOf for mpsc::Receiver<T> {
+        /// A bounded channel has a single receiver, which is counted as
+        /// owning the buffer.  This estimates the buffer as the maximum
+        /// capacity of the channel, although it is allocated in blocks
+        /// as messages are sent.
+        fn deep_size_of_children(&self, _context: &mut Context) -> usize {
+            self.max_capacity() * size_of::<T>()
+        }
+    }
+
+    // The value of a watch channel is shared between all of the senders
+    // and receivers, so it is only counted the first time it is seen.
+    fn watch_value_size<T: DeepSizeOf>(value: &T, context: &mut Context) -> usize {
+        if context.contains_arc_ptr(value) {
+            0
+        } else {
+            context.add_arc_ptr(value);
+            context.descend(|context| size_of_val(value) + value.deep_size_of_children(context))
+        }
+    }
+
+    impl<T: DeepSizeOf> DeepSizeOf for watch::Sender<T> {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            watch_value_size(&*self.borrow(), context)
+        }
+    }
+
+    impl<T: DeepSizeOf> DeepSizeOf for watch::Receiver<T> {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            watch_value_size(&*self.borrow(), context)
+        }
+    }
+}
+
 #[cfg(feature = "actix")]
 mod actix_impl {
     use crate::{Context, DeepSizeOf};
@@ -194,3 +465,4165 @@ mod actix_impl {
         }
     }
 }
+
+#[cfg(feature = "lru")]
+mod lru_impl {
+    use crate::{hash_table_upper_bound, pair_bounds, sum_bounds, Context, DeepSizeOf};
+    use core::hash::{BuildHasher, Hash};
+    use core::mem::size_of;
+    use lru::LruCache;
+
+    // This is an approximation of `lru`'s internals: each entry is a
+    // separately boxed `LruEntry` holding the key, the value, and prev/next
+    // pointers, with two extra sigil entries for the head and tail of the
+    // list.  The index is a hashbrown map from a key pointer to an entry
+    // pointer, with one control byte per slot, which is allocated up front
+    // for bounded caches.
+    fn map_capacity<K, V, S>(cache: &LruCache<K, V, S>) -> usize
+    where
+        K: Hash + Eq,
+        S: BuildHasher,
+    {
+        if cache.cap() == core::num::NonZeroUsize::MAX {
+            cache.len()
+        } else {
+            cache.cap().get().max(cache.len())
+        }
+    }
+
+    impl<K, V, S> DeepSizeOf for LruCache<K, V, S>
+    where
+        K: DeepSizeOf + Hash + Eq,
+        V: DeepSizeOf,
+        S: BuildHasher,
+    {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                let child_sizes = self.iter().fold(0, |sum, (key, val)| {
+                    sum + key.deep_size_of_children(context) + val.deep_size_of_children(context)
+                });
+                let entry_size = size_of::<(K, V, usize, usize)>();
+                child_sizes
+                    + (self.len() + 2) * entry_size
+                    + map_capacity(self) * (size_of::<(usize, usize)>() + 1)
+            })
+        }
+
+        /// The upper bound counts every bucket of the index's table.
+        fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+            context.descend(|context| {
+                let (lower, upper) =
+                    sum_bounds(self.iter().map(|(key, val)| pair_bounds(key, val, context)));
+                let entries = (self.len() + 2) * size_of::<(K, V, usize, usize)>();
+                let slot_size = size_of::<(usize, usize)>() + 1;
+                (
+                    lower + entries + map_capacity(self) * slot_size,
+                    upper + entries + hash_table_upper_bound(map_capacity(self), slot_size),
+                )
+            })
+        }
+    }
+}
+
+#[cfg(feature = "priority-queue")]
+mod priority_queue_impl {
+    use crate::{hash_table_upper_bound, pair_bounds, sum_bounds, Context, DeepSizeOf};
+    use core::mem::size_of;
+    use priority_queue::{DoublePriorityQueue, PriorityQueue};
+
+    // Both queues store the items in an `IndexMap<I, P>` (see the
+    // `indexmap` impl), plus two `Vec<usize>`s: the heap of indices, and
+    // the mapping from map indices back to heap positions.  These vecs
+    // grow alongside the map, so their capacity is approximated by the
+    // map's capacity.
+    fn store_size<I, P>(capacity: usize) -> usize {
+        let map_size = capacity * (size_of::<(usize, I, P)>() + size_of::<usize>());
+        map_size + 2 * capacity * size_of::<usize>()
+    }
+
+    /// Bounds the size of the store, where the upper bound counts every
+    /// bucket of the map's table
+    fn store_bounds<I, P>(capacity: usize) -> (usize, usize) {
+        let entries = capacity * size_of::<(usize, I, P)>() + 2 * capacity * size_of::<usize>();
+        (
+            store_size::<I, P>(capacity),
+            entries + hash_table_upper_bound(capacity, size_of::<usize>() + 1),
+        )
+    }
+
+    impl<I, P, H> DeepSizeOf for PriorityQueue<I, P, H>
+    where
+        I: DeepSizeOf,
+        P: DeepSizeOf,
+    {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                let child_sizes = self.iter().fold(0, |sum, (item, priority)| {
+                    sum + item.deep_size_of_children(context)
+                        + priority.deep_size_of_children(context)
+                });
+                child_sizes + store_size::<I, P>(self.capacity())
+            })
+        }
+
+        fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+            context.descend(|context| {
+                let (lower, upper) = sum_bounds(
+                    self.iter()
+                        .map(|(item, priority)| pair_bounds(item, priority, context)),
+                );
+                let (store_lower, store_upper) = store_bounds::<I, P>(self.capacity());
+                (lower + store_lower, upper + store_upper)
+            })
+        }
+    }
+
+    impl<I, P, H> DeepSizeOf for DoublePriorityQueue<I, P, H>
+    where
+        I: DeepSizeOf,
+        P: DeepSizeOf,
+    {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                let child_sizes = self.iter().fold(0, |sum, (item, priority)| {
+                    sum + item.deep_size_of_children(context)
+                        + priority.deep_size_of_children(context)
+                });
+                child_sizes + store_size::<I, P>(self.capacity())
+            })
+        }
+
+        fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+            context.descend(|context| {
+                let (lower, upper) = sum_bounds(
+                    self.iter()
+                        .map(|(item, priority)| pair_bounds(item, priority, context)),
+                );
+                let (store_lower, store_upper) = store_bounds::<I, P>(self.capacity());
+                (lower + store_lower, upper + store_upper)
+            })
+        }
+    }
+}
+
+#[cfg(feature = "petgraph")]
+mod petgraph_impl {
+    use crate::{Context, DeepSizeOf};
+    use core::hash::BuildHasher;
+    use core::mem::size_of;
+    use petgraph::graph::{Edge, Graph, IndexType, Node};
+    use petgraph::graphmap::{GraphMap, NodeTrait};
+    use petgraph::stable_graph::StableGraph;
+    use petgraph::EdgeType;
+
+    // The node and edge vectors store the weights alongside the indices
+    // of the next edges (and for edges, the endpoints).
+    impl<N, E, Ty, Ix> DeepSizeOf for Graph<N, E, Ty, Ix>
+    where
+        N: DeepSizeOf,
+        E: DeepSizeOf,
+        Ty: EdgeType,
+        Ix: IndexType,
+    {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                let node_sizes = self.raw_nodes().iter().fold(0, |sum, node| {
+                    sum + node.weight.deep_size_of_children(context)
+                });
+                let edge_sizes = self.raw_edges().iter().fold(0, |sum, edge| {
+                    sum + edge.weight.deep_size_of_children(context)
+                });
+                let (node_capacity, edge_capacity) = self.capacity();
+                node_sizes
+                    + edge_sizes
+                    + node_capacity * size_of::<Node<N, Ix>>()
+                    + edge_capacity * size_of::<Edge<E, Ix>>()
+            })
+        }
+    }
+
+    // A `StableGraph` is a `Graph<Option<N>, Option<E>>`, where removed
+    // nodes and edges are left as vacant `None` slots.
+    impl<N, E, Ty, Ix> DeepSizeOf for StableGraph<N, E, Ty, Ix>
+    where
+        N: DeepSizeOf,
+        E: DeepSizeOf,
+        Ty: EdgeType,
+        Ix: IndexType,
+    {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                let node_sizes = self
+                    .node_weights()
+                    .fold(0, |sum, weight| sum + weight.deep_size_of_children(context));
+                let edge_sizes = self
+                    .edge_weights()
+                    .fold(0, |sum, weight| sum + weight.deep_size_of_children(context));
+                let (node_capacity, edge_capacity) = self.capacity();
+                node_sizes
+                    + edge_sizes
+                    + node_capacity * size_of::<Node<Option<N>, Ix>>()
+                    + edge_capacity * size_of::<Edge<Option<E>, Ix>>()
+            })
+        }
+    }
+
+    // A `GraphMap` is an `IndexMap` of nodes to adjacency lists, and an
+    // `IndexMap` of node pairs to edge weights (see the `indexmap` impl).
+    // Each edge appears in the adjacency lists of both of its endpoints,
+    // and the capacity of those lists is approximated by their length.
+    impl<N, E, Ty, S> DeepSizeOf for GraphMap<N, E, Ty, S>
+    where
+        N: NodeTrait,
+        E: DeepSizeOf,
+        Ty: EdgeType,
+        S: BuildHasher,
+    {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                let edge_sizes = self.all_edges().fold(0, |sum, (_, _, weight)| {
+                    sum + weight.deep_size_of_children(context)
+                });
+                let (node_capacity, edge_capacity) = self.capacity();
+                let node_map_size = node_capacity
+                    * (size_of::<(usize, N, alloc::vec::Vec<(N, u8)>)>() + size_of::<usize>());
+                let edge_map_size =
+                    edge_capacity * (size_of::<(usize, (N, N), E)>() + size_of::<usize>());
+                let adjacency_size = 2 * self.edge_count() * size_of::<(N, u8)>();
+                edge_sizes + node_map_size + edge_map_size + adjacency_size
+            })
+        }
+    }
+}
+
+#[cfg(feature = "ndarray")]
+mod ndarray_impl {
+    use crate::{Context, DeepSizeOf};
+    use core::mem::size_of;
+    use ndarray::{ArcArray, Array, ArrayView, ArrayViewMut, CowArray, Dimension};
+
+    // The length of the buffer isn't accessible, so this assumes that it
+    // is the same as the number of elements in the array, which is true
+    // unless the array was sliced in place.
+    fn owned_size<'a, A, I>(len: usize, elements: I, context: &mut Context) -> usize
+    where
+        A: DeepSizeOf + 'a,
+        I: Iterator<Item = &'a A>,
+    {
+        context.descend(|context| {
+            let child_sizes =
+                elements.fold(0, |sum, elem| sum + elem.deep_size_of_children(context));
+            child_sizes + len * size_of::<A>()
+        })
+    }
+
+    impl<A, D> DeepSizeOf for Array<A, D>
+    where
+        A: DeepSizeOf,
+        D: Dimension,
+    {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            owned_size(self.len(), self.iter(), context)
+        }
+    }
+
+    // The shared buffer would ideally be tracked by the pointer to its
+    // allocation, but `ndarray` doesn't expose the array's representation,
+    // so it is tracked by the pointer to the array's first element instead.
+    // Clones of an `ArcArray` are only counted once, but a clone sliced to
+    // start at a different element (such as with `slice_move`) can't be
+    // matched, so it counts the elements that it can see.
+    impl<A, D> DeepSizeOf for ArcArray<A, D>
+    where
+        A: DeepSizeOf,
+        D: Dimension,
+    {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            let ptr = self.as_ptr();
+            if context.contains_arc_ptr(ptr) {
+                0
+            } else {
+                context.add_arc_ptr(ptr);
+                owned_size(self.len(), self.iter(), context)
+            }
+        }
+    }
+
+    impl<'a, A, D> DeepSizeOf for CowArray<'a, A, D>
+    where
+        A: DeepSizeOf,
+        D: Dimension,
+    {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            if self.is_view() {
+                0
+            } else {
+                owned_size(self.len(), self.iter(), context)
+            }
+        }
+    }
+
+    // Views borrow their data, like references
+    impl<'a, A, D> DeepSizeOf for ArrayView<'a, A, D>
+    where
+        D: Dimension,
+    {
+        fn deep_size_of_children(&self, _context: &mut Context) -> usize {
+            0
+        }
+    }
+
+    impl<'a, A, D> DeepSizeOf for ArrayViewMut<'a, A, D>
+    where
+        D: Dimension,
+    {
+        fn deep_size_of_children(&self, _context: &mut Context) -> usize {
+            0
+        }
+    }
+}
+
+#[cfg(feature = "nalgebra")]
+mod nalgebra_impl {
+    use crate::{Context, DeepSizeOf};
+    use core::mem::size_of_val;
+    use nalgebra::base::{ArrayStorage, Dim, Matrix, VecStorage, ViewStorage, ViewStorageMut};
+
+    // All of the allocation of a matrix is in its storage
+    impl<T, R, C, S> DeepSizeOf for Matrix<T, R, C, S>
+    where
+        S: DeepSizeOf,
+    {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            self.data.deep_size_of_children(context)
+        }
+    }
+
+    // Statically sized matrices are stored inline
+    impl<T, const R: usize, const C: usize> DeepSizeOf for ArrayStorage<T, R, C>
+    where
+        T: DeepSizeOf,
+    {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            self.0.deep_size_of_children(context)
+        }
+    }
+
+    impl<T, R, C> DeepSizeOf for VecStorage<T, R, C>
+    where
+        T: DeepSizeOf,
+        R: Dim,
+        C: Dim,
+    {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                let elements = self.as_slice();
+                elements.deep_size_of_children(context) + size_of_val(elements)
+            })
+        }
+    }
+
+    // Views borrow their data, like references
+    impl<'a, T, R, C, RStride, CStride> DeepSizeOf for ViewStorage<'a, T, R, C, RStride, CStride>
+    where
+        R: Dim,
+        C: Dim,
+        RStride: Dim,
+        CStride: Dim,
+    {
+        fn deep_size_of_children(&self, _context: &mut Context) -> usize {
+            0
+        }
+    }
+
+    impl<'a, T, R, C, RStride, CStride> DeepSizeOf for ViewStorageMut<'a, T, R, C, RStride, CStride>
+    where
+        R: Dim,
+        C: Dim,
+        RStride: Dim,
+        CStride: Dim,
+    {
+        fn deep_size_of_children(&self, _context: &mut Context) -> usize {
+            0
+        }
+    }
+}
+
+#[cfg(feature = "nalgebra-sparse")]
+mod nalgebra_sparse_impl {
+    use crate::{Context, DeepSizeOf};
+    use core::mem::{size_of, size_of_val};
+    use nalgebra_sparse::{CooMatrix, CscMatrix, CsrMatrix};
+
+    // Compressed matrices store the offsets of each major lane, the minor
+    // index of each entry, and the values of each entry.
+    impl<T: DeepSizeOf> DeepSizeOf for CsrMatrix<T> {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                let values = self.values();
+                values.deep_size_of_children(context)
+                    + size_of_val(values)
+                    + (self.row_offsets().len() + self.col_indices().len()) * size_of::<usize>()
+            })
+        }
+    }
+
+    impl<T: DeepSizeOf> DeepSizeOf for CscMatrix<T> {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                let values = self.values();
+                values.deep_size_of_children(context)
+                    + size_of_val(values)
+                    + (self.col_offsets().len() + self.row_indices().len()) * size_of::<usize>()
+            })
+        }
+    }
+
+    impl<T: DeepSizeOf> DeepSizeOf for CooMatrix<T> {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                let values = self.values();
+                values.deep_size_of_children(context)
+                    + size_of_val(values)
+                    + (self.row_indices().len() + self.col_indices().len()) * size_of::<usize>()
+            })
+        }
+    }
+}
+
+#[cfg(feature = "smartstring")]
+mod smartstring_impl {
+    use crate::{Context, DeepSizeOf};
+    use smartstring::{SmartString, SmartStringMode};
+
+    impl<Mode: SmartStringMode> DeepSizeOf for SmartString<Mode> {
+        fn deep_size_of_children(&self, _context: &mut Context) -> usize {
+            if self.is_inline() {
+                0
+            } else {
+                self.capacity()
+            }
+        }
+    }
+}
+
+#[cfg(feature = "bstr")]
+mod bstr_impl {
+    use crate::{known_deep_size, Context, DeepSizeOf};
+    use bstr::{BStr, BString};
+
+    known_deep_size!(0; BStr);
+
+    impl DeepSizeOf for BString {
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            self.capacity()
+        }
+    }
+}
+
+#[cfg(feature = "beef")]
+mod beef_impl {
+    use crate::{Context, DeepSizeOf};
+    use core::mem::size_of_val;
+
+    // beef's `Beef` and `Capacity` traits are private, so the impls can only
+    // be written for the concrete `str` and `[T]` flavors.  The owned capacity
+    // isn't exposed either, so owned values are counted by their length.
+    macro_rules! beef_cow_impls {
+        ($cow:ident) => {
+            impl DeepSizeOf for $cow<'_, str> {
+                /// Borrowed data is treated like a reference and isn't counted
+                fn deep_size_of_children(&self, _: &mut Context) -> usize {
+                    if self.is_owned() {
+                        self.len()
+                    } else {
+                        0
+                    }
+                }
+            }
+
+            impl<T: Clone + DeepSizeOf> DeepSizeOf for $cow<'_, [T]> {
+                /// Borrowed data is treated like a reference and isn't counted
+                fn deep_size_of_children(&self, context: &mut Context) -> usize {
+                    context.descend(|context| {
+                        if self.is_owned() {
+                            let slice: &[T] = self;
+                            size_of_val(slice)
+                                + slice
+                                    .iter()
+                                    .map(|item| item.deep_size_of_children(context))
+                                    .sum::<usize>()
+                        } else {
+                            0
+                        }
+                    })
+                }
+            }
+        };
+    }
+
+    use beef::Cow as WideCow;
+    beef_cow_impls!(WideCow);
+
+    #[cfg(target_pointer_width = "64")]
+    use beef::lean::Cow as LeanCow;
+    #[cfg(target_pointer_width = "64")]
+    beef_cow_impls!(LeanCow);
+}
+
+#[cfg(feature = "heapless")]
+mod heapless_impl {
+    use crate::{Context, DeepSizeOf};
+    use heapless::{Deque, IndexMap, LenType, String, Vec};
+
+    // heapless collections store their elements inline and never allocate,
+    // so only the children of the occupied elements are counted.
+
+    impl<T: DeepSizeOf, const N: usize, LenT: LenType> DeepSizeOf for Vec<T, N, LenT> {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                self.iter()
+                    .map(|child| child.deep_size_of_children(context))
+                    .sum()
+            })
+        }
+    }
+
+    impl<const N: usize, LenT: LenType> DeepSizeOf for String<N, LenT> {
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            0
+        }
+    }
+
+    impl<T: DeepSizeOf, const N: usize> DeepSizeOf for Deque<T, N> {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                self.iter()
+                    .map(|child| child.deep_size_of_children(context))
+                    .sum()
+            })
+        }
+    }
+
+    impl<K, V, S, const N: usize> DeepSizeOf for IndexMap<K, V, S, N>
+    where
+        K: DeepSizeOf,
+        V: DeepSizeOf,
+    {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                self.iter()
+                    .map(|(key, val)| {
+                        key.deep_size_of_children(context) + val.deep_size_of_children(context)
+                    })
+                    .sum()
+            })
+        }
+    }
+}
+
+#[cfg(feature = "enum-map")]
+mod enum_map_impl {
+    use crate::{Context, DeepSizeOf};
+    use enum_map::{EnumArray, EnumMap};
+
+    impl<K: EnumArray<V>, V: DeepSizeOf> DeepSizeOf for EnumMap<K, V> {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                self.values()
+                    .map(|child| child.deep_size_of_children(context))
+                    .sum()
+            })
+        }
+    }
+}
+
+#[cfg(feature = "fixedbitset")]
+mod fixedbitset_impl {
+    use crate::{Context, DeepSizeOf};
+    use core::mem::size_of_val;
+    use fixedbitset::FixedBitSet;
+
+    impl DeepSizeOf for FixedBitSet {
+        /// The bits are stored in blocks, so the length is rounded up to a
+        /// whole number of blocks.  The spare capacity isn't exposed, so it
+        /// isn't counted.
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            size_of_val(self.as_slice())
+        }
+    }
+}
+
+#[cfg(feature = "ahash")]
+mod ahash_impl {
+    use crate::known_deep_size;
+
+    // The hashing keys are stored inline
+    known_deep_size!(0; ahash::RandomState);
+}
+
+#[cfg(feature = "rustc-hash")]
+mod rustc_hash_impl {
+    use crate::known_deep_size;
+
+    known_deep_size!(0; rustc_hash::FxBuildHasher);
+}
+
+#[cfg(feature = "fnv")]
+mod fnv_impl {
+    use crate::known_deep_size;
+
+    known_deep_size!(0; fnv::FnvBuildHasher);
+}
+
+#[cfg(feature = "lasso")]
+mod lasso_impl {
+    use crate::{known_deep_size, Context, DeepSizeOf};
+    use core::hash::{BuildHasher, Hash};
+    use core::mem::size_of;
+    use lasso::{Key, Rodeo, RodeoReader, RodeoResolver, ThreadedRodeo};
+
+    known_deep_size!(0; lasso::Spur, lasso::LargeSpur, lasso::MiniSpur, lasso::MicroSpur);
+
+    // The interned strings are stored in an arena, and are counted by their
+    // length; the arena may have some unused space at the end of each bucket.
+    // Each interner also has a `Vec<&str>` for key -> string lookup, and (other
+    // than the resolver) a `HashMap<K, ()>` for string -> key lookup, with one
+    // control byte per entry.
+
+    fn interned_size<'a>(strings: impl Iterator<Item = &'a str>) -> usize {
+        strings.map(str::len).sum()
+    }
+
+    impl<K: Key, S> DeepSizeOf for Rodeo<K, S> {
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            interned_size(self.strings())
+                + self.capacity() * size_of::<&str>()
+                + self.len() * (size_of::<K>() + 1)
+        }
+    }
+
+    impl<K: Key, S> DeepSizeOf for RodeoReader<K, S> {
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            interned_size(self.strings()) + self.len() * (size_of::<&str>() + size_of::<K>() + 1)
+        }
+    }
+
+    impl<K: Key> DeepSizeOf for RodeoResolver<K> {
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            interned_size(self.strings()) + self.len() * size_of::<&str>()
+        }
+    }
+
+    impl<K, S> DeepSizeOf for ThreadedRodeo<K, S>
+    where
+        K: Key + Hash,
+        S: BuildHasher + Clone,
+    {
+        /// A `ThreadedRodeo` uses two `DashMap`s, one in each direction
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            interned_size(self.strings()) + self.len() * 2 * (size_of::<(&str, K)>() + 1)
+        }
+    }
+}
+
+#[cfg(all(feature = "anyhow", feature = "std"))]
+mod anyhow_impl {
+    use crate::{Context, DeepSizeOf};
+    use core::mem::{size_of, size_of_val};
+
+    impl DeepSizeOf for anyhow::Error {
+        /// Each error in the chain is counted as a separate allocation,
+        /// along with anyhow's header (a vtable pointer and an optional
+        /// backtrace).  This is exact for chains built with `context`, but
+        /// a custom error that stores its source inline will have the
+        /// source counted twice.  Captured backtrace frames aren't counted.
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            let header = size_of::<usize>() + size_of::<Option<std::backtrace::Backtrace>>();
+            self.chain().map(|error| header + size_of_val(error)).sum()
+        }
+    }
+}
+
+#[cfg(feature = "arrow")]
+mod arrow_impl {
+    use crate::{Context, DeepSizeOf};
+    use arrow_array::{Array, RecordBatch};
+    use arrow_buffer::{Buffer, MutableBuffer};
+    use arrow_data::ArrayData;
+    use core::mem::size_of_val;
+
+    impl DeepSizeOf for Buffer {
+        /// Buffers are reference counted and can be sliced, so they are
+        /// tracked by the start of the shared allocation and only counted
+        /// once.  Externally owned buffers report a capacity of zero.
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            let ptr = self.data_ptr().as_ptr() as *const u8;
+            if context.contains_arc_ptr(ptr) {
+                0
+            } else {
+                context.add_arc_ptr(ptr);
+                self.capacity()
+            }
+        }
+    }
+
+    impl DeepSizeOf for MutableBuffer {
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            self.capacity()
+        }
+    }
+
+    impl DeepSizeOf for ArrayData {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                let buffers = self.buffers();
+                let child_data = self.child_data();
+                size_of_val(buffers)
+                    + buffers
+                        .iter()
+                        .map(|buffer| buffer.deep_size_of_children(context))
+                        .sum::<usize>()
+                    + size_of_val(child_data)
+                    + child_data
+                        .iter()
+                        .map(|child| child.deep_size_of_children(context))
+                        .sum::<usize>()
+                    + self
+                        .nulls()
+                        .map_or(0, |nulls| nulls.buffer().deep_size_of_children(context))
+            })
+        }
+    }
+
+    impl DeepSizeOf for dyn Array {
+        /// Measured through the array's `ArrayData`, so that buffers shared
+        /// with other arrays are only counted once
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            self.to_data().deep_size_of_children(context)
+        }
+    }
+
+    impl DeepSizeOf for RecordBatch {
+        /// The schema is shared and usually small, so it isn't counted
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                let columns = self.columns();
+                size_of_val(columns)
+                    + columns
+                        .iter()
+                        .map(|column| column.deep_size_of_children(context))
+                        .sum::<usize>()
+            })
+        }
+    }
+}
+
+#[cfg(feature = "halfbrown")]
+mod halfbrown_impl {
+    use crate::{hash_table_upper_bound, pair_bounds, sum_bounds, Context, DeepSizeOf};
+    use core::mem::size_of;
+    use halfbrown::SizedHashMap;
+
+    impl<K, V, S, const VEC_LIMIT_UPPER: usize> DeepSizeOf for SizedHashMap<K, V, S, VEC_LIMIT_UPPER>
+    where
+        K: DeepSizeOf + Eq + core::hash::Hash,
+        V: DeepSizeOf,
+        S: core::hash::BuildHasher,
+    {
+        /// Small maps are stored in a `Vec<(K, V)>`, and larger maps use a
+        /// hashbrown table (with one control byte per slot).
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                let slot_size = if self.is_vec() {
+                    size_of::<(K, V)>()
+                } else {
+                    size_of::<K>() + size_of::<V>() + 1
+                };
+                self.iter().fold(0, |sum, (key, val)| {
+                    sum + key.deep_size_of_children(context) + val.deep_size_of_children(context)
+                }) + self.capacity() * slot_size
+            })
+        }
+
+        /// The size of the `Vec` is exact, and the table is bounded like a
+        /// hashbrown `HashMap`.
+        fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+            context.descend(|context| {
+                let (lower, upper) =
+                    sum_bounds(self.iter().map(|(key, val)| pair_bounds(key, val, context)));
+                if self.is_vec() {
+                    let entries = self.capacity() * size_of::<(K, V)>();
+                    (lower + entries, upper + entries)
+                } else {
+                    let slot_size = size_of::<K>() + size_of::<V>() + 1;
+                    (
+                        lower + self.capacity() * slot_size,
+                        upper + hash_table_upper_bound(self.capacity(), slot_size),
+                    )
+                }
+            })
+        }
+    }
+}
+
+#[cfg(feature = "thin-vec")]
+mod thin_vec_impl {
+    use crate::{Context, DeepSizeOf};
+    use core::mem::{align_of, size_of};
+    use thin_vec::ThinVec;
+
+    /// The size of the length and capacity header, plus any padding needed
+    /// to align the elements.
+    ///
+    /// With thin-vec's `gecko-ffi` feature the header is two `u32`s (to match
+    /// `nsTArray`), rather than two `usize`s.  That feature also removes the
+    /// special case for zero sized types, which is used to detect it here.
+    fn header_size<T>() -> usize {
+        let header_field = if ThinVec::<()>::new().capacity() == 0 {
+            size_of::<u32>()
+        } else {
+            size_of::<usize>()
+        };
+        let header_size = 2 * header_field;
+        header_size
+            + align_of::<T>()
+                .max(header_field)
+                .saturating_sub(header_size)
+    }
+
+    impl<T: DeepSizeOf> DeepSizeOf for ThinVec<T> {
+        /// Empty `ThinVec`s share a static header, and zero sized types are
+        /// never allocated, so neither owns any memory
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                if size_of::<T>() == 0 || !self.has_capacity() {
+                    return 0;
+                }
+                self.iter()
+                    .map(|child| child.deep_size_of_children(context))
+                    .sum::<usize>()
+                    + header_size::<T>()
+                    + self.capacity() * size_of::<T>()
+            })
+        }
+    }
+}
+
+#[cfg(feature = "crossbeam-queue")]
+mod crossbeam_queue_impl {
+    use crate::{Context, DeepSizeOf};
+    use core::mem::{size_of, MaybeUninit};
+    use crossbeam_queue::{ArrayQueue, SegQueue};
+
+    // Mirrors `crossbeam_queue`'s internal `Slot`
+    struct MockSlot<T> {
+        #[cfg(target_has_atomic = "64")]
+        _stamp: u64,
+        #[cfg(not(target_has_atomic = "64"))]
+        _stamp: usize,
+        _value: MaybeUninit<T>,
+    }
+
+    impl<T: DeepSizeOf> DeepSizeOf for ArrayQueue<T> {
+        /// The elements can't be accessed without popping them from the
+        /// queue, so only the slots are counted, and not the children of
+        /// any queued elements.
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            self.capacity() * size_of::<MockSlot<T>>()
+        }
+    }
+
+    // Mirrors `crossbeam_queue`'s internal `Block` for `SegQueue`
+    const BLOCK_CAP: usize = 31;
+    struct MockBlock<T> {
+        _next: usize,
+        _slots: [(MaybeUninit<T>, usize); BLOCK_CAP],
+    }
+
+    impl<T: DeepSizeOf> DeepSizeOf for SegQueue<T> {
+        /// Queued elements are stored in blocks of 31, so this is estimated
+        /// from the length, counting the partially filled blocks at each
+        /// end.  As with `ArrayQueue`, the children of the elements are not
+        /// counted.
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            let blocks = match self.len() {
+                0 => 0,
+                len => len / BLOCK_CAP + 1,
+            };
+            blocks * size_of::<MockBlock<T>>()
+        }
+
+        /// At best the elements fill whole blocks, and at worst they start
+        /// at the end of one block and spill into another at the end.
+        fn deep_size_of_children_approx(&self, _: &mut Context) -> (usize, usize) {
+            let len = self.len();
+            (
+                len.div_ceil(BLOCK_CAP) * size_of::<MockBlock<T>>(),
+                (len / BLOCK_CAP + 2) * size_of::<MockBlock<T>>(),
+            )
+        }
+    }
+}
+
+#[cfg(feature = "crossbeam-channel")]
+mod crossbeam_channel_impl {
+    use crate::{Context, DeepSizeOf};
+    use core::mem::{size_of, MaybeUninit};
+    use crossbeam_channel::{Receiver, Sender};
+
+    // Mirrors the internal `Slot` of bounded channels
+    struct MockSlot<T> {
+        #[cfg(target_has_atomic = "64")]
+        _stamp: u64,
+        #[cfg(not(target_has_atomic = "64"))]
+        _stamp: usize,
+        _value: MaybeUninit<T>,
+    }
+
+    impl<T> DeepSizeOf for Sender<T> {
+        /// Senders are handles to a channel that is counted by its receiver
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            0
+        }
+    }
+
+    impl<T> DeepSizeOf for Receiver<T> {
+        /// Bounded channels count their buffer, which is attributed to each
+        /// receiver; the messages can't be accessed without receiving them,
+        /// so their children are not counted.  Unbounded channels are only
+        /// counted as a handle.
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            match self.capacity() {
+                Some(capacity) => capacity * size_of::<MockSlot<T>>(),
+                None => 0,
+            }
+        }
+    }
+}
+
+#[cfg(feature = "arc-swap")]
+mod arc_swap_impl {
+    use crate::{Context, DeepSizeOf};
+    use arc_swap::{strategy::Strategy, ArcSwapAny, RefCnt};
+
+    impl<T, S> DeepSizeOf for ArcSwapAny<T, S>
+    where
+        T: RefCnt + DeepSizeOf,
+        S: Strategy<T>,
+    {
+        /// Measures the currently stored `Arc` (or `Option<Arc>`), so that
+        /// it's only counted once if it's also held elsewhere
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| self.load_full().deep_size_of_children(context))
+        }
+    }
+}
+
+#[cfg(feature = "once_cell")]
+mod once_cell_impl {
+    use crate::{Context, DeepSizeOf};
+    use once_cell::{sync, unsync};
+
+    // The cells are measured with `get`, so an uninitialized cell only
+    // counts its inline storage, and a `Lazy` is never forced.
+
+    impl<T: DeepSizeOf> DeepSizeOf for unsync::OnceCell<T> {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                self.get()
+                    .map_or(0, |val| val.deep_size_of_children(context))
+            })
+        }
+    }
+
+    impl<T: DeepSizeOf> DeepSizeOf for sync::OnceCell<T> {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                self.get()
+                    .map_or(0, |val| val.deep_size_of_children(context))
+            })
+        }
+    }
+
+    impl<T: DeepSizeOf, F: FnOnce() -> T> DeepSizeOf for unsync::Lazy<T, F> {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                unsync::Lazy::get(self).map_or(0, |val| val.deep_size_of_children(context))
+            })
+        }
+    }
+
+    impl<T: DeepSizeOf, F: FnOnce() -> T> DeepSizeOf for sync::Lazy<T, F> {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                sync::Lazy::get(self).map_or(0, |val| val.deep_size_of_children(context))
+            })
+        }
+    }
+}
+
+#[cfg(feature = "generic-array")]
+mod generic_array_impl {
+    use crate::{Context, DeepSizeOf};
+    use generic_array::{ArrayLength, GenericArray};
+
+    impl<T: DeepSizeOf, N: ArrayLength> DeepSizeOf for GenericArray<T, N> {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| self.as_slice().deep_size_of_children(context))
+        }
+    }
+}
+
+#[cfg(feature = "ipnet")]
+mod ipnet_impl {
+    use crate::known_deep_size;
+    use ipnet::*;
+
+    known_deep_size!(0;
+        IpNet, Ipv4Net, Ipv6Net,
+        IpAddrRange, Ipv4AddrRange, Ipv6AddrRange,
+        IpSubnets, Ipv4Subnets, Ipv6Subnets,
+        PrefixLenError, AddrParseError,
+    );
+}
+
+#[cfg(feature = "semver")]
+mod semver_impl {
+    use crate::{known_deep_size, Context, DeepSizeOf};
+    use semver::{BuildMetadata, Comparator, Op, Prerelease, Version, VersionReq};
+
+    known_deep_size!(0; Op);
+
+    /// The identifiers are stored inline when they are at most 8 bytes
+    /// long; longer identifiers are approximated by their length, which
+    /// ignores the small length header at the start of the allocation.
+    fn identifier_size(identifier: &str) -> usize {
+        if identifier.len() <= 8 {
+            0
+        } else {
+            identifier.len()
+        }
+    }
+
+    impl DeepSizeOf for Prerelease {
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            identifier_size(self.as_str())
+        }
+    }
+
+    impl DeepSizeOf for BuildMetadata {
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            identifier_size(self.as_str())
+        }
+    }
+
+    impl DeepSizeOf for Version {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            self.pre.deep_size_of_children(context) + self.build.deep_size_of_children(context)
+        }
+    }
+
+    impl DeepSizeOf for Comparator {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            self.pre.deep_size_of_children(context)
+        }
+    }
+
+    impl DeepSizeOf for VersionReq {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            self.comparators.deep_size_of_children(context)
+        }
+    }
+}
+
+#[cfg(feature = "http")]
+mod http_impl {
+    use crate::{known_deep_size, Context, DeepSizeOf};
+    use core::mem::size_of;
+    use http::header::{HeaderMap, HeaderName, HeaderValue};
+    use http::uri::{Scheme, Uri};
+    use http::{Method, StatusCode, Version};
+
+    // Custom methods longer than 15 bytes are allocated, but they are rare
+    // enough to ignore.
+    known_deep_size!(0; Method, StatusCode, Version);
+
+    /// Standard header names are static and own nothing, while custom names
+    /// own their bytes.
+    impl DeepSizeOf for HeaderName {
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            if is_standard(self) {
+                0
+            } else {
+                self.as_str().len()
+            }
+        }
+    }
+
+    // `HeaderName` doesn't expose whether it is one of the standard names,
+    // so compare against the constants in `http::header`.
+    fn is_standard(name: &HeaderName) -> bool {
+        use http::header::*;
+        static STANDARD: [HeaderName; 81] = [
+            ACCEPT,
+            ACCEPT_CHARSET,
+            ACCEPT_ENCODING,
+            ACCEPT_LANGUAGE,
+            ACCEPT_RANGES,
+            ACCESS_CONTROL_ALLOW_CREDENTIALS,
+            ACCESS_CONTROL_ALLOW_HEADERS,
+            ACCESS_CONTROL_ALLOW_METHODS,
+            ACCESS_CONTROL_ALLOW_ORIGIN,
+            ACCESS_CONTROL_EXPOSE_HEADERS,
+            ACCESS_CONTROL_MAX_AGE,
+            ACCESS_CONTROL_REQUEST_HEADERS,
+            ACCESS_CONTROL_REQUEST_METHOD,
+            AGE,
+            ALLOW,
+            ALT_SVC,
+            AUTHORIZATION,
+            CACHE_CONTROL,
+            CACHE_STATUS,
+            CDN_CACHE_CONTROL,
+            CONNECTION,
+            CONTENT_DISPOSITION,
+            CONTENT_ENCODING,
+            CONTENT_LANGUAGE,
+            CONTENT_LENGTH,
+            CONTENT_LOCATION,
+            CONTENT_RANGE,
+            CONTENT_SECURITY_POLICY,
+            CONTENT_SECURITY_POLICY_REPORT_ONLY,
+            CONTENT_TYPE,
+            COOKIE,
+            DNT,
+            DATE,
+            ETAG,
+            EXPECT,
+            EXPIRES,
+            FORWARDED,
+            FROM,
+            HOST,
+            IF_MATCH,
+            IF_MODIFIED_SINCE,
+            IF_NONE_MATCH,
+            IF_RANGE,
+            IF_UNMODIFIED_SINCE,
+            LAST_MODIFIED,
+            LINK,
+            LOCATION,
+            MAX_FORWARDS,
+            ORIGIN,
+            PRAGMA,
+            PROXY_AUTHENTICATE,
+            PROXY_AUTHORIZATION,
+            PUBLIC_KEY_PINS,
+            PUBLIC_KEY_PINS_REPORT_ONLY,
+            RANGE,
+            REFERER,
+            REFERRER_POLICY,
+            REFRESH,
+            RETRY_AFTER,
+            SEC_WEBSOCKET_ACCEPT,
+            SEC_WEBSOCKET_EXTENSIONS,
+            SEC_WEBSOCKET_KEY,
+            SEC_WEBSOCKET_PROTOCOL,
+            SEC_WEBSOCKET_VERSION,
+            SERVER,
+            SET_COOKIE,
+            STRICT_TRANSPORT_SECURITY,
+            TE,
+            TRAILER,
+            TRANSFER_ENCODING,
+            USER_AGENT,
+            UPGRADE,
+            UPGRADE_INSECURE_REQUESTS,
+            VARY,
+            VIA,
+            WARNING,
+            WWW_AUTHENTICATE,
+            X_CONTENT_TYPE_OPTIONS,
+            X_DNS_PREFETCH_CONTROL,
+            X_FRAME_OPTIONS,
+            X_XSS_PROTECTION,
+        ];
+        STANDARD.contains(name)
+    }
+
+    impl DeepSizeOf for HeaderValue {
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            self.len()
+        }
+    }
+
+    // The internal layouts of the `HeaderMap` entries
+    #[allow(dead_code)]
+    struct MockBucket<T> {
+        hash: u16,
+        key: HeaderName,
+        value: T,
+        links: Option<(usize, usize)>,
+    }
+
+    #[allow(dead_code)]
+    enum MockLink {
+        Entry(usize),
+        Extra(usize),
+    }
+
+    #[allow(dead_code)]
+    struct MockExtraValue<T> {
+        value: T,
+        prev: MockLink,
+        next: MockLink,
+    }
+
+    impl<T: DeepSizeOf> DeepSizeOf for HeaderMap<T> {
+        /// The index table has a power of two length with a 3/4 load factor,
+        /// and each index is a pair of `u16`s.  Values beyond the first for
+        /// each name are stored in a separate `Vec`, which is estimated by
+        /// its length.
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                let capacity = self.capacity();
+                let indices = match capacity {
+                    0 => 0,
+                    _ => (capacity + capacity / 3).next_power_of_two() * 2 * size_of::<u16>(),
+                };
+                let entries = capacity * size_of::<MockBucket<T>>();
+                let extra_values = (self.len() - self.keys_len()) * size_of::<MockExtraValue<T>>();
+
+                let names = self
+                    .keys()
+                    .map(|name| name.deep_size_of_children(context))
+                    .sum::<usize>();
+                let values = self
+                    .values()
+                    .map(|value| value.deep_size_of_children(context))
+                    .sum::<usize>();
+
+                indices + entries + extra_values + names + values
+            })
+        }
+    }
+
+    impl DeepSizeOf for Uri {
+        /// Parsed URIs share a single allocation between their components,
+        /// so this is estimated by the length of each component.  Standard
+        /// schemes are not allocated.
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            let scheme = match self.scheme() {
+                Some(scheme) if *scheme != Scheme::HTTP && *scheme != Scheme::HTTPS => {
+                    scheme.as_str().len()
+                }
+                _ => 0,
+            };
+            let authority = self.authority().map_or(0, |a| a.as_str().len());
+            let path_and_query = self.path_and_query().map_or(0, |p| p.as_str().len());
+            scheme + authority + path_and_query
+        }
+    }
+}
+
+#[cfg(feature = "ropey")]
+mod ropey_impl {
+    use crate::{known_deep_size, Context, DeepSizeOf};
+    use ropey::{Rope, RopeSlice, MAX_BYTES, MAX_CHILDREN};
+
+    known_deep_size!(0; {'a} RopeSlice<'a>);
+
+    /// Ropey sizes its nodes so that each node, along with the `Arc`
+    /// reference counts, takes up exactly this many bytes.
+    const NODE_SIZE: usize = 1024;
+
+    impl DeepSizeOf for Rope {
+        /// Each chunk of text is stored in a leaf node, and the internal
+        /// nodes are estimated from the number of leaves, assuming that the
+        /// nodes are full.  Clones of a `Rope` share their nodes, but each
+        /// clone is counted in full.
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            let mut leaves = 0usize;
+            let mut spilled = 0;
+            for chunk in self.chunks() {
+                leaves += 1;
+                if chunk.len() > MAX_BYTES {
+                    spilled += chunk.len();
+                }
+            }
+
+            // An empty rope still has an empty leaf node
+            let mut nodes = leaves.max(1);
+            let mut level = leaves;
+            while level > 1 {
+                level = level.div_ceil(MAX_CHILDREN);
+                nodes += level;
+            }
+
+            nodes * NODE_SIZE + spilled
+        }
+    }
+}
+
+#[cfg(feature = "bimap")]
+mod bimap_impl {
+    use crate::{btree_bounds, btree_size, hash_table_upper_bound, pair_bounds, sum_bounds};
+    use crate::{Context, DeepSizeOf};
+    use bimap::{BiBTreeMap, BiHashMap};
+    use core::hash::{BuildHasher, Hash};
+    use core::mem::size_of;
+
+    /// Each value is stored once, in an `Rc` shared by both tables, and
+    /// each table entry is a pair of `Rc` pointers.
+    fn pair_size<L, R>() -> usize {
+        2 * size_of::<usize>() + size_of::<L>() + 2 * size_of::<usize>() + size_of::<R>()
+    }
+
+    impl<L, R, LS, RS> DeepSizeOf for BiHashMap<L, R, LS, RS>
+    where
+        L: DeepSizeOf + Eq + Hash,
+        R: DeepSizeOf + Eq + Hash,
+        LS: BuildHasher,
+        RS: BuildHasher,
+    {
+        /// Only the smaller capacity of the two tables is exposed, so it
+        /// is used for both.
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                let values = self.iter().fold(0, |sum, (left, right)| {
+                    sum + left.deep_size_of_children(context) + right.deep_size_of_children(context)
+                });
+                let tables = 2 * self.capacity() * (2 * size_of::<usize>() + 1);
+                values + self.len() * pair_size::<L, R>() + tables
+            })
+        }
+
+        fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+            context.descend(|context| {
+                let (lower, upper) = sum_bounds(
+                    self.iter()
+                        .map(|(left, right)| pair_bounds(left, right, context)),
+                );
+                let pairs = self.len() * pair_size::<L, R>();
+                let slot_size = 2 * size_of::<usize>() + 1;
+                (
+                    lower + pairs + 2 * self.capacity() * slot_size,
+                    upper + pairs + 2 * hash_table_upper_bound(self.capacity(), slot_size),
+                )
+            })
+        }
+    }
+
+    impl<L, R> DeepSizeOf for BiBTreeMap<L, R>
+    where
+        L: DeepSizeOf + Ord,
+        R: DeepSizeOf + Ord,
+    {
+        /// The two btrees are estimated the same way as a `BTreeMap`.
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                let values = self.iter().fold(0, |sum, (left, right)| {
+                    sum + left.deep_size_of_children(context) + right.deep_size_of_children(context)
+                });
+                let entry_size = 2 * size_of::<usize>();
+                values + self.len() * pair_size::<L, R>() + 2 * btree_size(self.len(), entry_size)
+            })
+        }
+
+        fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+            context.descend(|context| {
+                let (lower, upper) = sum_bounds(
+                    self.iter()
+                        .map(|(left, right)| pair_bounds(left, right, context)),
+                );
+                let pairs = self.len() * pair_size::<L, R>();
+                let (node_lower, node_upper) = btree_bounds(self.len(), 2 * size_of::<usize>());
+                (
+                    lower + pairs + 2 * node_lower,
+                    upper + pairs + 2 * node_upper,
+                )
+            })
+        }
+    }
+}
+
+#[cfg(feature = "hashlink")]
+mod hashlink_impl {
+    use crate::{hash_table_upper_bound, pair_bounds, sum_bounds, Context, DeepSizeOf};
+    use core::hash::{BuildHasher, Hash};
+    use core::mem::size_of;
+    use hashlink::{LinkedHashMap, LinkedHashSet, LruCache};
+
+    /// The size of a boxed node, which holds an entry and the pointers to
+    /// the previous and next nodes
+    fn node_size<K, V>() -> usize {
+        size_of::<((K, V), [usize; 2])>()
+    }
+
+    /// The size of the table of node pointers and the nodes themselves,
+    /// including the guard node at the head of the list.  Removed nodes
+    /// may be kept in a free list, which isn't counted.
+    fn map_size<K, V>(len: usize, capacity: usize) -> usize {
+        let table = capacity * (size_of::<usize>() + 1);
+        let nodes = if len == 0 { 0 } else { len + 1 };
+        table + nodes * node_size::<K, V>()
+    }
+
+    /// Bounds `map_size`, where the upper bound counts every bucket of the
+    /// table
+    fn map_bounds<K, V>(len: usize, capacity: usize) -> (usize, usize) {
+        let nodes = if len == 0 { 0 } else { len + 1 };
+        (
+            map_size::<K, V>(len, capacity),
+            hash_table_upper_bound(capacity, size_of::<usize>() + 1) + nodes * node_size::<K, V>(),
+        )
+    }
+
+    /// Estimates the capacity of a hashbrown table that grew to hold `len`
+    /// elements.
+    fn estimated_capacity(len: usize) -> usize {
+        match len {
+            0 => 0,
+            1..=3 => 3,
+            4..=7 => 7,
+            _ => (len * 8 / 7).next_power_of_two() / 8 * 7,
+        }
+    }
+
+    impl<K, V, S> DeepSizeOf for LinkedHashMap<K, V, S>
+    where
+        K: DeepSizeOf + Eq + Hash,
+        V: DeepSizeOf,
+        S: BuildHasher,
+    {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                self.iter().fold(0, |sum, (key, val)| {
+                    sum + key.deep_size_of_children(context) + val.deep_size_of_children(context)
+                }) + map_size::<K, V>(self.len(), self.capacity())
+            })
+        }
+
+        fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+            context.descend(|context| {
+                let (lower, upper) =
+                    sum_bounds(self.iter().map(|(key, val)| pair_bounds(key, val, context)));
+                let (map_lower, map_upper) = map_bounds::<K, V>(self.len(), self.capacity());
+                (lower + map_lower, upper + map_upper)
+            })
+        }
+    }
+
+    impl<T, S> DeepSizeOf for LinkedHashSet<T, S>
+    where
+        T: DeepSizeOf + Eq + Hash,
+        S: BuildHasher,
+    {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                self.iter()
+                    .fold(0, |sum, item| sum + item.deep_size_of_children(context))
+                    + map_size::<T, ()>(self.len(), self.capacity())
+            })
+        }
+
+        fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+            context.descend(|context| {
+                let (lower, upper) = sum_bounds(
+                    self.iter()
+                        .map(|item| item.deep_size_of_children_approx(context)),
+                );
+                let (map_lower, map_upper) = map_bounds::<T, ()>(self.len(), self.capacity());
+                (lower + map_lower, upper + map_upper)
+            })
+        }
+    }
+
+    impl<K, V, S> DeepSizeOf for LruCache<K, V, S>
+    where
+        K: DeepSizeOf + Eq + Hash,
+        V: DeepSizeOf,
+        S: BuildHasher,
+    {
+        /// The capacity of the underlying table isn't exposed, so it is
+        /// estimated from the current length.
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                self.iter().fold(0, |sum, (key, val)| {
+                    sum + key.deep_size_of_children(context) + val.deep_size_of_children(context)
+                }) + map_size::<K, V>(self.len(), estimated_capacity(self.len()))
+            })
+        }
+
+        /// The table can't be smaller than the current length, and can't have
+        /// grown beyond one more than the cache's capacity (unless the cache
+        /// is unbounded, in which case the length is used).
+        fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+            context.descend(|context| {
+                let (lower, upper) =
+                    sum_bounds(self.iter().map(|(key, val)| pair_bounds(key, val, context)));
+                let max_capacity = match self.capacity() {
+                    usize::MAX => self.len(),
+                    capacity => capacity + 1,
+                };
+                let (map_lower, _) = map_bounds::<K, V>(self.len(), self.len());
+                let (_, map_upper) = map_bounds::<K, V>(self.len(), max_capacity);
+                (lower + map_lower, upper + map_upper)
+            })
+        }
+    }
+}
+
+#[cfg(feature = "bumpalo")]
+mod bumpalo_impl {
+    use crate::{known_deep_size, Context, DeepSizeOf};
+    use bumpalo::collections::{String, Vec};
+    use bumpalo::Bump;
+
+    impl<const MIN_ALIGN: usize> DeepSizeOf for Bump<MIN_ALIGN> {
+        /// Counts every chunk that the arena has allocated, including the
+        /// unused space at the end of the current chunk and the chunk
+        /// metadata.  Values in the arena are never dropped, so any heap
+        /// allocations that they own are not counted.
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            self.allocated_bytes_including_metadata()
+        }
+    }
+
+    // The storage of the bump collections belongs to the `Bump`, and is
+    // counted there, so only the allocations owned by their elements
+    // are counted.
+    known_deep_size!(0; {'bump} String<'bump>);
+
+    impl<'bump, T: DeepSizeOf> DeepSizeOf for Vec<'bump, T> {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                self.iter()
+                    .map(|child| child.deep_size_of_children(context))
+                    .sum()
+            })
+        }
+    }
+}
+
+#[cfg(feature = "typed-arena")]
+mod typed_arena_impl {
+    use crate::{Context, DeepSizeOf};
+    use core::mem::size_of;
+    use typed_arena::Arena;
+
+    impl<T> DeepSizeOf for Arena<T> {
+        /// The arena's chunks aren't exposed, so this estimates them from
+        /// the number of values.  An arena created by `Arena::new` starts
+        /// with a 1 KiB chunk, and each new chunk is twice the size of the
+        /// last, so the unused space is at most the size of the last chunk.
+        ///
+        /// The values in the arena can only be reached through a mutable
+        /// reference, so the allocations that they own are not counted;
+        /// they can be measured with `Arena::iter_mut`.
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            let len = self.len();
+            let mut chunk = (1024 / size_of::<T>().max(1)).max(1);
+            let mut capacity = chunk;
+            let mut chunks = 1;
+            while capacity < len {
+                chunk *= 2;
+                capacity += chunk;
+                chunks += 1;
+            }
+            // The full chunks are kept in a `Vec<Vec<T>>`
+            capacity * size_of::<T>() + (chunks - 1) * size_of::<alloc::vec::Vec<T>>()
+        }
+    }
+}
+
+#[cfg(feature = "memmap2")]
+mod memmap2_impl {
+    use crate::{Context, DeepSizeOf};
+    use memmap2::{Mmap, MmapMut, MmapRaw};
+
+    // Mapped regions are counted by their length, unless the context is
+    // set to exclude mapped memory.
+    macro_rules! mmap_impls {
+        ($($type:ty),*) => {
+            $(
+                impl DeepSizeOf for $type {
+                    fn deep_size_of_children(&self, context: &mut Context) -> usize {
+                        if context.count_mapped_memory() {
+                            self.len()
+                        } else {
+                            0
+                        }
+                    }
+                }
+            )*
+        };
+    }
+
+    mmap_impls!(Mmap, MmapMut, MmapRaw);
+}
+
+#[cfg(feature = "tinyvec")]
+mod tinyvec_impl {
+    use crate::{Context, DeepSizeOf};
+    use core::mem::size_of;
+    use tinyvec::{Array, ArrayVec, TinyVec};
+
+    impl<A> DeepSizeOf for ArrayVec<A>
+    where
+        A: Array,
+        A::Item: DeepSizeOf,
+    {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            self.iter()
+                .fold(0, |sum, elem| sum + elem.deep_size_of_children(context))
+        }
+    }
+
+    impl<A> DeepSizeOf for TinyVec<A>
+    where
+        A: Array,
+        A::Item: DeepSizeOf,
+    {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            let children = |context: &mut Context| {
+                self.iter()
+                    .fold(0, |sum, elem| sum + elem.deep_size_of_children(context))
+            };
+            if self.is_heap() {
+                context
+                    .descend(|context| children(context) + self.capacity() * size_of::<A::Item>())
+            } else {
+                children(context)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "flume")]
+mod flume_impl {
+    use crate::{Context, DeepSizeOf};
+    use core::mem::size_of;
+    use flume::{Receiver, Sender};
+
+    impl<T> DeepSizeOf for Sender<T> {
+        /// Senders are handles to a channel that is counted by its receiver
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            0
+        }
+    }
+
+    impl<T> DeepSizeOf for Receiver<T> {
+        /// The queued messages are stored in a `VecDeque`, which is estimated
+        /// from the number of messages, since its capacity isn't exposed.
+        /// The messages can't be accessed without receiving them, so their
+        /// children are not counted.
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            let queue = match self.len() {
+                0 => 0,
+                len => len.next_power_of_two().max(4),
+            };
+            queue * size_of::<T>()
+        }
+    }
+}
+
+#[cfg(feature = "im")]
+mod im_impl {
+    use crate::{Context, DeepSizeOf};
+    use core::mem::size_of;
+    use im::Vector;
+
+    /// The number of elements in each chunk of a `Vector`
+    const CHUNK_SIZE: usize = 64;
+
+    impl<A: Clone + DeepSizeOf> DeepSizeOf for Vector<A> {
+        /// Small vectors are stored inline.  Larger vectors store their
+        /// elements in reference counted chunks of 64, which may be shared
+        /// with other vectors, so each chunk is only counted the first time
+        /// that it is found.
+        ///
+        /// This is a rough estimate: only the chunks holding elements are
+        /// counted, and not the tree nodes pointing to them or any empty
+        /// chunks.
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            if self.is_inline() {
+                return self
+                    .iter()
+                    .fold(0, |sum, elem| sum + elem.deep_size_of_children(context));
+            }
+            // Each chunk also has a reference count header, and the start
+            // and end offsets of its elements
+            let chunk_size = CHUNK_SIZE * size_of::<A>() + 4 * size_of::<usize>();
+            context.descend(|context| {
+                let mut size = 0;
+                for leaf in self.leaves() {
+                    if context.contains_arc_ptr(leaf.as_ptr()) {
+                        continue;
+                    }
+                    context.add_arc_ptr(leaf.as_ptr());
+                    size += chunk_size
+                        + leaf
+                            .iter()
+                            .fold(0, |sum, elem| sum + elem.deep_size_of_children(context));
+                }
+                size
+            })
+        }
+    }
+}
+
+#[cfg(feature = "glam")]
+mod glam_impl {
+    use crate::known_deep_size;
+    use glam::*;
+
+    known_deep_size!(0;
+        Vec2, Vec3, Vec3A, Vec4, DVec2, DVec3, DVec4,
+        IVec2, IVec3, IVec4, UVec2, UVec3, UVec4,
+        I64Vec2, I64Vec3, I64Vec4, U64Vec2, U64Vec3, U64Vec4,
+        BVec2, BVec3, BVec3A, BVec4, BVec4A,
+        Mat2, Mat3, Mat3A, Mat4, DMat2, DMat3, DMat4,
+        Quat, DQuat, Affine2, Affine3A, DAffine2, DAffine3,
+        EulerRot,
+    );
+}
+
+#[cfg(feature = "euclid")]
+mod euclid_impl {
+    use crate::known_deep_size;
+    use euclid::*;
+
+    // The unit parameters are only markers, so they aren't bounded, and
+    // `Copy` scalars can't own any allocations.
+    known_deep_size!(0;
+        UnknownUnit, BoolVector2D, BoolVector3D,
+        {T: Copy} Angle<T>,
+        {T: Copy, U} Length<T, U>,
+        {T: Copy, U} Point2D<T, U>, {T: Copy, U} Point3D<T, U>,
+        {T: Copy, U} Vector2D<T, U>, {T: Copy, U} Vector3D<T, U>,
+        {T: Copy, U} Size2D<T, U>, {T: Copy, U} Size3D<T, U>,
+        {T: Copy, U} Rect<T, U>, {T: Copy, U} Box2D<T, U>, {T: Copy, U} Box3D<T, U>,
+        {T: Copy, U} SideOffsets2D<T, U>, {T: Copy, U} HomogeneousVector<T, U>,
+        {T: Copy, Src, Dst} Scale<T, Src, Dst>,
+        {T: Copy, Src, Dst} Translation2D<T, Src, Dst>,
+        {T: Copy, Src, Dst} Translation3D<T, Src, Dst>,
+        {T: Copy, Src, Dst} Rotation2D<T, Src, Dst>,
+        {T: Copy, Src, Dst} Rotation3D<T, Src, Dst>,
+        {T: Copy, Src, Dst} RigidTransform3D<T, Src, Dst>,
+        {T: Copy, Src, Dst} Transform2D<T, Src, Dst>,
+        {T: Copy, Src, Dst} Transform3D<T, Src, Dst>,
+    );
+}
+
+#[cfg(feature = "image")]
+mod image_impl {
+    use crate::{Context, DeepSizeOf};
+    use core::ops::Deref;
+    use image::{DynamicImage, ImageBuffer, Pixel};
+
+    impl<P, Container> DeepSizeOf for ImageBuffer<P, Container>
+    where
+        P: Pixel,
+        Container: Deref<Target = [P::Subpixel]> + DeepSizeOf,
+    {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            self.as_raw().deep_size_of_children(context)
+        }
+    }
+
+    impl DeepSizeOf for DynamicImage {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            match self {
+                DynamicImage::ImageLuma8(image) => image.deep_size_of_children(context),
+                DynamicImage::ImageLumaA8(image) => image.deep_size_of_children(context),
+                DynamicImage::ImageRgb8(image) => image.deep_size_of_children(context),
+                DynamicImage::ImageRgba8(image) => image.deep_size_of_children(context),
+                DynamicImage::ImageLuma16(image) => image.deep_size_of_children(context),
+                DynamicImage::ImageLumaA16(image) => image.deep_size_of_children(context),
+                DynamicImage::ImageRgb16(image) => image.deep_size_of_children(context),
+                DynamicImage::ImageRgba16(image) => image.deep_size_of_children(context),
+                DynamicImage::ImageRgb32F(image) => image.deep_size_of_children(context),
+                DynamicImage::ImageRgba32F(image) => image.deep_size_of_children(context),
+                // Later variants are counted by the length of their data
+                _ => self.as_bytes().len(),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "bytes")]
+mod bytes_impl {
+    use crate::{Context, DeepSizeOf};
+
+    impl DeepSizeOf for bytes::Bytes {
+        /// `Bytes` may share its buffer with other handles, but the buffer
+        /// itself isn't exposed, so the context tracks the bytes that each
+        /// handle views, and every byte is only counted once no matter how
+        /// many handles (or slices) view it.
+        ///
+        /// Parts of a buffer that no measured handle views, such as a part
+        /// that was split off and dropped, aren't counted.  Static data is
+        /// counted as well, since it can't be detected.
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.add_shared_range(self.as_ptr(), self.len())
+        }
+    }
+
+    impl DeepSizeOf for bytes::BytesMut {
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            self.capacity()
+        }
+    }
+}
+
+/// The containers used by generated messages (`prost::alloc`) are
+/// re-exports of `alloc`, and `bytes` fields are covered by the `bytes`
+/// impls, so generated messages only need an impl for each message and
+/// oneof type; the `deep_size_fields` macro can provide them for messages.
+#[cfg(feature = "prost")]
+mod prost_impl {
+    use crate::{deep_size_fields, known_deep_size, Context, DeepSizeOf};
+    use prost_types::{value::Kind, Any, Duration, FieldMask, ListValue, Struct, Timestamp, Value};
+
+    known_deep_size!(0; Timestamp, Duration);
+
+    deep_size_fields!(
+        Any { type_url, value },
+        FieldMask { paths },
+        Struct { fields },
+        Value { kind },
+        ListValue { values },
+    );
+
+    impl DeepSizeOf for Kind {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            match self {
+                Kind::StringValue(string) => string.deep_size_of_children(context),
+                Kind::StructValue(value) => value.deep_size_of_children(context),
+                Kind::ListValue(list) => list.deep_size_of_children(context),
+                Kind::NullValue(_) | Kind::NumberValue(_) | Kind::BoolValue(_) => 0,
+            }
+        }
+    }
+}
+
+#[cfg(feature = "serde_yaml")]
+mod serde_yaml_impl {
+    use crate::{hash_table_upper_bound, known_deep_size, pair_bounds, sum_bounds};
+    use crate::{Context, DeepSizeOf};
+    use core::fmt::{self, Write};
+    use core::mem::size_of;
+    use serde_yaml::value::{Tag, TaggedValue};
+    use serde_yaml::{Mapping, Number, Value};
+
+    known_deep_size!(0; Number);
+
+    impl DeepSizeOf for Value {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            match self {
+                Value::Null | Value::Bool(_) | Value::Number(_) => 0,
+                Value::String(string) => string.deep_size_of_children(context),
+                Value::Sequence(sequence) => sequence.deep_size_of_children(context),
+                Value::Mapping(mapping) => mapping.deep_size_of_children(context),
+                Value::Tagged(tagged) => tagged.deep_size_of_children(context),
+            }
+        }
+    }
+
+    // `Mapping` wraps an `IndexMap<Value, Value>`, so this uses the same
+    // estimate as the `indexmap` impl.
+    impl DeepSizeOf for Mapping {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                let child_sizes = self.iter().fold(0, |sum, (key, val)| {
+                    sum + key.deep_size_of_children(context) + val.deep_size_of_children(context)
+                });
+                let map_size =
+                    self.capacity() * (size_of::<(usize, Value, Value)>() + size_of::<usize>());
+                child_sizes + map_size
+            })
+        }
+
+        fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+            context.descend(|context| {
+                let (lower, upper) =
+                    sum_bounds(self.iter().map(|(key, val)| pair_bounds(key, val, context)));
+                let entries = self.capacity() * size_of::<(usize, Value, Value)>();
+                (
+                    lower + entries + self.capacity() * size_of::<usize>(),
+                    upper
+                        + entries
+                        + hash_table_upper_bound(self.capacity(), size_of::<usize>() + 1),
+                )
+            })
+        }
+    }
+
+    struct CountingWriter(usize);
+
+    impl Write for CountingWriter {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            self.0 += s.len();
+            Ok(())
+        }
+    }
+
+    impl DeepSizeOf for Tag {
+        /// The tag's string isn't accessible, so this counts the length of
+        /// its formatted form, which always includes the leading `!`.
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            let mut writer = CountingWriter(0);
+            let _ = write!(writer, "{}", self);
+            writer.0
+        }
+    }
+
+    impl DeepSizeOf for TaggedValue {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            self.tag.deep_size_of_children(context) + self.value.deep_size_of_children(context)
+        }
+    }
+}
+
+#[cfg(feature = "toml")]
+mod toml_impl {
+    use crate::{btree_bounds, btree_size, known_deep_size, pair_bounds, sum_bounds};
+    use crate::{Context, DeepSizeOf};
+    use core::hash::Hash;
+    use core::mem::size_of;
+    use toml::map::Map;
+    use toml::value::{Datetime, Value};
+
+    known_deep_size!(0; Datetime);
+
+    impl DeepSizeOf for Value {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            match self {
+                Value::String(string) => string.deep_size_of_children(context),
+                Value::Array(array) => array.deep_size_of_children(context),
+                Value::Table(table) => table.deep_size_of_children(context),
+                Value::Integer(_) | Value::Float(_) | Value::Boolean(_) | Value::Datetime(_) => 0,
+            }
+        }
+    }
+
+    impl<K, V> DeepSizeOf for Map<K, V>
+    where
+        K: DeepSizeOf + Ord + Hash,
+        V: DeepSizeOf,
+    {
+        /// Tables are backed by a `BTreeMap` by default, so this uses the
+        /// same estimate as `BTreeMap`, even when the `preserve_order`
+        /// feature of `toml` switches them to an `IndexMap`.
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                let element_size = self.iter().fold(0, |sum, (key, val)| {
+                    sum + key.deep_size_of_children(context) + val.deep_size_of_children(context)
+                });
+                element_size + btree_size(self.len(), size_of::<K>() + size_of::<V>())
+            })
+        }
+
+        fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+            context.descend(|context| {
+                let (lower, upper) =
+                    sum_bounds(self.iter().map(|(key, val)| pair_bounds(key, val, context)));
+                let (node_lower, node_upper) =
+                    btree_bounds(self.len(), size_of::<K>() + size_of::<V>());
+                (lower + node_lower, upper + node_upper)
+            })
+        }
+    }
+}
+
+#[cfg(feature = "toml_edit")]
+mod toml_edit_impl {
+    use crate::{Context, DeepSizeOf};
+    use core::mem::size_of;
+    use toml_edit::{
+        Array, ArrayOfTables, Decor, DocumentMut, InlineTable, Item, Key, RawString, Repr, Table,
+        Value,
+    };
+
+    impl DeepSizeOf for RawString {
+        /// Strings that refer to a span of the parsed input don't own
+        /// anything; explicit strings are counted by their length.
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            self.as_str().map_or(0, str::len)
+        }
+    }
+
+    impl DeepSizeOf for Repr {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            self.as_raw().deep_size_of_children(context)
+        }
+    }
+
+    impl DeepSizeOf for Decor {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            let prefix = self
+                .prefix()
+                .map_or(0, |raw| raw.deep_size_of_children(context));
+            let suffix = self
+                .suffix()
+                .map_or(0, |raw| raw.deep_size_of_children(context));
+            prefix + suffix
+        }
+    }
+
+    impl DeepSizeOf for Key {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            self.get().len()
+                + self
+                    .as_repr()
+                    .map_or(0, |repr| repr.deep_size_of_children(context))
+                + self.leaf_decor().deep_size_of_children(context)
+                + self.dotted_decor().deep_size_of_children(context)
+        }
+    }
+
+    // Counts the formatting of a `Formatted` value, but not the value
+    // itself.  The bound on `Formatted`'s methods can't be named, so this
+    // is a macro rather than a generic function.
+    macro_rules! formatting_size {
+        ($formatted:expr, $context:expr) => {
+            $formatted
+                .as_repr()
+                .map_or(0, |repr| repr.deep_size_of_children($context))
+                + $formatted.decor().deep_size_of_children($context)
+        };
+    }
+
+    impl DeepSizeOf for Value {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            match self {
+                Value::String(string) => {
+                    string.value().deep_size_of_children(context)
+                        + formatting_size!(string, context)
+                }
+                Value::Integer(value) => formatting_size!(value, context),
+                Value::Float(value) => formatting_size!(value, context),
+                Value::Boolean(value) => formatting_size!(value, context),
+                Value::Datetime(value) => formatting_size!(value, context),
+                Value::Array(array) => array.deep_size_of_children(context),
+                Value::InlineTable(table) => table.deep_size_of_children(context),
+            }
+        }
+    }
+
+    impl DeepSizeOf for Item {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            match self {
+                Item::None => 0,
+                Item::Value(value) => value.deep_size_of_children(context),
+                Item::Table(table) => table.deep_size_of_children(context),
+                Item::ArrayOfTables(tables) => tables.deep_size_of_children(context),
+            }
+        }
+    }
+
+    impl DeepSizeOf for Array {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                let values = self
+                    .iter()
+                    .fold(0, |sum, value| sum + value.deep_size_of_children(context));
+                // Values are stored as `Item`s
+                values
+                    + self.len() * size_of::<Item>()
+                    + self.decor().deep_size_of_children(context)
+                    + self.trailing().deep_size_of_children(context)
+            })
+        }
+    }
+
+    impl DeepSizeOf for ArrayOfTables {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                let tables = self
+                    .iter()
+                    .fold(0, |sum, table| sum + table.deep_size_of_children(context));
+                tables + self.len() * size_of::<Item>()
+            })
+        }
+    }
+
+    // Tables are backed by an `IndexMap<Key, Item>`, whose capacity isn't
+    // exposed, so these use the same estimate as the `indexmap` impl with
+    // the table's length.
+    fn entry_size() -> usize {
+        size_of::<(usize, Key, Item)>() + size_of::<usize>()
+    }
+
+    impl DeepSizeOf for Table {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                let entries = self.iter().fold(0, |sum, (key, item)| {
+                    let key = self
+                        .key(key)
+                        .map_or(0, |key| key.deep_size_of_children(context));
+                    sum + key + item.deep_size_of_children(context)
+                });
+                entries + self.len() * entry_size() + self.decor().deep_size_of_children(context)
+            })
+        }
+    }
+
+    impl DeepSizeOf for InlineTable {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                let entries = self.iter().fold(0, |sum, (key, value)| {
+                    let key = self
+                        .key(key)
+                        .map_or(0, |key| key.deep_size_of_children(context));
+                    sum + key + value.deep_size_of_children(context)
+                });
+                entries
+                    + self.len() * entry_size()
+                    + self.decor().deep_size_of_children(context)
+                    + self.trailing().deep_size_of_children(context)
+            })
+        }
+    }
+
+    impl DeepSizeOf for DocumentMut {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            self.as_item().deep_size_of_children(context)
+                + self.trailing().deep_size_of_children(context)
+        }
+    }
+}
+
+#[cfg(feature = "bit-vec")]
+mod bit_vec_impl {
+    use crate::{Context, DeepSizeOf};
+    use bit_vec::{BitBlock, BitVec};
+    use core::mem::size_of;
+
+    impl<B: BitBlock> DeepSizeOf for BitVec<B> {
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            self.capacity() / B::bits() * size_of::<B>()
+        }
+    }
+}
+
+#[cfg(feature = "bit-set")]
+mod bit_set_impl {
+    use crate::{Context, DeepSizeOf};
+    use bit_set::{BitBlock, BitSet};
+    use core::mem::size_of;
+
+    impl<B: BitBlock> DeepSizeOf for BitSet<B> {
+        /// The set is stored as a bit vector covering every value up to
+        /// the largest one, so its size depends on the largest value rather
+        /// than on the number of values.
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            self.capacity() / B::bits() * size_of::<B>()
+        }
+    }
+}
+
+#[cfg(feature = "rangemap")]
+mod rangemap_impl {
+    use crate::{btree_bounds, btree_size, pair_bounds, sum_bounds, Context, DeepSizeOf};
+    use core::mem::size_of;
+    use rangemap::{RangeInclusiveMap, RangeInclusiveSet, RangeMap, RangeSet, StepFns};
+
+    // Each collection is backed by a `BTreeMap` keyed by the ranges, so
+    // this uses the same node estimate as `BTreeMap`.
+
+    fn bounds_size<K: DeepSizeOf>(start: &K, end: &K, context: &mut Context) -> usize {
+        start.deep_size_of_children(context) + end.deep_size_of_children(context)
+    }
+
+    /// Adds the bounds of the nodes of a btree with `len` entries
+    fn with_nodes((lower, upper): (usize, usize), len: usize, entry_size: usize) -> (usize, usize) {
+        let (node_lower, node_upper) = btree_bounds(len, entry_size);
+        (lower + node_lower, upper + node_upper)
+    }
+
+    impl<K: DeepSizeOf, V: DeepSizeOf> DeepSizeOf for RangeMap<K, V> {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                let (len, children) = self.iter().fold((0, 0), |(len, sum), (range, value)| {
+                    let size = bounds_size(&range.start, &range.end, context)
+                        + value.deep_size_of_children(context);
+                    (len + 1, sum + size)
+                });
+                children + btree_size(len, 2 * size_of::<K>() + size_of::<V>())
+            })
+        }
+
+        fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+            context.descend(|context| {
+                let bounds = sum_bounds(self.iter().map(|(range, value)| {
+                    let (bounds_lower, bounds_upper) =
+                        pair_bounds(&range.start, &range.end, context);
+                    let (value_lower, value_upper) = value.deep_size_of_children_approx(context);
+                    (bounds_lower + value_lower, bounds_upper + value_upper)
+                }));
+                let len = self.iter().count();
+                with_nodes(bounds, len, 2 * size_of::<K>() + size_of::<V>())
+            })
+        }
+    }
+
+    impl<K: DeepSizeOf, V: DeepSizeOf, S> DeepSizeOf for RangeInclusiveMap<K, V, S> {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                let (len, children) = self.iter().fold((0, 0), |(len, sum), (range, value)| {
+                    let size = bounds_size(range.start(), range.end(), context)
+                        + value.deep_size_of_children(context);
+                    (len + 1, sum + size)
+                });
+                let entry_size = size_of::<core::ops::RangeInclusive<K>>() + size_of::<V>();
+                children + btree_size(len, entry_size)
+            })
+        }
+
+        fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+            context.descend(|context| {
+                let bounds = sum_bounds(self.iter().map(|(range, value)| {
+                    let (bounds_lower, bounds_upper) =
+                        pair_bounds(range.start(), range.end(), context);
+                    let (value_lower, value_upper) = value.deep_size_of_children_approx(context);
+                    (bounds_lower + value_lower, bounds_upper + value_upper)
+                }));
+                let len = self.iter().count();
+                let entry_size = size_of::<core::ops::RangeInclusive<K>>() + size_of::<V>();
+                with_nodes(bounds, len, entry_size)
+            })
+        }
+    }
+
+    impl<T> DeepSizeOf for RangeSet<T>
+    where
+        T: DeepSizeOf + Ord + Clone,
+    {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                let (len, children) = self.iter().fold((0, 0), |(len, sum), range| {
+                    (
+                        len + 1,
+                        sum + bounds_size(&range.start, &range.end, context),
+                    )
+                });
+                children + btree_size(len, 2 * size_of::<T>())
+            })
+        }
+
+        fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+            context.descend(|context| {
+                let bounds = sum_bounds(
+                    self.iter()
+                        .map(|range| pair_bounds(&range.start, &range.end, context)),
+                );
+                with_nodes(bounds, self.iter().count(), 2 * size_of::<T>())
+            })
+        }
+    }
+
+    impl<T, S> DeepSizeOf for RangeInclusiveSet<T, S>
+    where
+        T: DeepSizeOf + Ord + Clone,
+        S: StepFns<T>,
+    {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                let (len, children) = self.iter().fold((0, 0), |(len, sum), range| {
+                    (
+                        len + 1,
+                        sum + bounds_size(range.start(), range.end(), context),
+                    )
+                });
+                let entry_size = size_of::<core::ops::RangeInclusive<T>>();
+                children + btree_size(len, entry_size)
+            })
+        }
+
+        fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+            context.descend(|context| {
+                let bounds = sum_bounds(
+                    self.iter()
+                        .map(|range| pair_bounds(range.start(), range.end(), context)),
+                );
+                let entry_size = size_of::<core::ops::RangeInclusive<T>>();
+                with_nodes(bounds, self.iter().count(), entry_size)
+            })
+        }
+    }
+}
+
+#[cfg(feature = "vec_map")]
+mod vec_map_impl {
+    use crate::{Context, DeepSizeOf};
+    use core::mem::size_of;
+    use vec_map::VecMap;
+
+    impl<V: DeepSizeOf> DeepSizeOf for VecMap<V> {
+        /// The map is a vector with a slot for every key up to the largest
+        /// one, so empty slots are counted as well.
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                let child_sizes = self
+                    .values()
+                    .fold(0, |sum, value| sum + value.deep_size_of_children(context));
+                child_sizes + self.capacity() * size_of::<Option<V>>()
+            })
+        }
+    }
+}
+
+#[cfg(feature = "zerovec")]
+mod zerovec_impl {
+    use crate::{Context, DeepSizeOf};
+    use core::mem::size_of;
+    use zerovec::ule::{AsULE, VarULE};
+    use zerovec::vecs::VarZeroVecFormat;
+    use zerovec::{VarZeroVec, ZeroVec};
+
+    impl<T: AsULE> DeepSizeOf for ZeroVec<'_, T> {
+        /// Borrowed data is treated like a reference and isn't counted
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            self.owned_capacity()
+                .map_or(0, |capacity| capacity.get() * size_of::<T::ULE>())
+        }
+    }
+
+    impl<T: VarULE + ?Sized, F: VarZeroVecFormat> DeepSizeOf for VarZeroVec<'_, T, F> {
+        /// Borrowed data is treated like a reference and isn't counted
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            if self.is_owned() {
+                self.as_bytes().len()
+            } else {
+                0
+            }
+        }
+    }
+}
+
+#[cfg(feature = "tinystr")]
+mod tinystr_impl {
+    use crate::known_deep_size;
+    use tinystr::TinyAsciiStr;
+
+    known_deep_size!(0; {const N: usize} TinyAsciiStr<N>);
+}
+
+#[cfg(feature = "ustr")]
+mod ustr_impl {
+    use crate::known_deep_size;
+
+    // The strings are stored in a global cache and shared by every `Ustr`
+    // in the process, so they aren't counted; see `ustr_cache_size`.
+    known_deep_size!(0; ustr::Ustr);
+}
+
+#[cfg(feature = "bytestring")]
+mod bytestring_impl {
+    use crate::{Context, DeepSizeOf};
+    use bytestring::ByteString;
+
+    impl DeepSizeOf for ByteString {
+        /// The string is stored in a `Bytes`, so clones and slices are only
+        /// counted once, in the same way.
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            self.as_bytes().deep_size_of_children(context)
+        }
+    }
+}
+
+#[cfg(feature = "smallbox")]
+mod smallbox_impl {
+    use crate::{Context, DeepSizeOf};
+    use core::mem::size_of_val;
+    use smallbox::SmallBox;
+
+    impl<T: DeepSizeOf + ?Sized, Space> DeepSizeOf for SmallBox<T, Space> {
+        /// Values stored inline are counted as part of the `SmallBox`;
+        /// values that don't fit are boxed, and are counted like a `Box`.
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            let val: &T = self;
+            if self.is_heap() {
+                context.descend(|context| size_of_val(val) + val.deep_size_of_children(context))
+            } else {
+                val.deep_size_of_children(context)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "sharded-slab")]
+pub(crate) mod sharded_slab_impl {
+    use crate::{Context, DeepSizeOf};
+    use core::mem::size_of;
+    use sharded_slab::{Clear, Config, Pool, Slab};
+
+    /// Estimates the total size of a `sharded_slab::Slab`, including its
+    /// entries.
+    ///
+    /// The entries of a `Slab` can only be iterated with a unique reference,
+    /// so the `DeepSizeOf` impl only counts the slab's fixed overhead.  This
+    /// is an approximation: the slab allocates pages in each thread's shard,
+    /// starting with `C::INITIAL_PAGE_SIZE` slots and doubling for each new
+    /// page.  The iterator doesn't say which shard an entry is in, so this
+    /// assumes that every entry is in a single shard, and underestimates
+    /// slabs that are used from many threads.
+    pub fn sharded_slab_size<T, C>(slab: &mut Slab<T, C>, context: &mut Context) -> usize
+    where
+        T: DeepSizeOf,
+        C: Config,
+    {
+        let overhead = slab.deep_size_of_with_context(context);
+        context.descend(|context| {
+            let mut len = 0;
+            let mut children = 0;
+            for entry in slab.unique_iter() {
+                len += 1;
+                children += entry.deep_size_of_children(context);
+            }
+
+            // Each slot holds the entry, its lifecycle state, and a free list index
+            let slot_size = size_of::<Option<T>>() + 2 * size_of::<usize>();
+            let mut slots = 0;
+            let mut page_size = C::INITIAL_PAGE_SIZE;
+            while slots < len {
+                slots += page_size;
+                page_size *= 2;
+            }
+            overhead + slots * slot_size + children
+        })
+    }
+
+    // Both types allocate an array with a pointer to each thread's shard
+    // up front; the shards and their pages aren't reachable through a
+    // shared reference, so they aren't counted.
+    impl<T, C: Config> DeepSizeOf for Slab<T, C> {
+        /// **This only counts the slab's fixed overhead**, not its entries
+        /// or the pages that hold them, which can't be reached through a
+        /// shared reference.  Use [`sharded_slab_size`] with a unique
+        /// reference for an estimate that includes the entries.
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            C::MAX_THREADS * size_of::<usize>()
+        }
+    }
+
+    impl<T: Clear + Default, C: Config> DeepSizeOf for Pool<T, C> {
+        /// **This only counts the pool's fixed overhead**, not its entries
+        /// or the pages that hold them.  Unlike a `Slab`, a `Pool` can't be
+        /// iterated at all, so there is no way to estimate them.
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            C::MAX_THREADS * size_of::<usize>()
+        }
+    }
+}
+
+#[cfg(feature = "aho-corasick")]
+mod aho_corasick_impl {
+    use crate::{Context, DeepSizeOf};
+    use aho_corasick::automaton::Automaton;
+    use aho_corasick::{dfa, nfa, packed, AhoCorasick};
+
+    impl DeepSizeOf for AhoCorasick {
+        /// The automaton is shared between clones of an `AhoCorasick`, but
+        /// it isn't accessible, so each clone counts it.
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            self.memory_usage()
+        }
+    }
+
+    macro_rules! automaton_impls {
+        ($($automaton:ty),*) => {
+            $(
+                impl DeepSizeOf for $automaton {
+                    fn deep_size_of_children(&self, _: &mut Context) -> usize {
+                        Automaton::memory_usage(self)
+                    }
+                }
+            )*
+        };
+    }
+
+    automaton_impls!(dfa::DFA, nfa::contiguous::NFA, nfa::noncontiguous::NFA);
+
+    impl DeepSizeOf for packed::Searcher {
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            self.memory_usage()
+        }
+    }
+}
+
+#[cfg(feature = "smallstr")]
+mod smallstr_impl {
+    use crate::{Context, DeepSizeOf};
+    use smallstr::SmallString;
+
+    impl<A: smallvec::Array<Item = u8>> DeepSizeOf for SmallString<A> {
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            if self.spilled() {
+                self.capacity()
+            } else {
+                0
+            }
+        }
+    }
+}
+
+#[cfg(feature = "string_cache")]
+mod string_cache_impl {
+    use crate::{Context, DeepSizeOf};
+    use string_cache::{Atom, StaticAtomSet};
+
+    impl<Static: StaticAtomSet> DeepSizeOf for Atom<Static> {
+        /// Inline and static atoms don't own anything.  Dynamic atoms point
+        /// to a string in a global set, which is shared by every copy of
+        /// the atom, so it's tracked by its pointer and only counted once.
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            if !self.is_dynamic() {
+                return 0;
+            }
+            let ptr = self.as_ptr();
+            if context.contains_arc_ptr(ptr) {
+                0
+            } else {
+                context.add_arc_ptr(ptr);
+                self.len()
+            }
+        }
+    }
+}
+
+// The global interners can't be iterated from outside of `internment`,
+// so only the values reachable from each handle are counted.
+#[cfg(feature = "internment")]
+mod internment_impl {
+    use crate::{Context, DeepSizeOf};
+    use core::hash::Hash;
+    use core::mem::{size_of, size_of_val};
+    use core::sync::atomic::AtomicUsize;
+    use internment::{ArcIntern, Intern};
+
+    impl<T: ?Sized> DeepSizeOf for Intern<T> {
+        /// Interned values are leaked and shared by the whole process, so
+        /// they are treated like `&'static` references and aren't counted.
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            0
+        }
+    }
+
+    impl<T> DeepSizeOf for ArcIntern<T>
+    where
+        T: DeepSizeOf + Eq + Hash + Send + Sync + ?Sized,
+    {
+        /// Each value is stored in a reference counted allocation, which is
+        /// tracked like an `Arc` so that it is only counted once.
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            let val: &T = self;
+            let ptr: *const T = val;
+            if context.contains_arc_ptr(ptr) {
+                return 0;
+            }
+            context.add_arc_ptr(ptr);
+            context.descend(|context| {
+                size_of::<AtomicUsize>() + size_of_val(val) + val.deep_size_of_children(context)
+            })
+        }
+    }
+}
+
+#[cfg(feature = "futures")]
+mod futures_impl {
+    use crate::known_deep_size;
+    use futures::channel::{mpsc, oneshot};
+
+    // Channel handles share their buffer with the other end of the
+    // channel, and the queued messages can't be accessed, so only the
+    // handles themselves are counted.
+    known_deep_size!(0;
+        {T} mpsc::Sender<T>, {T} mpsc::Receiver<T>,
+        {T} mpsc::UnboundedSender<T>, {T} mpsc::UnboundedReceiver<T>,
+        {T} oneshot::Sender<T>, {T} oneshot::Receiver<T>,
+    );
+}
+
+#[cfg(feature = "ijson")]
+mod ijson_impl {
+    use crate::{Context, DeepSizeOf};
+    use core::mem::size_of;
+    use ijson::{DestructuredRef, IArray, INumber, IObject, IString, IValue};
+
+    impl DeepSizeOf for IValue {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            match self.destructure_ref() {
+                DestructuredRef::Null | DestructuredRef::Bool(_) => 0,
+                DestructuredRef::Number(num) => num.deep_size_of_children(context),
+                DestructuredRef::String(s) => s.deep_size_of_children(context),
+                DestructuredRef::Array(arr) => arr.deep_size_of_children(context),
+                DestructuredRef::Object(obj) => obj.deep_size_of_children(context),
+            }
+        }
+    }
+
+    impl DeepSizeOf for INumber {
+        /// Small numbers are packed into the value itself, and the rest are
+        /// boxed as an 8 byte `i64`, `u64` or `f64`.  The representation
+        /// isn't exposed, so this mirrors `ijson`'s inline encoding: integers
+        /// with a 56 bit mantissa, and floats that are a 56 bit mantissa
+        /// times `2^-7` to `2^7`.
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            const MANTISSA_LIMIT: i64 = 1 << 55;
+
+            let inline = if !self.has_decimal_point() {
+                self.to_i64()
+                    .is_some_and(|v| (-MANTISSA_LIMIT..MANTISSA_LIMIT).contains(&v))
+            } else {
+                let bits = self.to_f64_lossy().to_bits();
+                let raw_exp = ((bits >> 52) & 0x7ff) as i32;
+                let mut mantissa = bits & 0x000f_ffff_ffff_ffff;
+                let mut exp = if raw_exp == 0 {
+                    -1074
+                } else {
+                    mantissa |= 0x0010_0000_0000_0000;
+                    raw_exp - 1075
+                };
+                if mantissa == 0 {
+                    true
+                } else {
+                    exp += mantissa.trailing_zeros() as i32;
+                    mantissa >>= mantissa.trailing_zeros();
+                    (-7..=7).contains(&exp) && mantissa < MANTISSA_LIMIT as u64
+                }
+            };
+            if inline {
+                0
+            } else {
+                8
+            }
+        }
+    }
+
+    impl DeepSizeOf for IString {
+        /// Strings of up to 7 bytes are stored inline, and longer strings are
+        /// interned in a reference counted allocation, which is tracked like
+        /// an `Arc` so that it is only counted once.
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            let start = self as *const IString as usize;
+            let ptr = self.as_str().as_ptr();
+            if (start..start + size_of::<IString>()).contains(&(ptr as usize)) {
+                return 0;
+            }
+            if context.contains_arc_ptr(ptr) {
+                return 0;
+            }
+            context.add_arc_ptr(ptr);
+            // The reference count, and the packed length and cache shard
+            size_of::<usize>() + 8 + self.len()
+        }
+    }
+
+    impl DeepSizeOf for IArray {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                if self.capacity() == 0 {
+                    return 0;
+                }
+                // The length and capacity are stored in the allocation
+                let header = 2 * size_of::<usize>();
+                header
+                    + self.capacity() * size_of::<IValue>()
+                    + self
+                        .iter()
+                        .map(|value| value.deep_size_of_children(context))
+                        .sum::<usize>()
+            })
+        }
+    }
+
+    impl DeepSizeOf for IObject {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                let capacity = self.capacity();
+                if capacity == 0 {
+                    return 0;
+                }
+                // The length and capacity, followed by the entries and a hash
+                // table of indices with a load factor of 80%
+                let header = 2 * size_of::<usize>();
+                let table = (capacity + capacity / 4) * size_of::<usize>();
+                header
+                    + capacity * size_of::<(IString, IValue)>()
+                    + table
+                    + self
+                        .iter()
+                        .map(|(key, value)| {
+                            key.deep_size_of_children(context)
+                                + value.deep_size_of_children(context)
+                        })
+                        .sum::<usize>()
+            })
+        }
+    }
+}
+
+#[cfg(feature = "simd-json")]
+mod simd_json_impl {
+    use crate::{Context, DeepSizeOf};
+    use alloc::borrow::Cow;
+    use core::mem::{size_of, size_of_val};
+    use simd_json::value::{borrowed, owned};
+    use simd_json::{BorrowedValue, OwnedValue};
+
+    // The hasher of the object maps depends on simd-json's features, so the
+    // maps are only accessed through their type aliases.  Small maps are
+    // stored in a `Vec<(K, V)>`, and larger maps use a hashbrown table (with
+    // one control byte per slot).
+    macro_rules! object_size {
+        ($obj:expr, $entry:ty, $context:expr) => {{
+            let obj = $obj;
+            let slot_size = if obj.is_vec() {
+                size_of::<$entry>()
+            } else {
+                size_of::<$entry>() + 1
+            };
+            $context.descend(|context| {
+                size_of_val(obj)
+                    + obj.capacity() * slot_size
+                    + obj.iter().fold(0, |sum, (key, val)| {
+                        sum + key.deep_size_of_children(context)
+                            + val.deep_size_of_children(context)
+                    })
+            })
+        }};
+    }
+
+    impl DeepSizeOf for OwnedValue {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            match self {
+                OwnedValue::Static(_) => 0,
+                OwnedValue::String(s) => s.deep_size_of_children(context),
+                OwnedValue::Array(arr) => arr.deep_size_of_children(context),
+                OwnedValue::Object(obj) => {
+                    let obj: &owned::Object = obj;
+                    object_size!(obj, (String, OwnedValue), context)
+                }
+            }
+        }
+    }
+
+    impl DeepSizeOf for BorrowedValue<'_> {
+        /// Strings are unescaped in place, and borrowed from the parsed buffer,
+        /// so only strings that were changed to an owned `String` are counted.
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            match self {
+                BorrowedValue::Static(_) => 0,
+                BorrowedValue::String(s) => s.deep_size_of_children(context),
+                BorrowedValue::Array(arr) => arr.deep_size_of_children(context),
+                BorrowedValue::Object(obj) => {
+                    let obj: &borrowed::Object<'_> = obj;
+                    object_size!(obj, (Cow<'_, str>, BorrowedValue<'_>), context)
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "tinyset")]
+mod tinyset_impl {
+    use crate::{Context, DeepSizeOf};
+    use core::mem::size_of;
+    use tinyset::{Fits64, Set64, SetU32, SetU64};
+
+    impl DeepSizeOf for SetU32 {
+        /// Small sets are packed into the pointer, and `mem_used` includes
+        /// the set itself.
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            self.mem_used() - size_of::<Self>()
+        }
+    }
+
+    impl DeepSizeOf for SetU64 {
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            self.mem_used() - size_of::<Self>()
+        }
+    }
+
+    /// The number of elements that a `SetU64` can pack into its pointer
+    const INLINE_LEN: usize = 7;
+
+    impl<T: Fits64> DeepSizeOf for Set64<T> {
+        /// `Set64` doesn't expose its inner `SetU64`, so this is estimated
+        /// from its length.  Sets of up to seven elements are assumed to be
+        /// packed into the pointer, and larger sets are counted as one `u64`
+        /// bucket per element.  The bitmap formats that `tinyset` uses for
+        /// closely packed values are smaller than this, while hash sets of
+        /// widely spread values have some spare buckets.
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            if self.len() <= INLINE_LEN {
+                return 0;
+            }
+            self.len() * size_of::<u64>()
+        }
+    }
+}
+
+#[cfg(feature = "hibitset")]
+mod hibitset_impl {
+    use crate::{Context, DeepSizeOf};
+    use core::mem::size_of;
+    use hibitset::{AtomicBitSet, BitSet, BitSetLike};
+
+    impl DeepSizeOf for BitSet {
+        /// The lower three layers are each grown to fit the largest index
+        /// added, so this counts their lengths (the capacity isn't exposed).
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            (self.layer2_as_slice().len()
+                + self.layer1_as_slice().len()
+                + self.layer0_as_slice().len())
+                * size_of::<usize>()
+        }
+    }
+
+    impl DeepSizeOf for AtomicBitSet {
+        /// The upper layers are allocated up front, with each word of the
+        /// first layer owning a lazily allocated block of the bottom layer.
+        /// Blocks stay allocated when their bits are removed, so only the
+        /// blocks with bits set are counted.
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            let words = BitSet::BITS_PER_USIZE;
+            let layer2 = words * size_of::<usize>();
+            // Each block is a mask word and a pointer to the bottom layer
+            let layer1 = words * words * 2 * size_of::<usize>();
+            let layer0 = (0..words * words).filter(|&i| self.layer1(i) != 0).count()
+                * words
+                * size_of::<usize>();
+            layer2 + layer1 + layer0
+        }
+    }
+}
+
+#[cfg(feature = "generational-arena")]
+mod generational_arena_impl {
+    use crate::{known_deep_size, Context, DeepSizeOf};
+    use core::mem::size_of;
+    use generational_arena::{Arena, Index};
+
+    known_deep_size!(0; Index);
+
+    // Mirrors `generational_arena`'s internal `Entry` enum, where vacant
+    // entries form a free list and occupied entries store their generation.
+    enum MockEntry<T> {
+        _Free { next_free: Option<usize> },
+        _Occupied { generation: u64, value: T },
+    }
+
+    impl<T: DeepSizeOf> DeepSizeOf for Arena<T> {
+        /// `capacity()` is the number of entries, both free and occupied,
+        /// but only occupied entries can own any children.
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                let child_sizes = self
+                    .iter()
+                    .fold(0, |sum, (_, val)| sum + val.deep_size_of_children(context));
+                child_sizes + self.capacity() * size_of::<MockEntry<T>>()
+            })
+        }
+    }
+}
+
+#[cfg(feature = "id-arena")]
+mod id_arena_impl {
+    use crate::{known_deep_size, Context, DeepSizeOf};
+    use core::mem::size_of;
+    use id_arena::{Arena, ArenaBehavior, Id};
+
+    known_deep_size!(0; {T} Id<T>);
+
+    impl<T, A> DeepSizeOf for Arena<T, A>
+    where
+        T: DeepSizeOf,
+        A: ArenaBehavior,
+    {
+        /// Values are never removed from the arena, but its capacity isn't
+        /// exposed, so this only counts the allocated values.
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                let child_sizes = self
+                    .iter()
+                    .fold(0, |sum, (_, val)| sum + val.deep_size_of_children(context));
+                child_sizes + self.len() * size_of::<T>()
+            })
+        }
+    }
+}
+
+#[cfg(feature = "weak-table")]
+mod weak_table_impl {
+    use crate::{Context, DeepSizeOf};
+    use core::hash::{BuildHasher, Hash};
+    use core::mem::size_of;
+    use weak_table::traits::{WeakElement, WeakKey};
+    use weak_table::{WeakHashSet, WeakValueHashMap};
+
+    // Both tables are a hashbrown table of `(key, value)` slots, with one
+    // control byte per slot; weak keys also store their hash as a `u64`.
+    // Expired entries are removed lazily, so they are still counted in the
+    // table, but the values that they pointed to are not.
+
+    impl<T, S> DeepSizeOf for WeakHashSet<T, S>
+    where
+        T: WeakKey,
+        T::Strong: DeepSizeOf,
+        S: BuildHasher,
+    {
+        /// The live elements are only counted if
+        /// [`Context::set_count_weak_targets`] is enabled.
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                let targets = if context.count_weak_targets() {
+                    self.iter()
+                        .fold(0, |sum, elem| sum + elem.deep_size_of_children(context))
+                } else {
+                    0
+                };
+                targets + self.capacity() * (size_of::<(T, u64)>() + 1)
+            })
+        }
+    }
+
+    impl<K, V, S> DeepSizeOf for WeakValueHashMap<K, V, S>
+    where
+        K: DeepSizeOf + Eq + Hash,
+        V: WeakElement,
+        V::Strong: DeepSizeOf,
+        S: BuildHasher,
+    {
+        /// The keys are owned by the map, but the live values are only
+        /// counted if [`Context::set_count_weak_targets`] is enabled.
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                let count_targets = context.count_weak_targets();
+                self.iter().fold(0, |sum, (key, val)| {
+                    let val_size = if count_targets {
+                        val.deep_size_of_children(context)
+                    } else {
+                        0
+                    };
+                    sum + key.deep_size_of_children(context) + val_size
+                }) + self.capacity() * (size_of::<(K, V)>() + 1)
+            })
+        }
+    }
+}
+
+#[cfg(feature = "moka")]
+mod moka_impl {
+    use crate::{pair_bounds, sum_bounds, Context, DeepSizeOf};
+    use core::hash::{BuildHasher, Hash};
+    use core::mem::size_of;
+    use moka::sync::Cache;
+
+    /// An estimate of the metadata that `moka` allocates for each entry,
+    /// in words:
+    /// - 2 for the key's `Arc`
+    /// - 3 for the value entry (its reference count, and pointers to the
+    ///   entry info and deque nodes)
+    /// - 8 for the entry info (the key and its hash, flags, timestamps and
+    ///   weight)
+    /// - 6 for the deque nodes (a lock, and three optional node pointers)
+    /// - 3 for the node in the access order deque
+    /// - 4 for the concurrent hash table's bucket and its slot
+    /// - 1 for the frequency sketch
+    const ENTRY_OVERHEAD: usize = 27 * size_of::<usize>();
+
+    /// The concurrent hash table is resized by doubling, so it may have up
+    /// to twice as many buckets as entries, on top of those counted in
+    /// `ENTRY_OVERHEAD`.
+    const TABLE_SLACK: usize = 4 * size_of::<usize>();
+
+    impl<K, V, S> DeepSizeOf for Cache<K, V, S>
+    where
+        K: DeepSizeOf + Hash + Eq + Send + Sync + 'static,
+        V: DeepSizeOf + Clone + Send + Sync + 'static,
+        S: BuildHasher + Clone + Send + Sync + 'static,
+    {
+        /// The iterator yields clones of the values, which are measured in
+        /// place of the cached values.  The fixed overhead of the cache (such
+        /// as its housekeeping channels) isn't counted.
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                self.iter().fold(0, |sum, (key, val)| {
+                    sum + key.deep_size_of_children(context)
+                        + val.deep_size_of_children(context)
+                        + size_of::<V>()
+                        + ENTRY_OVERHEAD
+                })
+            })
+        }
+
+        /// The lower bound only counts the keys and values, and the upper
+        /// bound adds the slack of the hash table to the estimate.
+        fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+            context.descend(|context| {
+                let (lower, upper) = sum_bounds(
+                    self.iter()
+                        .map(|(key, val)| pair_bounds(&key, &val, context)),
+                );
+                let len = self.iter().count();
+                (
+                    lower + len * size_of::<V>(),
+                    upper + len * (size_of::<V>() + ENTRY_OVERHEAD + TABLE_SLACK),
+                )
+            })
+        }
+    }
+}
+
+#[cfg(feature = "quick_cache")]
+mod quick_cache_impl {
+    use crate::{Context, DeepSizeOf};
+    use core::hash::{BuildHasher, Hash};
+    use quick_cache::sync::Cache;
+    use quick_cache::{Lifecycle, Weighter};
+
+    impl<K, V, We, B, L> DeepSizeOf for Cache<K, V, We, B, L>
+    where
+        K: DeepSizeOf + Eq + Hash + Clone,
+        V: DeepSizeOf + Clone,
+        We: Weighter<K, V> + Clone,
+        B: BuildHasher + Clone,
+        L: Lifecycle<K, V> + Clone,
+    {
+        /// `memory_used` counts the entry slabs (including each entry's
+        /// metadata and any ghost entries) and the hash tables of every
+        /// shard.  The iterator yields clones of the keys and values, which
+        /// are measured in place of the cached ones.
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                self.iter()
+                    .fold(self.memory_used().total(), |sum, (key, val)| {
+                        sum + key.deep_size_of_children(context)
+                            + val.deep_size_of_children(context)
+                    })
+            })
+        }
+    }
+}
+
+#[cfg(feature = "kstring")]
+mod kstring_impl {
+    use crate::{known_deep_size, Context, DeepSizeOf};
+    use core::mem::size_of;
+    use kstring::backend::{ArcStr, BoxedStr, RcStr};
+    use kstring::{KStringBase, KStringRef};
+
+    known_deep_size!(0; KStringRef<'_>);
+
+    /// Checks if a string is stored inline, in the `KString` itself.
+    fn is_inline<B>(s: &KStringBase<B>, data: &str) -> bool {
+        let start = s as *const KStringBase<B> as usize;
+        (start..start + size_of::<KStringBase<B>>()).contains(&(data.as_ptr() as usize))
+    }
+
+    // Strings are either a `&'static str`, stored inline, or stored in the
+    // heap allocated backend.  `kstring` doesn't expose which of these a
+    // string uses, and only inline strings can be detected (by their
+    // address), so `'static` strings are counted as if they were owned.
+    // This overcounts strings created with `from_static`.
+    macro_rules! kstring_impls {
+        ($backend:ty, shared) => {
+            impl DeepSizeOf for KStringBase<$backend> {
+                /// Shared strings are tracked like an `Arc`, so that they are
+                /// only counted once.
+                fn deep_size_of_children(&self, context: &mut Context) -> usize {
+                    let data = self.as_str();
+                    if data.is_empty() || is_inline(self, data) {
+                        return 0;
+                    }
+                    if context.contains_arc_ptr(data.as_ptr()) {
+                        return 0;
+                    }
+                    context.add_arc_ptr(data.as_ptr());
+                    data.len()
+                }
+            }
+        };
+        ($backend:ty) => {
+            impl DeepSizeOf for KStringBase<$backend> {
+                fn deep_size_of_children(&self, _: &mut Context) -> usize {
+                    let data = self.as_str();
+                    if data.is_empty() || is_inline(self, data) {
+                        return 0;
+                    }
+                    data.len()
+                }
+            }
+        };
+    }
+
+    kstring_impls!(BoxedStr);
+    kstring_impls!(ArcStr, shared);
+    kstring_impls!(RcStr, shared);
+}
+
+#[cfg(feature = "sprs")]
+mod sprs_impl {
+    use crate::{Context, DeepSizeOf};
+    use core::mem::size_of_val;
+    use sprs::{CsMatI, CsVecI, SpIndex};
+
+    // The storage vectors are only accessible as slices, so this assumes
+    // that their capacity is the same as their length.
+
+    impl<N, I, Iptr> DeepSizeOf for CsMatI<N, I, Iptr>
+    where
+        N: DeepSizeOf,
+        I: SpIndex,
+        Iptr: SpIndex,
+    {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                let indptr = size_of_val(self.indptr().raw_storage());
+                let indices = size_of_val(self.indices());
+                let data = size_of_val(self.data()) + self.data().deep_size_of_children(context);
+                indptr + indices + data
+            })
+        }
+    }
+
+    impl<N, I> DeepSizeOf for CsVecI<N, I>
+    where
+        N: DeepSizeOf,
+        I: SpIndex,
+    {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                let indices = size_of_val(self.indices());
+                let data = size_of_val(self.data()) + self.data().deep_size_of_children(context);
+                indices + data
+            })
+        }
+    }
+}
+
+#[cfg(feature = "half")]
+mod half_impl {
+    use crate::known_deep_size;
+
+    known_deep_size!(0; half::f16, half::bf16);
+}
+
+#[cfg(feature = "fixed")]
+mod fixed_impl {
+    use crate::known_deep_size;
+    use fixed::{
+        FixedI128, FixedI16, FixedI32, FixedI64, FixedI8, FixedU128, FixedU16, FixedU32, FixedU64,
+        FixedU8,
+    };
+
+    // The number of fractional bits is only a type parameter, so these
+    // don't require the `LeEqU*` bounds.
+    known_deep_size!(0;
+        {Frac} FixedI8<Frac>, {Frac} FixedI16<Frac>, {Frac} FixedI32<Frac>,
+        {Frac} FixedI64<Frac>, {Frac} FixedI128<Frac>,
+        {Frac} FixedU8<Frac>, {Frac} FixedU16<Frac>, {Frac} FixedU32<Frac>,
+        {Frac} FixedU64<Frac>, {Frac} FixedU128<Frac>,
+        {F} fixed::Wrapping<F>, {F} fixed::Saturating<F>, {F} fixed::Strict<F>,
+    );
+}
+
+#[cfg(feature = "jiff")]
+mod jiff_impl {
+    use crate::known_deep_size;
+    use jiff::civil::{Date, DateTime, ISOWeekDate, Time, Weekday};
+    use jiff::tz::{Offset, TimeZone};
+    use jiff::{SignedDuration, Span, Timestamp, Zoned};
+
+    // A `TimeZone` may point to reference counted tzdb or POSIX rules, but
+    // that data is shared by every value in the same zone (and cached by the
+    // tz database), and its pointer isn't exposed, so it is treated as an
+    // amortized zero cost, the same as `chrono`'s `Tz` parameter.
+    known_deep_size!(0;
+        Timestamp, Zoned, Span, SignedDuration,
+        Date, Time, DateTime, ISOWeekDate, Weekday,
+        Offset, TimeZone,
+    );
+}
+
+#[cfg(feature = "rkyv")]
+mod rkyv_impl {
+    use crate::{known_deep_size, Context, DeepSizeOf};
+    use rkyv::boxed::ArchivedBox;
+    use rkyv::string::ArchivedString;
+    use rkyv::traits::ArchivePointee;
+    use rkyv::util::{Align, AlignedVec};
+    use rkyv::vec::ArchivedVec;
+
+    impl<const ALIGNMENT: usize> DeepSizeOf for AlignedVec<ALIGNMENT> {
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            self.capacity()
+        }
+    }
+
+    impl<T: DeepSizeOf> DeepSizeOf for Align<T> {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            self.0.deep_size_of_children(context)
+        }
+    }
+
+    // Archived types only hold relative pointers into the buffer that
+    // contains them, so their contents are counted by that buffer (usually
+    // an `AlignedVec`), and not again by each view into it.
+    known_deep_size!(0;
+        ArchivedString,
+        {T} ArchivedVec<T>,
+        {T: ArchivePointee + ?Sized} ArchivedBox<T>,
+    );
+}
+
+#[cfg(feature = "geo-types")]
+mod geo_types_impl {
+    use crate::{known_deep_size, Context, DeepSizeOf};
+    use core::mem::size_of_val;
+    use geo_types::*;
+
+    // Coordinate numbers are `Copy`, so they can't own any heap data.
+    known_deep_size!(0;
+        {T: CoordNum} Coord<T>, {T: CoordNum} Point<T>, {T: CoordNum} Line<T>,
+        {T: CoordNum} Rect<T>, {T: CoordNum} Triangle<T>,
+    );
+
+    impl<T: CoordNum> DeepSizeOf for LineString<T> {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| self.0.deep_size_of_children(context))
+        }
+    }
+
+    impl<T: CoordNum> DeepSizeOf for Polygon<T> {
+        /// The capacity of the interior rings isn't exposed, so only their
+        /// length is counted.
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                self.exterior().deep_size_of_children(context)
+                    + size_of_val(self.interiors())
+                    + self
+                        .interiors()
+                        .iter()
+                        .map(|ring| ring.deep_size_of_children(context))
+                        .sum::<usize>()
+            })
+        }
+    }
+
+    impl<T: CoordNum> DeepSizeOf for MultiPoint<T> {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| self.0.deep_size_of_children(context))
+        }
+    }
+
+    impl<T: CoordNum> DeepSizeOf for MultiLineString<T> {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| self.0.deep_size_of_children(context))
+        }
+    }
+
+    impl<T: CoordNum> DeepSizeOf for MultiPolygon<T> {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| self.0.deep_size_of_children(context))
+        }
+    }
+
+    impl<T: CoordNum> DeepSizeOf for GeometryCollection<T> {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| self.0.deep_size_of_children(context))
+        }
+    }
+
+    impl<T: CoordNum> DeepSizeOf for Geometry<T> {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| match self {
+                Geometry::Point(_)
+                | Geometry::Line(_)
+                | Geometry::Rect(_)
+                | Geometry::Triangle(_) => 0,
+                Geometry::LineString(g) => g.deep_size_of_children(context),
+                Geometry::Polygon(g) => g.deep_size_of_children(context),
+                Geometry::MultiPoint(g) => g.deep_size_of_children(context),
+                Geometry::MultiLineString(g) => g.deep_size_of_children(context),
+                Geometry::MultiPolygon(g) => g.deep_size_of_children(context),
+                Geometry::GeometryCollection(g) => g.deep_size_of_children(context),
+            })
+        }
+    }
+}
+
+#[cfg(feature = "rstar")]
+mod rstar_impl {
+    use crate::{sum_bounds, Context, DeepSizeOf};
+    use core::mem::size_of;
+    use rstar::{RTree, RTreeNode, RTreeObject, RTreeParams};
+
+    /// The number of nodes in a tree of `size` elements, when each node
+    /// holds `fill` children
+    fn node_count(size: usize, fill: usize) -> usize {
+        1 + size / fill.saturating_sub(1).max(1)
+    }
+
+    impl<T, Params> DeepSizeOf for RTree<T, Params>
+    where
+        T: RTreeObject + DeepSizeOf,
+        Params: RTreeParams,
+    {
+        /// The node hierarchy isn't exposed, so its size is an approximation:
+        /// nodes are assumed to be filled halfway between the minimum and
+        /// maximum node size, and each node's child list to have room for
+        /// `MAX_SIZE + 1` entries, as is allocated when nodes are split.
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                let fill = (Params::MIN_SIZE + Params::MAX_SIZE) / 2;
+                let nodes = node_count(self.size(), fill);
+                let node_size = (Params::MAX_SIZE + 1) * size_of::<RTreeNode<T>>();
+
+                nodes * node_size
+                    + self
+                        .iter()
+                        .map(|elem| elem.deep_size_of_children(context))
+                        .sum::<usize>()
+            })
+        }
+
+        /// At best every node is full, and at worst every node holds only
+        /// `MIN_SIZE` children.
+        fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+            context.descend(|context| {
+                let (lower, upper) = sum_bounds(
+                    self.iter()
+                        .map(|elem| elem.deep_size_of_children_approx(context)),
+                );
+                let node_size = (Params::MAX_SIZE + 1) * size_of::<RTreeNode<T>>();
+                (
+                    lower + node_count(self.size(), Params::MAX_SIZE) * node_size,
+                    upper + node_count(self.size(), Params::MIN_SIZE) * node_size,
+                )
+            })
+        }
+    }
+}
+
+#[cfg(feature = "csv")]
+mod csv_impl {
+    use crate::{known_deep_size, Context, DeepSizeOf};
+    use core::mem::size_of;
+    use csv::{ByteRecord, Position, StringRecord};
+
+    known_deep_size!(0; Position);
+
+    impl DeepSizeOf for ByteRecord {
+        /// The record's buffers aren't exposed, so this counts the boxed
+        /// header, the bytes of the fields, and one end index per field,
+        /// which is a lower bound for records that have been reused.
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            // Option<Position>, the field buffer, and the field ends and count
+            let header = size_of::<Option<Position>>()
+                + size_of::<Vec<u8>>()
+                + size_of::<Vec<usize>>()
+                + size_of::<usize>();
+            header + self.as_slice().len() + self.len() * size_of::<usize>()
+        }
+    }
+
+    impl DeepSizeOf for StringRecord {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            self.as_byte_record().deep_size_of_children(context)
+        }
+    }
+}
+
+#[cfg(feature = "crossbeam-skiplist")]
+mod crossbeam_skiplist_impl {
+    use crate::{pair_bounds, sum_bounds, Context, DeepSizeOf};
+    use core::mem::size_of;
+    use crossbeam_skiplist::{SkipMap, SkipSet};
+
+    // Mirrors `crossbeam_skiplist`'s internal `Node`, with a tower of two
+    // pointers: tower heights are chosen with a 1/2 probability of growing
+    // each level, so nodes have two levels on average (and at most 32).
+    struct MockNode<K, V> {
+        _value: V,
+        _key: K,
+        _refs_and_height: usize,
+        _tower: [usize; 2],
+    }
+
+    /// The maximum height of a tower
+    const MAX_HEIGHT: usize = 32;
+
+    /// Bounds the size of a node, with a tower of one level at best, and
+    /// of `MAX_HEIGHT` levels at worst
+    fn node_bounds<K, V>() -> (usize, usize) {
+        let size = size_of::<MockNode<K, V>>();
+        (
+            size - size_of::<usize>(),
+            size + (MAX_HEIGHT - 2) * size_of::<usize>(),
+        )
+    }
+
+    impl<K: Ord + DeepSizeOf, V: DeepSizeOf> DeepSizeOf for SkipMap<K, V> {
+        /// Tower heights are random, so this counts the expected size of
+        /// each node, rather than the exact size.
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                self.iter().fold(0, |sum, entry| {
+                    sum + size_of::<MockNode<K, V>>()
+                        + entry.key().deep_size_of_children(context)
+                        + entry.value().deep_size_of_children(context)
+                })
+            })
+        }
+
+        fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+            context.descend(|context| {
+                let (node_lower, node_upper) = node_bounds::<K, V>();
+                sum_bounds(self.iter().map(|entry| {
+                    let (lower, upper) = pair_bounds(entry.key(), entry.value(), context);
+                    (lower + node_lower, upper + node_upper)
+                }))
+            })
+        }
+    }
+
+    impl<K: Ord + DeepSizeOf> DeepSizeOf for SkipSet<K> {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                self.iter().fold(0, |sum, entry| {
+                    sum + size_of::<MockNode<K, ()>>()
+                        + entry.value().deep_size_of_children(context)
+                })
+            })
+        }
+
+        fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+            context.descend(|context| {
+                let (node_lower, node_upper) = node_bounds::<K, ()>();
+                sum_bounds(self.iter().map(|entry| {
+                    let (lower, upper) = entry.value().deep_size_of_children_approx(context);
+                    (lower + node_lower, upper + node_upper)
+                }))
+            })
+        }
+    }
+}
+
+#[cfg(feature = "scc")]
+mod scc_impl {
+    use crate::{Context, DeepSizeOf};
+    use core::hash::{BuildHasher, Hash};
+    use core::mem::size_of;
+    use scc::{HashIndex, HashMap};
+
+    // Mirrors `scc`'s internal `Bucket` header; each bucket is paired with
+    // a data block holding `BUCKET_LEN` keys and values.
+    const BUCKET_LEN: usize = 32;
+    struct MockBucket {
+        _len: usize,
+        _lock: usize,
+        _occupied: u32,
+        _removed: u32,
+        _partial_hashes: [u8; BUCKET_LEN],
+        _link: usize,
+    }
+
+    fn bucket_array_size<K, V>(capacity: usize) -> usize {
+        (capacity / BUCKET_LEN) * size_of::<MockBucket>()
+            + capacity * (size_of::<K>() + size_of::<V>())
+    }
+
+    impl<K, V, H> DeepSizeOf for HashMap<K, V, H>
+    where
+        K: DeepSizeOf + Eq + Hash,
+        V: DeepSizeOf,
+        H: BuildHasher,
+    {
+        /// The bucket array is estimated from the capacity, without any
+        /// overflow buckets, and entries that have been removed but are
+        /// still waiting for epoch reclamation are not counted.
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                let mut size = bucket_array_size::<K, V>(self.capacity());
+                self.iter_sync(|key, value| {
+                    size +=
+                        key.deep_size_of_children(context) + value.deep_size_of_children(context);
+                    true
+                });
+                size
+            })
+        }
+    }
+
+    impl<K, V, H> DeepSizeOf for HashIndex<K, V, H>
+    where
+        K: DeepSizeOf + Eq + Hash,
+        V: DeepSizeOf,
+        H: BuildHasher,
+    {
+        /// Estimated the same way as `HashMap`; entries that have been
+        /// removed or replaced may still be waiting for reclamation, and are
+        /// not counted.
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                let mut size = bucket_array_size::<K, V>(self.capacity());
+                self.iter_sync(|key, value| {
+                    size +=
+                        key.deep_size_of_children(context) + value.deep_size_of_children(context);
+                    true
+                });
+                size
+            })
+        }
+    }
+}
+
+#[cfg(feature = "ringbuf")]
+mod ringbuf_impl {
+    use crate::{Context, DeepSizeOf};
+    use core::mem::size_of;
+    use ringbuf::traits::{Consumer, Observer};
+    use ringbuf::wrap::Wrap;
+    use ringbuf::{HeapCons, HeapProd, HeapRb};
+
+    impl<T: DeepSizeOf> DeepSizeOf for HeapRb<T> {
+        /// The whole ring is allocated up front, so this counts the capacity
+        /// regardless of occupancy.  The children of queued elements are only
+        /// counted while there is no separate consumer half, which could be
+        /// popping them concurrently.
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                let ring = self.capacity().get() * size_of::<T>();
+                if self.read_is_held() {
+                    return ring;
+                }
+                let (first, second) = self.as_slices();
+                ring + first.deep_size_of_children(context) + second.deep_size_of_children(context)
+            })
+        }
+    }
+
+    // Both halves share the ring through an `Arc`, so it's only counted once.
+    impl<T: DeepSizeOf> DeepSizeOf for HeapProd<T> {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            self.rb_ref().deep_size_of_children(context)
+        }
+    }
+
+    impl<T: DeepSizeOf> DeepSizeOf for HeapCons<T> {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            self.rb_ref().deep_size_of_children(context)
+        }
+    }
+}
+
+#[cfg(feature = "hdrhistogram")]
+mod hdrhistogram_impl {
+    use crate::{Context, DeepSizeOf};
+    use core::mem::size_of;
+    use hdrhistogram::{Counter, Histogram};
+
+    impl<T: Counter> DeepSizeOf for Histogram<T> {
+        /// The counts array has one counter per distinct value the histogram
+        /// can represent, which depends on its range and precision.
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            self.distinct_values() * size_of::<T>()
+        }
+    }
+}
+
+#[cfg(feature = "bloomfilter")]
+mod bloomfilter_impl {
+    use crate::{Context, DeepSizeOf};
+    use bloomfilter::Bloom;
+
+    impl<T: ?Sized> DeepSizeOf for Bloom<T> {
+        /// The bitmap is stored after a small header, in a single buffer.
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            self.as_slice().len()
+        }
+    }
+}
+
+#[cfg(feature = "growable-bloom-filter")]
+pub(crate) mod growable_bloom_filter_impl {
+    use crate::{Context, DeepSizeOf};
+    use core::fmt;
+    use core::mem::size_of;
+    use growable_bloom_filter::GrowableBloom;
+    use serde::ser::{Impossible, Serialize, SerializeSeq, SerializeStruct, Serializer};
+
+    // Mirrors `growable_bloom_filter`'s internal `Bloom`
+    struct MockBloom {
+        _buffer: Box<[u8]>,
+        _num_slices: u64,
+    }
+
+    impl DeepSizeOf for GrowableBloom {
+        /// The inner filters aren't exposed, so they are found by
+        /// serializing the filter, and summing the lengths of the bit
+        /// buffers, without copying them anywhere.  The capacity alone
+        /// isn't enough to size the filters, since each one also depends on
+        /// the error ratio and growth parameters, which are private.
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            let mut counter = BufferCounter::default();
+            // This only fails if the serialized format changes, in which case
+            // the buffers counted so far are still a lower bound.
+            let _ = self.serialize(&mut counter);
+            counter.filters * size_of::<MockBloom>() + counter.bytes
+        }
+    }
+
+    /// Whether the filter serializes in the format that `BufferCounter`
+    /// expects, so that the tests catch a change in a new release.
+    #[cfg(test)]
+    pub(crate) fn format_is_known(bloom: &GrowableBloom) -> bool {
+        bloom.serialize(&mut BufferCounter::default()).is_ok()
+    }
+
+    /// A serializer that only records the length of byte buffers, and the
+    /// number of elements in sequences.
+    #[derive(Default)]
+    struct BufferCounter {
+        bytes: usize,
+        filters: usize,
+    }
+
+    macro_rules! ignore_values {
+        ($($method:ident($ty:ty)),* $(,)?) => {
+            $(
+                fn $method(self, _: $ty) -> Result<(), fmt::Error> {
+                    Ok(())
+                }
+            )*
+        };
+    }
+
+    impl Serializer for &mut BufferCounter {
+        type Ok = ();
+        type Error = fmt::Error;
+        type SerializeSeq = Self;
+        type SerializeTuple = Impossible<(), fmt::Error>;
+        type SerializeTupleStruct = Impossible<(), fmt::Error>;
+        type SerializeTupleVariant = Impossible<(), fmt::Error>;
+        type SerializeMap = Impossible<(), fmt::Error>;
+        type SerializeStruct = Self;
+        type SerializeStructVariant = Impossible<(), fmt::Error>;
+
+        ignore_values!(
+            serialize_bool(bool),
+            serialize_i8(i8),
+            serialize_i16(i16),
+            serialize_i32(i32),
+            serialize_i64(i64),
+            serialize_u8(u8),
+            serialize_u16(u16),
+            serialize_u32(u32),
+            serialize_u64(u64),
+            serialize_f32(f32),
+            serialize_f64(f64),
+            serialize_char(char),
+            serialize_str(&str),
+            serialize_unit_struct(&'static str),
+        );
+
+        fn serialize_bytes(self, v: &[u8]) -> Result<(), fmt::Error> {
+            self.bytes += v.len();
+            Ok(())
+        }
+
+        fn serialize_none(self) -> Result<(), fmt::Error> {
+            Ok(())
+        }
+
+        fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), fmt::Error> {
+            value.serialize(self)
+        }
+
+        fn serialize_unit(self) -> Result<(), fmt::Error> {
+            Ok(())
+        }
+
+        fn serialize_unit_variant(
+            self,
+            _: &'static str,
+            _: u32,
+            _: &'static str,
+        ) -> Result<(), fmt::Error> {
+            Ok(())
+        }
+
+        fn serialize_newtype_struct<T: Serialize + ?Sized>(
+            self,
+            _: &'static str,
+            value: &T,
+        ) -> Result<(), fmt::Error> {
+            value.serialize(self)
+        }
+
+        fn serialize_newtype_variant<T: Serialize + ?Sized>(
+            self,
+            _: &'static str,
+            _: u32,
+            _: &'static str,
+            value: &T,
+        ) -> Result<(), fmt::Error> {
+            value.serialize(self)
+        }
+
+        fn serialize_seq(self, _: Option<usize>) -> Result<Self, fmt::Error> {
+            Ok(self)
+        }
+
+        fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple, fmt::Error> {
+            Err(fmt::Error)
+        }
+
+        fn serialize_tuple_struct(
+            self,
+            _: &'static str,
+            _: usize,
+        ) -> Result<Self::SerializeTupleStruct, fmt::Error> {
+            Err(fmt::Error)
+        }
+
+        fn serialize_tuple_variant(
+            self,
+            _: &'static str,
+            _: u32,
+            _: &'static str,
+            _: usize,
+        ) -> Result<Self::SerializeTupleVariant, fmt::Error> {
+            Err(fmt::Error)
+        }
+
+        fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, fmt::Error> {
+            Err(fmt::Error)
+        }
+
+        fn serialize_struct(self, _: &'static str, _: usize) -> Result<Self, fmt::Error> {
+            Ok(self)
+        }
+
+        fn serialize_struct_variant(
+            self,
+            _: &'static str,
+            _: u32,
+            _: &'static str,
+            _: usize,
+        ) -> Result<Self::SerializeStructVariant, fmt::Error> {
+            Err(fmt::Error)
+        }
+    }
+
+    impl SerializeSeq for &mut BufferCounter {
+        type Ok = ();
+        type Error = fmt::Error;
+
+        fn serialize_element<T: Serialize + ?Sized>(
+            &mut self,
+            value: &T,
+        ) -> Result<(), fmt::Error> {
+            self.filters += 1;
+            value.serialize(&mut **self)
+        }
+
+        fn end(self) -> Result<(), fmt::Error> {
+            Ok(())
+        }
+    }
+
+    impl SerializeStruct for &mut BufferCounter {
+        type Ok = ();
+        type Error = fmt::Error;
+
+        fn serialize_field<T: Serialize + ?Sized>(
+            &mut self,
+            _: &'static str,
+            value: &T,
+        ) -> Result<(), fmt::Error> {
+            value.serialize(&mut **self)
+        }
+
+        fn end(self) -> Result<(), fmt::Error> {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(feature = "fst")]
+mod fst_impl {
+    use crate::{Context, DeepSizeOf};
+    use fst::raw::Fst;
+    use fst::{Map, Set};
+
+    // The size comes from the backing storage, so an owned buffer counts its
+    // bytes, a borrowed slice counts nothing, and a memory map follows the
+    // context's mapped memory setting.
+    impl<D: AsRef<[u8]> + DeepSizeOf> DeepSizeOf for Fst<D> {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            self.as_inner().deep_size_of_children(context)
+        }
+    }
+
+    impl<D: AsRef<[u8]> + DeepSizeOf> DeepSizeOf for Map<D> {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            self.as_fst().deep_size_of_children(context)
+        }
+    }
+
+    impl<D: AsRef<[u8]> + DeepSizeOf> DeepSizeOf for Set<D> {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            self.as_fst().deep_size_of_children(context)
+        }
+    }
+}
+
+#[cfg(feature = "indextree")]
+mod indextree_impl {
+    use crate::{known_deep_size, Context, DeepSizeOf};
+    use core::mem::size_of;
+    use indextree::{Arena, Node, NodeId};
+
+    known_deep_size!(0; NodeId);
+
+    impl<T: DeepSizeOf> DeepSizeOf for Arena<T> {
+        /// Every slot in the node `Vec` is counted, including the slots of
+        /// removed nodes waiting to be reused, but only live nodes have a
+        /// value to measure.
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                self.capacity() * size_of::<Node<T>>()
+                    + self
+                        .iter()
+                        .filter(|node| !node.is_removed())
+                        .map(|node| node.get().deep_size_of_children(context))
+                        .sum::<usize>()
+            })
+        }
+    }
+}
+
+#[cfg(feature = "ego-tree")]
+mod ego_tree_impl {
+    use crate::{known_deep_size, Context, DeepSizeOf};
+    use core::mem::size_of;
+    use ego_tree::{NodeId, Tree};
+
+    known_deep_size!(0; NodeId);
+
+    // Mirrors `ego_tree`'s internal `Node`
+    struct MockNode<T> {
+        _parent: Option<NodeId>,
+        _prev_sibling: Option<NodeId>,
+        _next_sibling: Option<NodeId>,
+        _children: Option<(NodeId, NodeId)>,
+        _value: T,
+    }
+
+    impl<T: DeepSizeOf> DeepSizeOf for Tree<T> {
+        /// The capacity of the node `Vec` isn't exposed, so only its length
+        /// is counted.  Detached nodes are never removed from the `Vec`, so
+        /// they are counted as well.
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                self.values().fold(0, |sum, value| {
+                    sum + size_of::<MockNode<T>>() + value.deep_size_of_children(context)
+                })
+            })
+        }
+    }
+}
+
+#[cfg(feature = "qp-trie")]
+mod qp_trie_impl {
+    use crate::{pair_bounds, sum_bounds, Context, DeepSizeOf};
+    use core::mem::size_of;
+    use qp_trie::Trie;
+
+    // Mirrors `qp_trie`'s internal `Node`: entries are stored in leaves,
+    // and branches keep their children in a sparse `Vec`.
+    #[allow(dead_code)]
+    enum MockNode<K, V> {
+        Leaf(K, V),
+        Branch(usize, u32, Vec<MockNode<K, V>>),
+    }
+
+    impl<K: DeepSizeOf, V: DeepSizeOf> DeepSizeOf for Trie<K, V> {
+        /// The nodes aren't exposed, so this is an approximation: with
+        /// 4-bit branching, there are about as many branches as there are
+        /// entries, so two nodes are counted per entry.
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                self.iter().fold(0, |sum, (key, val)| {
+                    sum + 2 * size_of::<MockNode<K, V>>()
+                        + key.deep_size_of_children(context)
+                        + val.deep_size_of_children(context)
+                })
+            })
+        }
+
+        /// Every entry has its own leaf, and since each branch has at least
+        /// two children, there are fewer branches than leaves.
+        fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+            context.descend(|context| {
+                let node_size = size_of::<MockNode<K, V>>();
+                sum_bounds(self.iter().map(|(key, val)| {
+                    let (lower, upper) = pair_bounds(key, val, context);
+                    (lower + node_size, upper + 2 * node_size)
+                }))
+            })
+        }
+    }
+}
+
+#[cfg(feature = "radix_trie")]
+mod radix_trie_impl {
+    use crate::{pair_bounds, sum_bounds, Context, DeepSizeOf};
+    use core::mem::size_of;
+    use radix_trie::{NibbleVec, Trie, TrieCommon, TrieKey};
+
+    // Mirrors `radix_trie`'s internal `TrieNode`, which stores its entry in
+    // a separate box.
+    struct MockNode {
+        _key: NibbleVec<[u8; 64]>,
+        _key_value: Option<Box<()>>,
+        _child_count: usize,
+        _children: [Option<Box<()>>; 16],
+    }
+
+    impl<K: TrieKey + DeepSizeOf, V: DeepSizeOf> DeepSizeOf for Trie<K, V> {
+        /// The nodes aren't exposed, so this is an approximation: each entry
+        /// is counted with its own node and about one branching node, and
+        /// long key fragments that spill out of a node aren't counted.
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                self.iter().fold(0, |sum, (key, val)| {
+                    sum + 2 * size_of::<MockNode>()
+                        + size_of::<(K, V)>()
+                        + key.deep_size_of_children(context)
+                        + val.deep_size_of_children(context)
+                })
+            })
+        }
+
+        /// Every entry has its own node, and since each branching node has
+        /// at least two children, there is at most one branching node per
+        /// entry.  Spilled key fragments aren't counted in either bound.
+        fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+            context.descend(|context| {
+                let entry_size = size_of::<MockNode>() + size_of::<(K, V)>();
+                sum_bounds(self.iter().map(|(key, val)| {
+                    let (lower, upper) = pair_bounds(key, val, context);
+                    (
+                        lower + entry_size,
+                        upper + entry_size + size_of::<MockNode>(),
+                    )
+                }))
+            })
+        }
+    }
+}
+
+#[cfg(feature = "hickory-proto")]
+mod hickory_proto_impl {
+    use crate::{known_deep_size, Context, DeepSizeOf};
+    use core::mem::size_of_val;
+    use hickory_proto::rr::rdata::{A, AAAA, CNAME, MX, NS, PTR, SOA, TXT};
+    use hickory_proto::rr::{DNSClass, Name, RData, Record, RecordData, RecordType};
+
+    known_deep_size!(0; A, AAAA, DNSClass, RecordType);
+
+    impl DeepSizeOf for Name {
+        /// Labels are stored inline for names of up to 32 bytes of label data
+        /// and 24 labels; beyond that, the spilled buffers are counted by
+        /// their length, as their capacity isn't exposed.
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            let label_data: usize = self.iter().map(|label| label.len()).sum();
+            let label_ends = usize::from(self.num_labels());
+            let data = if label_data > 32 { label_data } else { 0 };
+            let ends = if label_ends > 24 { label_ends } else { 0 };
+            data + ends
+        }
+    }
+
+    macro_rules! name_rdata_impls {
+        ($($type:ty),*) => {
+            $(
+                impl DeepSizeOf for $type {
+                    fn deep_size_of_children(&self, context: &mut Context) -> usize {
+                        self.0.deep_size_of_children(context)
+                    }
+                }
+            )*
+        };
+    }
+
+    name_rdata_impls!(CNAME, NS, PTR);
+
+    impl DeepSizeOf for MX {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            self.exchange.deep_size_of_children(context)
+        }
+    }
+
+    impl DeepSizeOf for SOA {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            self.mname.deep_size_of_children(context) + self.rname.deep_size_of_children(context)
+        }
+    }
+
+    impl DeepSizeOf for TXT {
+        fn deep_size_of_children(&self, _: &mut Context) -> usize {
+            size_of_val(&*self.txt_data)
+                + self.txt_data.iter().map(|data| data.len()).sum::<usize>()
+        }
+    }
+
+    impl DeepSizeOf for RData {
+        /// Only the common record types are measured; the children of
+        /// other record data are not counted.
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            match self {
+                RData::A(_) | RData::AAAA(_) => 0,
+                RData::CNAME(data) => data.deep_size_of_children(context),
+                RData::NS(data) => data.deep_size_of_children(context),
+                RData::PTR(data) => data.deep_size_of_children(context),
+                RData::MX(data) => data.deep_size_of_children(context),
+                RData::SOA(data) => data.deep_size_of_children(context),
+                RData::TXT(data) => data.deep_size_of_children(context),
+                _ => 0,
+            }
+        }
+    }
+
+    impl<R: RecordData + DeepSizeOf> DeepSizeOf for Record<R> {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            self.name.deep_size_of_children(context) + self.data.deep_size_of_children(context)
+        }
+    }
+}
+
+#[cfg(feature = "ciborium")]
+mod ciborium_impl {
+    use crate::{known_deep_size, Context, DeepSizeOf};
+    use ciborium::value::{Integer, Value};
+
+    known_deep_size!(0; Integer);
+
+    impl DeepSizeOf for Value {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            match self {
+                Value::Bytes(bytes) => bytes.deep_size_of_children(context),
+                Value::Text(text) => text.deep_size_of_children(context),
+                Value::Tag(_, value) => value.deep_size_of_children(context),
+                Value::Array(array) => array.deep_size_of_children(context),
+                Value::Map(map) => map.deep_size_of_children(context),
+                _ => 0,
+            }
+        }
+    }
+}
+
+#[cfg(feature = "serde_cbor")]
+mod serde_cbor_impl {
+    use crate::{Context, DeepSizeOf};
+    use serde_cbor::Value;
+
+    impl DeepSizeOf for Value {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            match self {
+                Value::Bytes(bytes) => bytes.deep_size_of_children(context),
+                Value::Text(text) => text.deep_size_of_children(context),
+                Value::Array(array) => array.deep_size_of_children(context),
+                Value::Map(map) => map.deep_size_of_children(context),
+                Value::Tag(_, value) => value.deep_size_of_children(context),
+                _ => 0,
+            }
+        }
+    }
+}
+
+#[cfg(all(feature = "elsa", feature = "std"))]
+mod elsa_impl {
+    use crate::{btree_bounds, btree_size, hash_table_upper_bound, Context, DeepSizeOf};
+    use core::hash::Hash;
+    use core::mem::{size_of, size_of_val};
+    use elsa::{FrozenBTreeMap, FrozenMap, FrozenVec};
+    use stable_deref_trait::StableDeref;
+
+    impl<T> DeepSizeOf for FrozenVec<T>
+    where
+        T: StableDeref,
+        T::Target: DeepSizeOf,
+    {
+        /// The capacity of the inner `Vec` isn't exposed, so only its length
+        /// is counted, plus the allocation each element points to.
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            context.descend(|context| {
+                self.iter().fold(self.len() * size_of::<T>(), |sum, elem| {
+                    sum + size_of_val(elem) + elem.deep_size_of_children(context)
+                })
+            })
+        }
+    }
+
+    // The maps can't be read through a shared reference without a
+    // `StableDeref` value, and can't be iterated at all (that could alias an
+    // insertion), so they are estimated from their length: the table, plus
+    // the allocation that each value points to.  The allocations owned by
+    // the keys and by the values' targets aren't counted.
+
+    impl<K, V> DeepSizeOf for FrozenMap<K, V>
+    where
+        K: Eq + Hash,
+        V: StableDeref,
+        V::Target: Sized,
+    {
+        /// The table is estimated as one slot (and one control byte) per
+        /// element, like a `HashMap` without any spare capacity.
+        fn deep_size_of_children(&self, _context: &mut Context) -> usize {
+            self.len() * (size_of::<(K, V)>() + 1 + size_of::<V::Target>())
+        }
+
+        /// Entries are never removed, so the table has at most as many
+        /// buckets as are needed to hold its length.  The children of the
+        /// keys and of the values' targets can't be read, so they are
+        /// missing from both bounds, and the upper bound is only an upper
+        /// bound for entries that don't own any allocations.
+        fn deep_size_of_children_approx(&self, _context: &mut Context) -> (usize, usize) {
+            let slot_size = size_of::<(K, V)>() + 1;
+            let targets = self.len() * size_of::<V::Target>();
+            (
+                self.len() * slot_size + targets,
+                hash_table_upper_bound(self.len(), slot_size) + targets,
+            )
+        }
+    }
+
+    impl<K, V> DeepSizeOf for FrozenBTreeMap<K, V>
+    where
+        K: Clone + Ord,
+        V: StableDeref,
+        V::Target: Sized,
+    {
+        /// The nodes are estimated the same way as a `BTreeMap`.
+        fn deep_size_of_children(&self, _context: &mut Context) -> usize {
+            btree_size(self.len(), size_of::<K>() + size_of::<V>())
+                + self.len() * size_of::<V::Target>()
+        }
+
+        /// Like `FrozenMap`, the children of the keys and of the values'
+        /// targets are missing from both bounds.
+        fn deep_size_of_children_approx(&self, _context: &mut Context) -> (usize, usize) {
+            let (lower, upper) = btree_bounds(self.len(), size_of::<K>() + size_of::<V>());
+            let targets = self.len() * size_of::<V::Target>();
+            (lower + targets, upper + targets)
+        }
+    }
+}
@@ -1,5 +1,6 @@
 #![forbid(missing_docs)]
 #![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(feature = "nightly", feature(min_specialization))]
 
 //! A utility for recursively measuring the size of an object
 //!
@@ -31,6 +32,32 @@
 //! assert_eq!(object.deep_size_of(), 9);
 //! ```
 //!
+//! ## Recursive types
+//!
+//! Recursive types need some indirection to have a finite size, and the
+//! pointer types used for that (`Box`, `Rc`, `Arc`) already implement
+//! `DeepSizeOf`, so the derive works on them without any extra attributes.
+//! `Rc` and `Arc` pointers are only counted once, so shared nodes and
+//! reference cycles are not counted multiple times.
+//!
+//! ```rust
+//! use deepsize::DeepSizeOf;
+//!
+//! #[derive(DeepSizeOf)]
+//! enum Tree {
+//!     Leaf(u32),
+//!     Node(Box<Tree>, Box<Tree>),
+//! }
+//!
+//! let tree = Tree::Node(
+//!     Box::new(Tree::Leaf(1)),
+//!     Box::new(Tree::Node(Box::new(Tree::Leaf(2)), Box::new(Tree::Leaf(3)))),
+//! );
+//!
+//! // Every node other than the root is in its own allocation
+//! assert_eq!(tree.deep_size_of(), 5 * std::mem::size_of::<Tree>());
+//! ```
+//!
 
 extern crate alloc;
 extern crate core;
@@ -40,6 +67,8 @@ extern crate self as deepsize;
 #[cfg(feature = "derive")]
 pub use deepsize_derive::*;
 
+use alloc::string::String;
+use alloc::vec::Vec;
 use core::mem::{size_of, size_of_val};
 
 #[cfg(test)]
@@ -48,6 +77,9 @@ mod test;
 mod default_impls;
 mod external_impls;
 
+#[cfg(feature = "sharded-slab")]
+pub use external_impls::sharded_slab_impl::sharded_slab_size;
+
 /// A trait for measuring the size of an object and its children
 ///
 /// In many cases this is just `std::mem::size_of::<T>()`, but if
@@ -79,7 +111,32 @@ pub trait DeepSizeOf {
     /// );
     /// ```
     fn deep_size_of(&self) -> usize {
-        size_of_val(self) + self.deep_size_of_children(&mut Context::new())
+        self.deep_size_of_with_context(&mut Context::new())
+    }
+
+    /// Returns an estimation of a total size of memory owned by the
+    /// object, using an existing [`Context`](Context).
+    ///
+    /// This allows measuring several related objects while sharing the
+    /// tracking of visited references, so that an [`Arc`](std::sync::Arc)
+    /// or [`Rc`](std::rc::Rc) shared between them is only counted once.
+    ///
+    /// ```rust
+    /// use deepsize::{Context, DeepSizeOf};
+    /// use std::sync::Arc;
+    ///
+    /// let shared = Arc::new([0u8; 64]);
+    /// let a = Arc::clone(&shared);
+    /// let b = Arc::clone(&shared);
+    ///
+    /// let mut context = Context::new();
+    /// let total = a.deep_size_of_with_context(&mut context)
+    ///     + b.deep_size_of_with_context(&mut context);
+    ///
+    /// assert_eq!(total, 2 * std::mem::size_of::<Arc<[u8; 64]>>() + 64);
+    /// ```
+    fn deep_size_of_with_context(&self, context: &mut Context) -> usize {
+        size_of_val(self) + self.deep_size_of_children(context)
     }
 
     /// Returns an estimation of the heap-managed storage of this object.
@@ -122,6 +179,262 @@ pub trait DeepSizeOf {
     /// }
     /// ```
     fn deep_size_of_children(&self, context: &mut Context) -> usize;
+
+    /// Returns a lower and upper bound for the heap-managed storage of
+    /// this object, like [`deep_size_of_children`](DeepSizeOf::deep_size_of_children).
+    ///
+    /// Many implementations (such as those for hash tables and B-trees) can
+    /// only estimate their size, since the exact layout isn't exposed.  These
+    /// implementations override this method to give bounds on the real size,
+    /// which are as tight as the available information allows.  The default
+    /// implementation is for types that are measured exactly, and returns
+    /// `deep_size_of_children` as both bounds.
+    ///
+    /// ```rust
+    /// use deepsize::{Context, DeepSizeOf};
+    /// use std::collections::HashMap;
+    ///
+    /// let vec = vec![0u32; 16];
+    /// assert_eq!(vec.deep_size_of_children_approx(&mut Context::new()), (64, 64));
+    ///
+    /// let map: HashMap<u32, u32> = (0..16).map(|i| (i, i)).collect();
+    /// let (lower, upper) = map.deep_size_of_children_approx(&mut Context::new());
+    /// assert!(lower <= upper);
+    /// ```
+    ///
+    /// Implementations for containers should override this to sum the
+    /// bounds of their children, so that an estimate nested anywhere inside
+    /// them is reflected in the bounds.  The derive macro does this for
+    /// each field.
+    fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+        let size = self.deep_size_of_children(context);
+        (size, size)
+    }
+
+    /// Returns the size of the heap-managed storage of this object, like
+    /// [`deep_size_of_children`](DeepSizeOf::deep_size_of_children), while
+    /// adding the size of each of its fields to `tree`.
+    ///
+    /// This is implemented by the derive macro, which adds a child to the
+    /// tree for each field with [`SizeTree::add_field`].  The default
+    /// implementation doesn't break the size down any further.
+    fn deep_size_of_fields(&self, context: &mut Context, tree: &mut SizeTree) -> usize {
+        let _ = tree;
+        self.deep_size_of_children(context)
+    }
+
+    /// Returns a breakdown of the size of this object and each of its
+    /// fields.  See [`measure_tree`].
+    fn deep_size_tree(&self) -> SizeTree
+    where
+        Self: Sized,
+    {
+        measure_tree(self)
+    }
+}
+
+/// Measures the size of an object, giving up if it contains allocations
+/// nested more than `max_depth` levels deep.
+///
+/// Each owned allocation (such as a `Box`, `Vec`, or `Arc`), and each value
+/// of a type that derives `DeepSizeOf`, is one level of depth.  This returns
+/// `None` if the limit was hit, so that very deep structures can be measured
+/// without risking a stack overflow.
+///
+/// ```rust
+/// use deepsize::depth_limited_deep_size_of;
+///
+/// let nested = Box::new(Box::new(Box::new(5u32)));
+///
+/// assert_eq!(depth_limited_deep_size_of(&nested, 2), None);
+/// assert_eq!(
+///     depth_limited_deep_size_of(&nested, 3),
+///     Some(3 * std::mem::size_of::<usize>() + 4)
+/// );
+/// ```
+pub fn depth_limited_deep_size_of<T: DeepSizeOf>(val: &T, max_depth: usize) -> Option<usize> {
+    let mut context = Context::new();
+    context.set_max_depth(max_depth);
+    let size = val.deep_size_of_with_context(&mut context);
+    if context.depth_exceeded() {
+        None
+    } else {
+        Some(size)
+    }
+}
+
+/// The number of bytes allocated by `ustr`'s global string cache.
+///
+/// `Ustr` handles don't count the interned strings, since they are shared
+/// by the whole process; this can be used to report them once instead.
+#[cfg(feature = "ustr")]
+pub fn ustr_cache_size() -> usize {
+    ustr::total_allocated()
+}
+
+/// Measures an object, returning a tree with the size of each of its
+/// fields, and of their fields in turn.
+///
+/// Only types using the derive macro are broken down into fields; the
+/// tree has no children for other types.  The root of the tree is named
+/// after the type of the object.
+///
+/// ```rust
+/// use deepsize::{measure_tree, DeepSizeOf};
+///
+/// #[derive(DeepSizeOf)]
+/// struct Inventory {
+///     items: Vec<u64>,
+///     count: u32,
+/// }
+///
+/// let inventory = Inventory { items: vec![1, 2, 3], count: 3 };
+/// let tree = measure_tree(&inventory);
+///
+/// assert_eq!(tree.size, inventory.deep_size_of());
+/// assert_eq!(tree.children[0].name, "items");
+/// assert_eq!(tree.children[0].size, std::mem::size_of::<Vec<u64>>() + 3 * 8);
+/// assert_eq!(tree.children[1].name, "count");
+/// assert_eq!(tree.children[1].size, 4);
+/// ```
+pub fn measure_tree<T: DeepSizeOf + ?Sized>(val: &T) -> SizeTree {
+    SizeTree::measure(
+        String::from(core::any::type_name::<T>()),
+        val,
+        &mut Context::new(),
+    )
+}
+
+/// The size of an object, broken down by field
+///
+/// The `Display` impl writes each field on its own line, indented
+/// under its parent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SizeTree {
+    /// The name of the field, or of the type at the root of the tree
+    pub name: String,
+    /// The total size of the field, including its heap-managed storage
+    pub size: usize,
+    /// The fields of this field, if it was broken down further
+    pub children: Vec<SizeTree>,
+}
+
+impl SizeTree {
+    /// Measures a value and its fields, using the given context
+    pub fn measure<T: DeepSizeOf + ?Sized>(name: String, val: &T, context: &mut Context) -> Self {
+        let mut tree = SizeTree {
+            name,
+            size: 0,
+            children: Vec::new(),
+        };
+        tree.size = size_of_val(val) + val.deep_size_of_fields(context, &mut tree);
+        tree
+    }
+
+    /// Measures a field and adds it to the tree, returning the size of
+    /// the field's heap-managed storage
+    pub fn add_field<T: DeepSizeOf + ?Sized>(
+        &mut self,
+        name: &str,
+        val: &T,
+        context: &mut Context,
+    ) -> usize {
+        let field = SizeTree::measure(String::from(name), val, context);
+        let children_size = field.size - size_of_val(val);
+        self.children.push(field);
+        children_size
+    }
+
+    fn fmt_indented(&self, f: &mut core::fmt::Formatter<'_>, indent: usize) -> core::fmt::Result {
+        writeln!(
+            f,
+            "{:indent$}{}: {} bytes",
+            "",
+            self.name,
+            self.size,
+            indent = indent
+        )?;
+        for child in &self.children {
+            child.fmt_indented(f, indent + 2)?;
+        }
+        Ok(())
+    }
+}
+
+impl core::fmt::Display for SizeTree {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.fmt_indented(f, 0)
+    }
+}
+
+/// A wrapper that compares, orders, and hashes values by their deep size.
+///
+/// This makes it easy to sort values by size, or to keep the largest
+/// values in a `BinaryHeap`.  Formatting it with `Display` prints the value
+/// along with its size.
+///
+/// ```rust
+/// use deepsize::SizeOf;
+///
+/// let mut values: Vec<SizeOf<Vec<u8>>> = vec![
+///     vec![0; 30].into(),
+///     vec![0; 10].into(),
+///     vec![0; 20].into(),
+/// ];
+/// values.sort();
+///
+/// assert_eq!(values[0].0.len(), 10);
+/// assert_eq!(
+///     SizeOf(String::from("abc")).to_string(),
+///     format!("abc ({} bytes)", std::mem::size_of::<String>() + 3)
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SizeOf<T>(pub T);
+
+impl<T: DeepSizeOf> SizeOf<T> {
+    /// Returns the deep size of the wrapped value
+    pub fn size(&self) -> usize {
+        self.0.deep_size_of()
+    }
+}
+
+impl<T> From<T> for SizeOf<T> {
+    fn from(val: T) -> Self {
+        SizeOf(val)
+    }
+}
+
+impl<T: DeepSizeOf + core::fmt::Display> core::fmt::Display for SizeOf<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} ({} bytes)", self.0, self.size())
+    }
+}
+
+impl<T: DeepSizeOf> PartialEq for SizeOf<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.size() == other.size()
+    }
+}
+
+impl<T: DeepSizeOf> Eq for SizeOf<T> {}
+
+impl<T: DeepSizeOf> PartialOrd for SizeOf<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: DeepSizeOf> Ord for SizeOf<T> {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.size().cmp(&other.size())
+    }
+}
+
+impl<T: DeepSizeOf> core::hash::Hash for SizeOf<T> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        (self.size() as u64).hash(state);
+    }
 }
 
 #[cfg(not(feature = "std"))]
@@ -129,19 +442,86 @@ use alloc::collections::BTreeSet as GenericSet;
 #[cfg(feature = "std")]
 use std::collections::HashSet as GenericSet;
 
+/// The number of pointers that a `PointerSet` stores inline
+const INLINE_POINTERS: usize = 8;
+
+/// A set of pointers, which is kept as a sorted array while it is small
+/// to avoid allocating when only a few pointers are tracked.
+#[derive(Debug)]
+enum PointerSet {
+    Inline {
+        len: usize,
+        ptrs: [usize; INLINE_POINTERS],
+    },
+    Spilled(GenericSet<usize>),
+}
+
+impl PointerSet {
+    fn new() -> Self {
+        PointerSet::Inline {
+            len: 0,
+            ptrs: [0; INLINE_POINTERS],
+        }
+    }
+
+    fn insert(&mut self, ptr: usize) {
+        match self {
+            PointerSet::Inline { len, ptrs } => match ptrs[..*len].binary_search(&ptr) {
+                Ok(_) => {}
+                Err(index) if *len < INLINE_POINTERS => {
+                    ptrs.copy_within(index..*len, index + 1);
+                    ptrs[index] = ptr;
+                    *len += 1;
+                }
+                Err(_) => {
+                    let mut set: GenericSet<usize> = ptrs.iter().copied().collect();
+                    set.insert(ptr);
+                    *self = PointerSet::Spilled(set);
+                }
+            },
+            PointerSet::Spilled(set) => {
+                set.insert(ptr);
+            }
+        }
+    }
+
+    fn contains(&self, ptr: usize) -> bool {
+        match self {
+            PointerSet::Inline { len, ptrs } => ptrs[..*len].binary_search(&ptr).is_ok(),
+            PointerSet::Spilled(set) => set.contains(&ptr),
+        }
+    }
+
+    fn remove(&mut self, ptr: usize) {
+        match self {
+            PointerSet::Inline { len, ptrs } => {
+                if let Ok(index) = ptrs[..*len].binary_search(&ptr) {
+                    ptrs.copy_within(index + 1..*len, index);
+                    *len -= 1;
+                }
+            }
+            PointerSet::Spilled(set) => {
+                set.remove(&ptr);
+            }
+        }
+    }
+}
+
 /// The context of which references have already been seen.
 /// This should only be used in the implementation of the
-/// `deep_size_of_children` function, and (eventually, when this
-/// reaches 0.2) will not be able to be constructed, and only obtained
-/// from inside the function.
+/// `deep_size_of_children` function, or passed to
+/// [`deep_size_of_with_context`](DeepSizeOf::deep_size_of_with_context)
+/// to share reference tracking between multiple measurements.
 ///
 /// Keeps track of the [`Arc`](std::sync::Arc)s, [`Rc`](std::rc::Rc)s, and references
 /// that have been visited, so that [`Arc`](std::sync::Arc)s and other references
 /// aren't double counted.
 ///
-/// Currently this counts each reference once, although there are arguments for
-/// only counting owned data and ignoring partial ownership, or for counting
-/// partial references such as Arc as its size divided by the strong reference count.
+/// By default ([`ArcCountingStrategy::Once`]), the contents of each shared
+/// reference are counted in full the first time it is found, and not again
+/// for its clones.  [`ArcCountingStrategy::Proportional`] instead splits the
+/// contents of an `Arc` between its owners, by its strong reference count;
+/// see [`Context::with_arc_strategy`].
 ///
 /// [Github issue discussion here](https://github.com/dtolnay/request-for-implementation/issues/22)
 ///
@@ -150,39 +530,263 @@ use std::collections::HashSet as GenericSet;
 #[derive(Debug)]
 pub struct Context {
     /// A set of all [`Arc`](std::sync::Arc)s that have already been counted
-    arcs: GenericSet<usize>,
+    arcs: PointerSet,
     /// A set of all [`Rc`](std::sync::Arc)s that have already been counted
-    rcs: GenericSet<usize>,
+    rcs: PointerSet,
+    /// The byte ranges of shared buffers that have already been counted,
+    /// as disjoint `start..end` address ranges keyed by their start
+    shared_ranges: alloc::collections::BTreeMap<usize, usize>,
+    /// The current number of nested allocations being measured
+    depth: usize,
+    /// The maximum number of nested allocations to measure, if limited
+    max_depth: Option<usize>,
+    /// Whether any allocation was skipped because of `max_depth`
+    depth_exceeded: bool,
+    /// Whether to count the reference counts stored alongside an `Arc`'s value
+    arc_control_block: bool,
+    /// How the contents of shared [`Arc`](std::sync::Arc)s are counted
+    arc_strategy: ArcCountingStrategy,
+    /// Whether to count memory mapped regions, which aren't heap allocations
+    count_mapped_memory: bool,
+    /// Whether to count the live targets of weak pointers held by containers
+    count_weak_targets: bool,
+}
+
+/// How a [`Context`] counts the contents of [`Arc`](std::sync::Arc)s
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ArcCountingStrategy {
+    /// The contents are counted in full the first time an `Arc` is found,
+    /// and not counted again for any of its clones.
+    #[default]
+    Once,
+    /// Each `Arc` counts an equal share of its contents, divided by its
+    /// strong count, so that the owners of an `Arc` are each attributed a
+    /// part of it.  The shares are rounded down.
+    Proportional,
 }
 
 impl Context {
     /// Creates a new empty context for use in the `deep_size` functions
-    fn new() -> Self {
+    pub fn new() -> Self {
         Self {
-            arcs: GenericSet::new(),
-            rcs: GenericSet::new(),
+            arcs: PointerSet::new(),
+            rcs: PointerSet::new(),
+            shared_ranges: alloc::collections::BTreeMap::new(),
+            depth: 0,
+            max_depth: None,
+            depth_exceeded: false,
+            arc_control_block: false,
+            arc_strategy: ArcCountingStrategy::Once,
+            count_mapped_memory: true,
+            count_weak_targets: false,
         }
     }
 
+    /// Creates a new empty context, which can also count the control block
+    /// of each [`Arc`](std::sync::Arc).
+    ///
+    /// An `Arc`'s allocation holds the strong and weak reference counts
+    /// as well as the value; these are not counted by default.
+    ///
+    /// ```rust
+    /// use deepsize::{Context, DeepSizeOf};
+    /// use std::sync::Arc;
+    ///
+    /// let arc = Arc::new(0u64);
+    ///
+    /// let mut context = Context::with_arc_control_block(true);
+    /// assert_eq!(
+    ///     arc.deep_size_of_with_context(&mut context),
+    ///     std::mem::size_of::<Arc<u64>>() + 8 + 2 * std::mem::size_of::<usize>()
+    /// );
+    /// ```
+    pub fn with_arc_control_block(arc_control_block: bool) -> Self {
+        Self {
+            arc_control_block,
+            ..Self::new()
+        }
+    }
+
+    /// Creates a new empty context, which counts [`Arc`](std::sync::Arc)s
+    /// with the given strategy.
+    ///
+    /// ```rust
+    /// use deepsize::{ArcCountingStrategy, Context, DeepSizeOf};
+    /// use std::sync::Arc;
+    ///
+    /// let shared = Arc::new(vec![0u8; 1000]);
+    /// let other = Arc::clone(&shared);
+    ///
+    /// let mut context = Context::with_arc_strategy(ArcCountingStrategy::Proportional);
+    /// let share = (std::mem::size_of::<Vec<u8>>() + 1000) / 2;
+    /// assert_eq!(shared.deep_size_of_children(&mut context), share);
+    /// assert_eq!(other.deep_size_of_children(&mut context), share);
+    /// ```
+    pub fn with_arc_strategy(arc_strategy: ArcCountingStrategy) -> Self {
+        Self {
+            arc_strategy,
+            ..Self::new()
+        }
+    }
+
+    /// Limits the number of nested allocations that will be measured.
+    ///
+    /// Allocations past this depth are not counted, and
+    /// [`depth_exceeded`](Context::depth_exceeded) will return `true`.
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = Some(max_depth);
+    }
+
+    /// Returns whether any allocations were skipped because they were
+    /// nested deeper than the limit set by [`set_max_depth`](Context::set_max_depth)
+    pub fn depth_exceeded(&self) -> bool {
+        self.depth_exceeded
+    }
+
+    /// Sets whether memory mapped regions (such as files mapped with
+    /// `memmap2`) are counted.
+    ///
+    /// Mapped memory isn't allocated on the heap, but does take up address
+    /// space and may be resident in memory; it is counted by default.
+    pub fn set_count_mapped_memory(&mut self, count_mapped_memory: bool) {
+        self.count_mapped_memory = count_mapped_memory;
+    }
+
+    /// Returns whether memory mapped regions should be counted, for use in
+    /// implementations of `deep_size_of_children`
+    pub fn count_mapped_memory(&self) -> bool {
+        self.count_mapped_memory
+    }
+
+    /// Sets whether the values that are still alive behind the weak
+    /// pointers of a container (such as `weak_table`'s maps) are counted.
+    ///
+    /// Like a `Weak` pointer, these containers don't own their values, so
+    /// they aren't counted by default.  When enabled, each live value is
+    /// counted like the `Arc` or `Rc` that it upgrades to, so values that
+    /// are shared with the rest of the measurement are only counted once.
+    pub fn set_count_weak_targets(&mut self, count_weak_targets: bool) {
+        self.count_weak_targets = count_weak_targets;
+    }
+
+    /// Returns whether the live targets of weak pointers in containers should
+    /// be counted, for use in implementations of `deep_size_of_children`
+    pub fn count_weak_targets(&self) -> bool {
+        self.count_weak_targets
+    }
+
+    /// Measures the contents of an allocation one level deeper, returning
+    /// 0 without calling `f` if this would exceed the maximum depth.
+    ///
+    /// Implementations of `deep_size_of_children` for types that own an
+    /// allocation holding other values should measure that allocation and
+    /// its children in `f`, so that the limit set by
+    /// [`set_max_depth`](Context::set_max_depth) also applies to them.
+    ///
+    /// ```rust
+    /// use deepsize::{Context, DeepSizeOf};
+    /// use std::mem::size_of;
+    ///
+    /// struct Bag {
+    ///     items: Vec<String>,
+    /// }
+    ///
+    /// impl DeepSizeOf for Bag {
+    ///     fn deep_size_of_children(&self, context: &mut Context) -> usize {
+    ///         // The items are stored in an allocation owned by the bag
+    ///         context.descend(|context| {
+    ///             self.items.capacity() * size_of::<String>()
+    ///                 + self.items.iter().map(|item| item.deep_size_of_children(context)).sum::<usize>()
+    ///         })
+    ///     }
+    /// }
+    ///
+    /// let bag = Bag { items: vec![String::from("item")] };
+    ///
+    /// let mut context = Context::new();
+    /// context.set_max_depth(0);
+    /// assert_eq!(bag.deep_size_of_with_context(&mut context), size_of::<Bag>());
+    /// assert!(context.depth_exceeded());
+    /// ```
+    pub fn descend<R: Default, F: FnOnce(&mut Self) -> R>(&mut self, f: F) -> R {
+        if matches!(self.max_depth, Some(max) if self.depth >= max) {
+            self.depth_exceeded = true;
+            return R::default();
+        }
+        self.depth += 1;
+        let size = f(self);
+        self.depth -= 1;
+        size
+    }
+
     /// Adds an [`Arc`](std::sync::Arc) to the list of visited [`Arc`](std::sync::Arc)s
     fn add_arc<T: ?Sized>(&mut self, arc: &alloc::sync::Arc<T>) {
-        self.arcs.insert(&**arc as *const T as *const u8 as usize);
+        self.add_arc_ptr(alloc::sync::Arc::as_ptr(arc));
     }
     /// Checks if an [`Arc`](std::sync::Arc) is in the list visited [`Arc`](std::sync::Arc)s
     fn contains_arc<T: ?Sized>(&self, arc: &alloc::sync::Arc<T>) -> bool {
+        self.contains_arc_ptr(alloc::sync::Arc::as_ptr(arc))
+    }
+    /// Removes an [`Arc`](std::sync::Arc) from the list of visited [`Arc`](std::sync::Arc)s
+    fn remove_arc<T: ?Sized>(&mut self, arc: &alloc::sync::Arc<T>) {
         self.arcs
-            .contains(&(&**arc as *const T as *const u8 as usize))
+            .remove(alloc::sync::Arc::as_ptr(arc) as *const u8 as usize);
+    }
+
+    /// Adds a pointer to shared data to the list of visited [`Arc`](std::sync::Arc)s,
+    /// for types that share an allocation without exposing the `Arc` itself
+    fn add_arc_ptr<T: ?Sized>(&mut self, ptr: *const T) {
+        self.arcs.insert(ptr as *const u8 as usize);
+    }
+    /// Checks if a pointer to shared data is in the list visited [`Arc`](std::sync::Arc)s
+    fn contains_arc_ptr<T: ?Sized>(&self, ptr: *const T) -> bool {
+        self.arcs.contains(ptr as *const u8 as usize)
+    }
+
+    /// Adds a view of a shared buffer to the counted ranges, for types
+    /// that share a buffer without exposing the allocation itself.  Returns
+    /// the number of bytes of the view that weren't already counted.
+    #[cfg_attr(not(feature = "bytes"), allow(dead_code))]
+    fn add_shared_range(&mut self, start: *const u8, len: usize) -> usize {
+        if len == 0 {
+            return 0;
+        }
+        let (start, end) = (start as usize, start as usize + len);
+        // The ranges are disjoint, so the ones that touch this view are the
+        // last few that start before its end.
+        let touching: Vec<(usize, usize)> = self
+            .shared_ranges
+            .range(..=end)
+            .rev()
+            .take_while(|&(_, &range_end)| range_end >= start)
+            .map(|(&range_start, &range_end)| (range_start, range_end))
+            .collect();
+        let (mut merged_start, mut merged_end, mut counted) = (start, end, 0);
+        for (range_start, range_end) in touching {
+            self.shared_ranges.remove(&range_start);
+            counted += range_end.min(end).saturating_sub(range_start.max(start));
+            merged_start = merged_start.min(range_start);
+            merged_end = merged_end.max(range_end);
+        }
+        self.shared_ranges.insert(merged_start, merged_end);
+        len - counted
     }
 
     /// Adds an [`Rc`](std::rc::Rc) to the list of visited [`Rc`](std::rc::Rc)s
     fn add_rc<T: ?Sized>(&mut self, rc: &alloc::rc::Rc<T>) {
-        self.rcs.insert(&**rc as *const T as *const u8 as usize);
+        self.rcs
+            .insert(alloc::rc::Rc::as_ptr(rc) as *const u8 as usize);
     }
     /// Checks if an [`Rc`](std::rc::Rc) is in the list visited [`Rc`](std::rc::Rc)s
-    /// Adds an [`Rc`](std::rc::Rc) to the list of visited [`Rc`](std::rc::Rc)s
     fn contains_rc<T: ?Sized>(&self, rc: &alloc::rc::Rc<T>) -> bool {
         self.rcs
-            .contains(&(&**rc as *const T as *const u8 as usize))
+            .contains(alloc::rc::Rc::as_ptr(rc) as *const u8 as usize)
+    }
+}
+
+impl Default for Context {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -218,11 +822,124 @@ where
     /// assert_eq!(vec.deep_size_of(), 24 + vec.capacity() * 8 + 13 * 8);
     /// ```
     fn deep_size_of_children(&self, context: &mut Context) -> usize {
-        self.iter()
+        // Zero sized types can't own any memory, and the Vec never allocates
+        if size_of::<T>() == 0 {
+            return 0;
+        }
+        context.descend(|context| {
+            T::elements_children(self, context) + self.capacity() * size_of::<T>()
+            // Size of unused capacity
+        })
+    }
+
+    fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+        if size_of::<T>() == 0 {
+            return (0, 0);
+        }
+        context.descend(|context| {
+            let (lower, upper) = self.as_slice().deep_size_of_children_approx(context);
+            let buffer = self.capacity() * size_of::<T>();
+            (lower + buffer, upper + buffer)
+        })
+    }
+}
+
+/// Sums the children of the elements of a `Vec`.
+///
+/// With the `nightly` feature, this is specialized to skip the loop for
+/// primitive types, since they can't own any allocations.
+trait ElementsChildren: Sized {
+    fn elements_children(elements: &[Self], context: &mut Context) -> usize;
+}
+
+#[cfg(not(feature = "nightly"))]
+impl<T: DeepSizeOf> ElementsChildren for T {
+    fn elements_children(elements: &[Self], context: &mut Context) -> usize {
+        elements
+            .iter()
             .map(|child| child.deep_size_of_children(context))
-            .sum::<usize>()
-            + self.capacity() * size_of::<T>()
-        // Size of unused capacity
+            .sum()
+    }
+}
+
+#[cfg(feature = "nightly")]
+mod specialization {
+    use super::{Context, DeepSizeOf, ElementsChildren};
+
+    impl<T: DeepSizeOf> ElementsChildren for T {
+        default fn elements_children(elements: &[Self], context: &mut Context) -> usize {
+            elements
+                .iter()
+                .map(|child| child.deep_size_of_children(context))
+                .sum()
+        }
+    }
+
+    // `min_specialization` can't specialize on `Copy` (only on traits
+    // marked by the standard library), so this is limited to concrete types.
+    macro_rules! skip_elements {
+        ($($type:ty),* $(,)?) => {
+            $(
+                impl ElementsChildren for $type {
+                    fn elements_children(_: &[Self], _: &mut Context) -> usize {
+                        0
+                    }
+                }
+            )*
+        };
+    }
+
+    skip_elements!(
+        (),
+        bool,
+        char,
+        u8,
+        u16,
+        u32,
+        u64,
+        u128,
+        usize,
+        i8,
+        i16,
+        i32,
+        i64,
+        i128,
+        isize,
+        f32,
+        f64,
+    );
+}
+
+impl<T> DeepSizeOf for alloc::vec::Drain<'_, T>
+where
+    T: DeepSizeOf,
+{
+    /// Sums the size of the elements that haven't been drained yet.
+    /// The buffer itself belongs to the source `Vec`, so it isn't counted.
+    ///
+    /// ```rust
+    /// use deepsize::DeepSizeOf;
+    ///
+    /// let mut vec = vec![vec![0u8; 10], vec![0u8; 20], vec![0u8; 30]];
+    /// let mut drain = vec.drain(..);
+    /// drain.next();
+    ///
+    /// assert_eq!(
+    ///     drain.deep_size_of(),
+    ///     std::mem::size_of::<std::vec::Drain<Vec<u8>>>() + 20 + 30
+    /// );
+    /// ```
+    fn deep_size_of_children(&self, context: &mut Context) -> usize {
+        context.descend(|context| {
+            self.as_slice()
+                .iter()
+                .map(|child| child.deep_size_of_children(context))
+                .sum()
+        })
+    }
+
+    fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+        context.descend(|context| self.as_slice().deep_size_of_children_approx(context))
     }
 }
 
@@ -262,20 +979,41 @@ where
     /// assert_eq!(vec.deep_size_of(), 32 + vec.capacity() * 8 + 13 * 8);
     /// ```
     fn deep_size_of_children(&self, context: &mut Context) -> usize {
-        // Deep size of children
-        self.iter()
-            .map(|child| child.deep_size_of_children(context))
-            .sum::<usize>()
-            + self.capacity() * size_of::<T>() // Size of Vec's heap allocation
+        context.descend(|context| {
+            // Deep size of children
+            self.iter()
+                .map(|child| child.deep_size_of_children(context))
+                .sum::<usize>()
+                + self.capacity() * size_of::<T>() // Size of Vec's heap allocation
+        })
+    }
+
+    fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+        context.descend(|context| {
+            let (lower, upper) = sum_bounds(
+                self.iter()
+                    .map(|child| child.deep_size_of_children_approx(context)),
+            );
+            let buffer = self.capacity() * size_of::<T>();
+            (lower + buffer, upper + buffer)
+        })
     }
 }
 
+// Mirrors the standard library's internal `LinkedList` node
+struct MockListNode<T> {
+    _next: Option<core::ptr::NonNull<MockListNode<T>>>,
+    _prev: Option<core::ptr::NonNull<MockListNode<T>>>,
+    _element: T,
+}
+
 impl<T> DeepSizeOf for alloc::collections::LinkedList<T>
 where
     T: DeepSizeOf,
 {
-    /// Sums the size of each child object, assuming the overhead of
-    /// each node is 2 usize (next, prev)
+    /// Sums the size of each child object, plus the overhead of each
+    /// node's next and prev pointers.  Each node is a separate heap
+    /// allocation.
     ///
     /// ```rust
     /// use deepsize::DeepSizeOf;
@@ -288,14 +1026,72 @@ where
     /// list.push_front(13);
     ///
     /// assert_eq!(list.deep_size_of(), std::mem::size_of::<LinkedList<u8>>()
-    ///                                + 13 * 1 + 13 * 2 * 8);
+    ///                                + 13 * 1 + 13 * 2 * std::mem::size_of::<usize>());
     /// ```
     fn deep_size_of_children(&self, context: &mut Context) -> usize {
-        self.iter().fold(0, |sum, child| {
-            sum + size_of_val(child) + child.deep_size_of_children(context) + size_of::<usize>() * 2
-            // overhead of each node
+        // Each node stores the element itself and its next and prev
+        // pointers; padding between them isn't counted.
+        let node_size =
+            size_of::<T>() + size_of::<Option<core::ptr::NonNull<MockListNode<T>>>>() * 2;
+        context.descend(|context| {
+            self.iter().fold(0, |sum, child| {
+                sum + node_size + child.deep_size_of_children(context)
+            })
         })
     }
+
+    fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+        let node_size =
+            size_of::<T>() + size_of::<Option<core::ptr::NonNull<MockListNode<T>>>>() * 2;
+        context.descend(|context| {
+            let (lower, upper) = sum_bounds(
+                self.iter()
+                    .map(|child| child.deep_size_of_children_approx(context)),
+            );
+            (
+                lower + self.len() * node_size,
+                upper + self.len() * node_size,
+            )
+        })
+    }
+}
+
+/// The number of control bytes in a hashbrown group; this is the largest
+/// of the SIMD and generic implementations
+#[cfg_attr(not(feature = "std"), allow(dead_code))]
+const HASH_GROUP_WIDTH: usize = 16;
+
+/// The upper bound of the size of a hashbrown table with the given capacity.
+///
+/// The number of buckets is a power of two, with 1/8 of the buckets left
+/// empty for tables with at least 8 buckets.  The table also has an extra
+/// group of control bytes, and may have some alignment padding.
+#[cfg_attr(not(feature = "std"), allow(dead_code))]
+pub(crate) fn hash_table_upper_bound(capacity: usize, slot_size: usize) -> usize {
+    let buckets = match capacity {
+        0 => return 0,
+        1..=7 => (capacity + 1).next_power_of_two(),
+        _ => (capacity * 8 / 7).next_power_of_two(),
+    };
+    buckets * slot_size + 2 * HASH_GROUP_WIDTH
+}
+
+/// Sums the bounds of several children
+pub(crate) fn sum_bounds(bounds: impl Iterator<Item = (usize, usize)>) -> (usize, usize) {
+    bounds.fold((0, 0), |(lower, upper), (child_lower, child_upper)| {
+        (lower + child_lower, upper + child_upper)
+    })
+}
+
+/// Adds the bounds of the children of a key and a value
+pub(crate) fn pair_bounds<K, V>(key: &K, val: &V, context: &mut Context) -> (usize, usize)
+where
+    K: DeepSizeOf + ?Sized,
+    V: DeepSizeOf + ?Sized,
+{
+    let (key_lower, key_upper) = key.deep_size_of_children_approx(context);
+    let (val_lower, val_upper) = val.deep_size_of_children_approx(context);
+    (key_lower + val_lower, key_upper + val_upper)
 }
 
 #[cfg(feature = "std")]
@@ -305,24 +1101,49 @@ where
     V: DeepSizeOf,
     S: std::hash::BuildHasher,
 {
-    // How much more overhead is there to a hashmap? The docs say it is
-    // essentially just a Vec<Option<(u64, K, V)>>;
-    // For the old implementation of HashMap:
-    // fn deep_size_of_children(&self, context: &mut Context) -> usize {
-    //     self.iter().fold(0, |sum, (key, val)| {
-    //         sum + key.deep_size_of_children(context) + val.deep_size_of_children(context)
-    //     }) + self.capacity() * size_of::<Option<(u64, K, V)>>()
-    // }
-    // For the hashbrown implementation of HashMap:
+    /// Sums the size of each key and value, and then adds the size of
+    /// the table itself.
+    ///
+    /// `HashMap` uses hashbrown's Swiss Table, where each slot holds a key
+    /// and a value, and has one extra control byte, so the table is
+    /// `capacity * (size_of::<K>() + size_of::<V>() + 1)` bytes.
+    ///
+    /// The table actually allocates a power of two number of buckets, which
+    /// can be up to 8/7 of the capacity, plus an extra group of control
+    /// bytes.  This isn't exposed, so the capacity is used as a lower bound;
+    /// [`deep_size_of_children_approx`](DeepSizeOf::deep_size_of_children_approx)
+    /// also gives an upper bound based on the number of buckets.
+    ///
+    /// ```rust
+    /// use deepsize::DeepSizeOf;
+    /// use std::collections::HashMap;
+    ///
+    /// let mut map: HashMap<u8, u8> = HashMap::with_capacity(3);
+    /// map.insert(1, 2);
+    ///
+    /// assert_eq!(map.deep_size_of(),
+    ///     std::mem::size_of::<HashMap<u8, u8>>() + map.capacity() * (1 + 1 + 1));
+    /// ```
     fn deep_size_of_children(&self, context: &mut Context) -> usize {
-        self.iter().fold(0, |sum, (key, val)| {
-            sum + key.deep_size_of_children(context) + val.deep_size_of_children(context)
-        }) + self.capacity() * size_of::<(K, V)>()
-        // Buckets would be the more correct value, but there isn't
-        // an API for accessing that with hashbrown.
-        // I believe that hashbrown's HashTable is represented as
-        // an array of (K, V), with control bytes at the start/end
-        // that mark used/uninitialized buckets (?)
+        context.descend(|context| {
+            self.iter().fold(0, |sum, (key, val)| {
+                sum + key.deep_size_of_children(context) + val.deep_size_of_children(context)
+            }) + self.capacity() * (size_of::<K>() + size_of::<V>() + 1)
+        })
+    }
+
+    /// The lower bound counts one slot per element of capacity, and the
+    /// upper bound counts every bucket of the table.
+    fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+        context.descend(|context| {
+            let (lower, upper) =
+                sum_bounds(self.iter().map(|(key, val)| pair_bounds(key, val, context)));
+            let slot_size = size_of::<K>() + size_of::<V>() + 1;
+            (
+                lower + self.capacity() * slot_size,
+                upper + hash_table_upper_bound(self.capacity(), slot_size),
+            )
+        })
     }
 }
 
@@ -332,48 +1153,142 @@ where
     K: DeepSizeOf + Eq + std::hash::Hash,
     S: std::hash::BuildHasher,
 {
+    /// Like `HashMap`, each slot of the table holds a key and one control byte
     fn deep_size_of_children(&self, context: &mut Context) -> usize {
-        // self.iter()
-        //     .fold(0, |sum, item| sum + item.deep_size_of_children(context))
-        //     + self.capacity() * size_of::<Option<(u64, K, ())>>()
-        self.iter()
-            .fold(0, |sum, key| sum + key.deep_size_of_children(context))
-            + self.capacity() * size_of::<K>()
+        context.descend(|context| {
+            self.iter()
+                .fold(0, |sum, key| sum + key.deep_size_of_children(context))
+                + self.capacity() * (size_of::<K>() + 1)
+        })
+    }
+
+    fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+        context.descend(|context| {
+            let (lower, upper) = sum_bounds(
+                self.iter()
+                    .map(|key| key.deep_size_of_children_approx(context)),
+            );
+            let slot_size = size_of::<K>() + 1;
+            (
+                lower + self.capacity() * slot_size,
+                upper + hash_table_upper_bound(self.capacity(), slot_size),
+            )
+        })
     }
 }
 
-// A btree node has 2*B - 1 (K,V) pairs and (usize, u16, u16)
-// overhead, and an internal btree node additionally has 2*B
-// `usize` overhead.
-// A node can contain between B - 1 and 2*B - 1 elements, so
-// we assume it has the midpoint 3/2*B - 1.
+// A btree node has between B - 1 and 2*B - 1 elements, plus some
+// overhead for the parent pointer, length, and (for internal nodes)
+// the child pointers.  This is estimated as adding one full node for
+// every B elements.
 
 // Constants from rust's source:
 // https://doc.rust-lang.org/src/alloc/collections/btree/node.rs.html#43-45
 
 const BTREE_B: usize = 6;
-const BTREE_MIN: usize = 2 * BTREE_B - 1;
-const BTREE_MAX: usize = BTREE_B - 1;
+/// The maximum number of elements in a single btree node
+const BTREE_CAPACITY: usize = 2 * BTREE_B - 1;
 
-#[cfg(feature = "std")]
-impl<K: Ord + DeepSizeOf, V: DeepSizeOf> DeepSizeOf for std::collections::BTreeMap<K, V> {
+/// The estimated size of a btree's nodes, given the size of each entry.
+///
+/// Each element is stored in a node, and roughly one full node is added
+/// for every `B` elements.
+pub(crate) fn btree_size(len: usize, entry_size: usize) -> usize {
+    len * entry_size + (len / BTREE_B) * entry_size * BTREE_CAPACITY
+}
+
+/// The bounds of the size of a btree's nodes, given the size of each entry.
+///
+/// At best every node is full, and at worst every node is an internal
+/// node (with child pointers) holding only `B - 1` elements.
+pub(crate) fn btree_bounds(len: usize, entry_size: usize) -> (usize, usize) {
+    if len == 0 {
+        return (0, 0);
+    }
+    let node_overhead = size_of::<(usize, u16, u16)>() + (BTREE_CAPACITY + 1) * size_of::<usize>();
+    let max_nodes = len / (BTREE_B - 1) + 1;
+    (
+        len * entry_size,
+        len * entry_size + max_nodes * (BTREE_CAPACITY * entry_size + node_overhead),
+    )
+}
+
+impl<K: Ord + DeepSizeOf, V: DeepSizeOf> DeepSizeOf for alloc::collections::BTreeMap<K, V> {
+    /// Sums the size of each key and value, and then adds an estimate
+    /// of the size of the btree's nodes.
+    ///
+    /// This is only an approximation: each element is stored in a node,
+    /// and roughly one node allocation (with space for 11 elements) is
+    /// added for every 6 elements.
+    ///
+    /// ```rust
+    /// use deepsize::DeepSizeOf;
+    /// use std::collections::BTreeMap;
+    ///
+    /// let map: BTreeMap<u32, u32> = (0..60).map(|i| (i, i)).collect();
+    ///
+    /// assert_eq!(map.deep_size_of(),
+    ///     std::mem::size_of::<BTreeMap<u32, u32>>() + 60 * 8 + (60 / 6) * 8 * 11);
+    /// ```
     fn deep_size_of_children(&self, context: &mut Context) -> usize {
-        let element_size = self.iter().fold(0, |sum, (k, v)| {
-            sum + k.deep_size_of_children(context) + v.deep_size_of_children(context)
-        });
-        let overhead = size_of::<(usize, u16, u16, [(K, V); BTREE_MAX], [usize; BTREE_B])>();
-        element_size + self.len() * overhead * 2 / (BTREE_MAX + BTREE_MIN)
+        context.descend(|context| {
+            let element_size = self.iter().fold(0, |sum, (k, v)| {
+                sum + k.deep_size_of_children(context) + v.deep_size_of_children(context)
+            });
+            element_size + btree_size(self.len(), size_of::<K>() + size_of::<V>())
+        })
+    }
+
+    fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+        context.descend(|context| {
+            let (lower, upper) = sum_bounds(self.iter().map(|(k, v)| pair_bounds(k, v, context)));
+            let (node_lower, node_upper) =
+                btree_bounds(self.len(), size_of::<K>() + size_of::<V>());
+            (lower + node_lower, upper + node_upper)
+        })
     }
 }
 
-#[cfg(feature = "std")]
-impl<K: Ord + DeepSizeOf> DeepSizeOf for std::collections::BTreeSet<K> {
+impl<K: Ord + DeepSizeOf> DeepSizeOf for alloc::collections::BTreeSet<K> {
+    /// Sums the size of each element, and then adds an estimate of the
+    /// size of the btree's nodes.
+    ///
+    /// A `BTreeSet<K>` is a `BTreeMap<K, ()>`, so this is only an
+    /// approximation, like the `BTreeMap` impl (listed with the other
+    /// [implementors of `DeepSizeOf`](DeepSizeOf#implementors)).  Each
+    /// element is stored in a node, and the internal nodes and the space
+    /// left in partially filled nodes are estimated as 6 more elements for
+    /// every 6 elements.
+    ///
+    /// ```rust
+    /// use deepsize::DeepSizeOf;
+    /// use std::collections::BTreeSet;
+    ///
+    /// let set: BTreeSet<u32> = (0..60).collect();
+    ///
+    /// assert_eq!(set.deep_size_of(),
+    ///     std::mem::size_of::<BTreeSet<u32>>() + 60 * 4 + (60 / 6) * 4 * 6);
+    /// ```
     fn deep_size_of_children(&self, context: &mut Context) -> usize {
-        let element_size = self
-            .iter()
-            .fold(0, |sum, item| sum + item.deep_size_of_children(context));
-        let overhead = size_of::<(usize, u16, u16, [K; BTREE_MAX], [usize; BTREE_B])>();
-        element_size + self.len() * overhead * 2 / (BTREE_MAX + BTREE_MIN)
+        context.descend(|context| {
+            let element_size = self
+                .iter()
+                .fold(0, |sum, item| sum + item.deep_size_of_children(context));
+            let node_size =
+                self.len() * size_of::<K>() + (self.len() / BTREE_B) * size_of::<K>() * BTREE_B;
+            element_size + node_size
+        })
+    }
+
+    fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+        context.descend(|context| {
+            let (lower, upper) = sum_bounds(
+                self.iter()
+                    .map(|item| item.deep_size_of_children_approx(context)),
+            );
+            let (node_lower, node_upper) = btree_bounds(self.len(), size_of::<K>());
+            (lower + node_lower, upper + node_upper)
+        })
     }
 }
 
@@ -382,8 +1297,16 @@ where
     T: DeepSizeOf + ?Sized,
 {
     fn deep_size_of_children(&self, context: &mut Context) -> usize {
-        let val: &T = &*self;
-        size_of_val(val) + val.deep_size_of_children(context)
+        let val: &T = self;
+        context.descend(|context| size_of_val(val) + val.deep_size_of_children(context))
+    }
+
+    fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+        let val: &T = self;
+        context.descend(|context| {
+            let (lower, upper) = val.deep_size_of_children_approx(context);
+            (size_of_val(val) + lower, size_of_val(val) + upper)
+        })
     }
 }
 
@@ -391,14 +1314,57 @@ impl<T> DeepSizeOf for alloc::sync::Arc<T>
 where
     T: DeepSizeOf + ?Sized,
 {
+    /// With [`ArcCountingStrategy::Proportional`], an `Arc` is only tracked
+    /// while its contents are being measured, to avoid looping on cycles.
     fn deep_size_of_children(&self, context: &mut Context) -> usize {
         if context.contains_arc(self) {
             0
         } else {
             context.add_arc(self);
-            let val: &T = &*self;
+            let val: &T = self;
+            let control_block = if context.arc_control_block {
+                2 * size_of::<usize>()
+            } else {
+                0
+            };
             // Size of the Arc, size of the value, size of the allocations of the value
-            size_of_val(val) + val.deep_size_of_children(context)
+            let size = context.descend(|context| {
+                control_block + size_of_val(val) + val.deep_size_of_children(context)
+            });
+            match context.arc_strategy {
+                ArcCountingStrategy::Once => size,
+                ArcCountingStrategy::Proportional => {
+                    context.remove_arc(self);
+                    size / alloc::sync::Arc::strong_count(self)
+                }
+            }
+        }
+    }
+
+    fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+        if context.contains_arc(self) {
+            (0, 0)
+        } else {
+            context.add_arc(self);
+            let val: &T = self;
+            let control_block = if context.arc_control_block {
+                2 * size_of::<usize>()
+            } else {
+                0
+            };
+            let own_size = control_block + size_of_val(val);
+            let (lower, upper) = context.descend(|context| {
+                let (lower, upper) = val.deep_size_of_children_approx(context);
+                (own_size + lower, own_size + upper)
+            });
+            match context.arc_strategy {
+                ArcCountingStrategy::Once => (lower, upper),
+                ArcCountingStrategy::Proportional => {
+                    context.remove_arc(self);
+                    let count = alloc::sync::Arc::strong_count(self);
+                    (lower / count, upper / count)
+                }
+            }
         }
     }
 }
@@ -412,13 +1378,31 @@ where
             0
         } else {
             context.add_rc(self);
-            let val: &T = &*self;
-            size_of_val(val) + val.deep_size_of_children(context)
+            let val: &T = self;
+            context.descend(|context| size_of_val(val) + val.deep_size_of_children(context))
+        }
+    }
+
+    fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+        if context.contains_rc(self) {
+            (0, 0)
+        } else {
+            context.add_rc(self);
+            let val: &T = self;
+            context.descend(|context| {
+                let (lower, upper) = val.deep_size_of_children_approx(context);
+                (size_of_val(val) + lower, size_of_val(val) + upper)
+            })
         }
     }
 }
 
 // References aren't owned; should they count?
+//
+// There is intentionally no impl for `&mut T`: with one, calling
+// `self.deep_size_of_children(context)` on a `&mut self` would silently
+// resolve to it and return 0.  A struct holding a `&mut T` field can
+// measure it with `(&*self.field).deep_size_of_children(context)`.
 impl<T> DeepSizeOf for &T
 where
     T: DeepSizeOf + ?Sized,
@@ -434,10 +1418,21 @@ where
     }
 }
 
-impl<T> DeepSizeOf for &mut T
+impl<T> DeepSizeOf for core::pin::Pin<&T>
 where
     T: DeepSizeOf + ?Sized,
 {
+    /// Pinning doesn't change ownership, so this is counted the same as `&T`
+    fn deep_size_of_children(&self, _context: &mut Context) -> usize {
+        0
+    }
+}
+
+impl<T> DeepSizeOf for core::pin::Pin<&mut T>
+where
+    T: DeepSizeOf + ?Sized,
+{
+    /// A mutable borrow owns nothing, so no children are counted
     fn deep_size_of_children(&self, _context: &mut Context) -> usize {
         0
     }
@@ -452,4 +1447,11 @@ where
             .map(|child| child.deep_size_of_children(context))
             .sum()
     }
+
+    fn deep_size_of_children_approx(&self, context: &mut Context) -> (usize, usize) {
+        sum_bounds(
+            self.iter()
+                .map(|child| child.deep_size_of_children_approx(context)),
+        )
+    }
 }
@@ -46,6 +46,25 @@ fn arcs() {
     );
 }
 
+#[test]
+fn rcs() {
+    use std::rc::Rc;
+    let test: Rc<[u32]> = vec![1, 2, 3].into();
+    let multiple = (Rc::clone(&test), Rc::clone(&test), test);
+
+    assert_eq!(
+        multiple.deep_size_of(),
+        3 * size_of::<Rc<[u32]>>() + 3 * size_of::<u32>()
+    );
+
+    // Distinct allocations with equal contents are counted separately
+    let separate = (Rc::new(String::from("abc")), Rc::new(String::from("abc")));
+    assert_eq!(
+        separate.deep_size_of(),
+        2 * size_of::<Rc<String>>() + 2 * (size_of::<String>() + 3)
+    );
+}
+
 #[test]
 fn slices() {
     let array: Box<[u32]> = vec![0; 64].into_boxed_slice();
@@ -64,13 +83,125 @@ fn slices() {
     );
 }
 
+#[test]
+fn hash_maps() {
+    use std::collections::{HashMap, HashSet};
+
+    let mut map: HashMap<u8, u8> = HashMap::with_capacity(3);
+    map.insert(0, 1);
+    map.insert(2, 3);
+    assert_eq!(
+        map.deep_size_of(),
+        size_of::<HashMap<u8, u8>>() + map.capacity() * 3
+    );
+
+    let mut set: HashSet<u32> = HashSet::with_capacity(3);
+    set.insert(0);
+    assert_eq!(
+        set.deep_size_of(),
+        size_of::<HashSet<u32>>() + set.capacity() * 5
+    );
+
+    let mut map: HashMap<u32, String> = HashMap::with_capacity(3);
+    map.insert(0, String::from("Hello World"));
+    assert_eq!(
+        map.deep_size_of(),
+        size_of::<HashMap<u32, String>>() + map.capacity() * (4 + size_of::<String>() + 1) + 11
+    );
+}
+
+#[test]
+fn btree_maps() {
+    use std::collections::BTreeMap;
+
+    let small: BTreeMap<u8, String> = (0..5).map(|i| (i, String::from("abc"))).collect();
+    assert_eq!(
+        small.deep_size_of(),
+        size_of::<BTreeMap<u8, String>>() + 5 * (1 + size_of::<String>()) + 5 * 3
+    );
+
+    let large: BTreeMap<u64, u64> = (0..6000).map(|i| (i, i)).collect();
+    assert_eq!(
+        large.deep_size_of(),
+        size_of::<BTreeMap<u64, u64>>() + 6000 * 16 + 1000 * 16 * 11
+    );
+}
+
+#[test]
+fn btree_sets() {
+    use std::collections::BTreeSet;
+
+    let set: BTreeSet<String> = (0..120).map(|i| format!("{:03}", i)).collect();
+    let string_sizes: usize = set.iter().map(String::capacity).sum();
+    assert_eq!(
+        set.deep_size_of(),
+        size_of::<BTreeSet<String>>()
+            + 120 * size_of::<String>()
+            + 20 * size_of::<String>() * 6
+            + string_sizes
+    );
+}
+
+#[test]
+fn zero_sized_vecs() {
+    use core::marker::PhantomData;
+
+    let units = vec![(); 1_000_000];
+    assert_eq!(units.deep_size_of(), size_of::<vec::Vec<()>>());
+
+    let phantoms: vec::Vec<PhantomData<String>> = vec![PhantomData; 1000];
+    assert_eq!(phantoms.deep_size_of(), size_of::<vec::Vec<()>>());
+}
+
+#[test]
+fn depth_limited() {
+    use crate::depth_limited_deep_size_of;
+
+    let nested = vec![vec![Box::new(0u32)]];
+    let size = nested.deep_size_of();
+    assert_eq!(depth_limited_deep_size_of(&nested, 3), Some(size));
+    assert_eq!(depth_limited_deep_size_of(&nested, 2), None);
+    assert_eq!(depth_limited_deep_size_of(&0u32, 0), Some(4));
+
+    // A deeply nested list that would overflow the stack if fully measured
+    struct List(Option<Box<List>>);
+    impl DeepSizeOf for List {
+        fn deep_size_of_children(&self, context: &mut crate::Context) -> usize {
+            self.0.deep_size_of_children(context)
+        }
+    }
+    let mut list = List(None);
+    for _ in 0..1_000_000 {
+        list = List(Some(Box::new(list)));
+    }
+    assert_eq!(depth_limited_deep_size_of(&list, 100), None);
+    // Avoid overflowing the stack when dropping
+    while let Some(next) = list.0.take() {
+        list = *next;
+    }
+}
+
+#[test]
+fn arrays() {
+    let bytes = [0u8; 64];
+    assert_eq!(bytes.deep_size_of(), 64);
+
+    let strings: [String; 100] = core::array::from_fn(|_| String::from("abcd"));
+    assert_eq!(strings.deep_size_of(), size_of::<[String; 100]>() + 100 * 4);
+
+    let empty: [Box<u32>; 0] = [];
+    assert_eq!(empty.deep_size_of(), 0);
+}
+
 // TODO: find edge cases
 #[test]
 fn alignment() {
     #[repr(align(256))]
+    #[allow(dead_code)]
     struct Test(u8);
     known_deep_size!(0; Test);
 
+    #[allow(dead_code)]
     struct Test2(Test, u8);
     known_deep_size!(0; Test2);
 
@@ -93,6 +224,255 @@ fn strings() {
     assert_eq!(string_b.deep_size_of(), size_of::<String>() + 16);
 }
 
+#[test]
+fn array_iterators() {
+    let array = [vec![0u8; 10], vec![0u8; 20], vec![0u8; 30]];
+    let mut iter = IntoIterator::into_iter(array);
+    assert_eq!(
+        iter.deep_size_of(),
+        size_of::<core::array::IntoIter<Vec<u8>, 3>>() + 60
+    );
+
+    iter.next();
+    assert_eq!(
+        iter.deep_size_of(),
+        size_of::<core::array::IntoIter<Vec<u8>, 3>>() + 50
+    );
+}
+
+#[test]
+fn function_pointers() {
+    type Combine = fn(u8, u16, u32, u64, i8, i16, i32, i64, f32, f64, char, bool) -> String;
+
+    #[derive(DeepSizeOf)]
+    struct Callbacks {
+        init: fn(),
+        filter: fn(usize) -> bool,
+        combine: Combine,
+        raw: unsafe fn(*const u8) -> u8,
+        ffi: extern "C" fn(i32) -> i32,
+        raw_ffi: unsafe extern "C" fn(),
+    }
+
+    fn init() {}
+    fn filter(val: usize) -> bool {
+        val > 3
+    }
+    #[allow(clippy::too_many_arguments)]
+    fn combine(
+        _: u8,
+        _: u16,
+        _: u32,
+        _: u64,
+        _: i8,
+        _: i16,
+        _: i32,
+        _: i64,
+        _: f32,
+        _: f64,
+        _: char,
+        _: bool,
+    ) -> String {
+        String::new()
+    }
+    unsafe fn raw(ptr: *const u8) -> u8 {
+        *ptr
+    }
+    extern "C" fn ffi(val: i32) -> i32 {
+        val
+    }
+    unsafe extern "C" fn raw_ffi() {}
+
+    let callbacks = Callbacks {
+        init,
+        filter,
+        combine,
+        raw,
+        ffi,
+        raw_ffi,
+    };
+    assert_eq!(callbacks.deep_size_of(), 6 * size_of::<usize>());
+}
+
+#[test]
+fn pinned_references() {
+    use core::pin::Pin;
+
+    let mut vec = vec![0u8; 100];
+    let pinned = Pin::new(&vec);
+    assert_eq!(pinned.deep_size_of(), size_of::<Pin<&Vec<u8>>>());
+
+    let pinned_mut = Pin::new(&mut vec);
+    assert_eq!(pinned_mut.deep_size_of(), size_of::<Pin<&mut Vec<u8>>>());
+}
+
+#[test]
+fn mutex_guards() {
+    use std::sync::{Mutex, MutexGuard};
+
+    let mutex = Mutex::new(vec![0u8; 100]);
+    let guard = mutex.lock().unwrap();
+    assert_eq!(guard.deep_size_of(), size_of::<MutexGuard<Vec<u8>>>() + 100);
+}
+
+#[test]
+fn boxed_errors() {
+    use std::error::Error;
+    use std::fmt;
+
+    #[derive(Debug)]
+    struct LargeError([u8; 256]);
+
+    impl fmt::Display for LargeError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "large error ({} bytes)", self.0.len())
+        }
+    }
+
+    impl Error for LargeError {}
+
+    let error: Box<dyn Error> = Box::new(LargeError([0; 256]));
+    assert_eq!(error.deep_size_of(), size_of::<Box<dyn Error>>() + 256);
+
+    let error: Box<dyn Error + Send + Sync> = Box::new(LargeError([0; 256]));
+    assert_eq!(
+        error.deep_size_of(),
+        size_of::<Box<dyn Error + Send + Sync>>() + 256
+    );
+}
+
+#[test]
+fn approximate_bounds() {
+    use crate::Context;
+    use std::collections::{BTreeMap, HashMap};
+
+    let vec = vec![String::from("hello"); 10];
+    let size = vec.deep_size_of_children(&mut Context::new());
+    assert_eq!(
+        vec.deep_size_of_children_approx(&mut Context::new()),
+        (size, size)
+    );
+
+    // 100 elements of capacity needs 128 buckets
+    let map: HashMap<u64, u64> = HashMap::with_capacity(100);
+    let (lower, upper) = map.deep_size_of_children_approx(&mut Context::new());
+    assert_eq!(lower, map.capacity() * 17);
+    assert_eq!(upper, 128 * 17 + 32);
+
+    let map: HashMap<u64, Vec<u8>> = (0..100).map(|i| (i, vec![0; 10])).collect();
+    let size = map.deep_size_of_children(&mut Context::new());
+    let (lower, upper) = map.deep_size_of_children_approx(&mut Context::new());
+    assert!(lower <= size && size <= upper);
+    assert_eq!(lower, size);
+
+    let map: BTreeMap<u64, Vec<u8>> = (0..1000).map(|i| (i, vec![0; 10])).collect();
+    let size = map.deep_size_of_children(&mut Context::new());
+    let (lower, upper) = map.deep_size_of_children_approx(&mut Context::new());
+    assert!(lower <= size && size <= upper);
+
+    let empty: BTreeMap<u64, u64> = BTreeMap::new();
+    assert_eq!(
+        empty.deep_size_of_children_approx(&mut Context::new()),
+        (0, 0)
+    );
+
+    // The bounds of maps nested in other containers are kept
+    let maps: Vec<HashMap<u64, u64>> = vec![HashMap::with_capacity(100); 3];
+    let size = maps.deep_size_of_children(&mut Context::new());
+    let (lower, upper) = maps.deep_size_of_children_approx(&mut Context::new());
+    assert_eq!(lower, size);
+    assert!(lower < upper);
+
+    let pair = (Box::new(map), Some(String::from("map")));
+    let (lower, upper) = pair.deep_size_of_children_approx(&mut Context::new());
+    assert!(lower < upper);
+}
+
+#[test]
+fn ref_cells() {
+    use core::cell::RefCell;
+
+    let cell = RefCell::new(vec![0u8; 100]);
+    assert_eq!(cell.deep_size_of(), size_of::<RefCell<Vec<u8>>>() + 100);
+
+    let _borrow = cell.borrow();
+    assert_eq!(cell.deep_size_of(), size_of::<RefCell<Vec<u8>>>() + 100);
+    drop(_borrow);
+
+    // The contents can't be read while mutably borrowed
+    let _borrow = cell.borrow_mut();
+    assert_eq!(cell.deep_size_of(), size_of::<RefCell<Vec<u8>>>());
+}
+
+#[test]
+fn size_of_wrapper() {
+    use crate::SizeOf;
+    use std::collections::{BinaryHeap, HashSet};
+
+    let mut heap: BinaryHeap<SizeOf<String>> = BinaryHeap::new();
+    for len in &[5, 50, 20, 10] {
+        heap.push("a".repeat(*len).into());
+    }
+    assert_eq!(heap.pop().unwrap().0.len(), 50);
+    assert_eq!(heap.pop().unwrap().0.len(), 20);
+
+    // Values are equal if their sizes are
+    assert_eq!(SizeOf(vec![1u32, 2]), SizeOf(vec![3u32, 4]));
+    let set: HashSet<SizeOf<Vec<u32>>> = vec![SizeOf(vec![1, 2]), SizeOf(vec![3, 4])]
+        .into_iter()
+        .collect();
+    assert_eq!(set.len(), 1);
+
+    let wrapped = SizeOf(vec![0u8; 10]);
+    assert_eq!(wrapped.size(), size_of::<Vec<u8>>() + 10);
+    assert_eq!(
+        SizeOf(String::from("hello")).to_string(),
+        format!("hello ({} bytes)", size_of::<String>() + 5)
+    );
+}
+
+#[test]
+fn chained_readers() {
+    use std::collections::VecDeque;
+    use std::io::{Chain, Read};
+
+    let first: &[u8] = b"header";
+    let second = VecDeque::from(vec::Vec::from(&b"the rest of the body"[..]));
+    let capacity = second.capacity();
+    let chain = first.chain(second);
+    assert_eq!(
+        chain.deep_size_of(),
+        size_of::<Chain<&[u8], VecDeque<u8>>>() + capacity
+    );
+}
+
+#[test]
+fn linked_lists() {
+    use alloc::collections::LinkedList;
+
+    let list: LinkedList<u64> = (0..20).collect();
+    assert_eq!(
+        list.deep_size_of(),
+        size_of::<LinkedList<u64>>() + 20 * (size_of::<u64>() + 2 * size_of::<usize>())
+    );
+
+    let list: LinkedList<String> = (0..4).map(|_| String::from("node")).collect();
+    assert_eq!(
+        list.deep_size_of(),
+        size_of::<LinkedList<String>>() + 4 * (size_of::<String>() + 2 * size_of::<usize>() + 4)
+    );
+}
+
+#[test]
+fn cows() {
+    use alloc::borrow::Cow;
+
+    let borrowed: Cow<str> = Cow::Borrowed("Hello World");
+    let owned: Cow<str> = Cow::Owned(String::from("Hello World"));
+    assert_eq!(borrowed.deep_size_of(), size_of::<Cow<str>>());
+    assert_eq!(owned.deep_size_of(), size_of::<Cow<str>>() + 11);
+}
+
 #[test]
 fn tuples() {
     // Alignment - ######## #.##....
@@ -110,8 +490,11 @@ fn tuples() {
     );
 }
 
+#[allow(clippy::bool_assert_comparison)]
 mod context_tests {
     use crate::Context;
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
 
     #[test]
     fn context_arc_test() {
@@ -132,6 +515,115 @@ mod context_tests {
         context.add_rc(&rc);
         assert_eq!(context.contains_rc(&rc), true);
     }
+
+    #[test]
+    fn shared_context() {
+        use alloc::sync::Arc;
+        let shared: Arc<[u32]> = alloc::vec![1, 2, 3].into();
+        let first = Arc::clone(&shared);
+        let second = Arc::clone(&shared);
+
+        let mut context = Context::new();
+        assert_eq!(
+            first.deep_size_of_with_context(&mut context),
+            size_of::<Arc<[u32]>>() + 3 * size_of::<u32>()
+        );
+        assert_eq!(
+            second.deep_size_of_with_context(&mut context),
+            size_of::<Arc<[u32]>>()
+        );
+
+        // Without a shared context, each measurement counts the allocation
+        assert_eq!(first.deep_size_of(), second.deep_size_of());
+    }
+
+    #[test]
+    fn arc_control_blocks() {
+        use std::sync::Arc;
+
+        let arc = Arc::new(vec![0u8; 100]);
+        let clone = Arc::clone(&arc);
+        let value_size = size_of::<Vec<u8>>() + 100;
+
+        let mut context = Context::with_arc_control_block(true);
+        let total = arc.deep_size_of_with_context(&mut context)
+            + clone.deep_size_of_with_context(&mut context);
+        assert_eq!(
+            total,
+            2 * size_of::<Arc<Vec<u8>>>() + value_size + 2 * size_of::<usize>()
+        );
+
+        let mut context = Context::with_arc_control_block(false);
+        assert_eq!(
+            arc.deep_size_of_with_context(&mut context),
+            size_of::<Arc<Vec<u8>>>() + value_size
+        );
+        assert_eq!(arc.deep_size_of(), size_of::<Arc<Vec<u8>>>() + value_size);
+    }
+
+    #[test]
+    fn many_shared_pointers() {
+        use alloc::rc::Rc;
+        use std::sync::Arc;
+
+        // More pointers than are tracked inline, each shared twice
+        let arcs: Vec<Arc<u64>> = (0..20).map(Arc::new).collect();
+        let shared: Vec<Arc<u64>> = arcs.iter().chain(arcs.iter()).cloned().collect();
+        assert_eq!(
+            shared.deep_size_of(),
+            size_of::<Vec<Arc<u64>>>() + 40 * size_of::<Arc<u64>>() + 20 * size_of::<u64>()
+        );
+
+        let rcs: Vec<Rc<u64>> = (0..20).rev().map(Rc::new).collect();
+        let shared: Vec<Rc<u64>> = rcs.iter().chain(rcs.iter()).cloned().collect();
+        assert_eq!(
+            shared.deep_size_of(),
+            size_of::<Vec<Rc<u64>>>() + 40 * size_of::<Rc<u64>>() + 20 * size_of::<u64>()
+        );
+    }
+
+    #[test]
+    fn proportional_arcs() {
+        use crate::ArcCountingStrategy;
+        use std::sync::{Arc, Mutex};
+
+        let arc = Arc::new(vec![0u8; 1000]);
+        let owners = vec![arc.clone(), arc.clone(), arc];
+        let value_size = size_of::<Vec<u8>>() + 1000;
+
+        let mut context = Context::with_arc_strategy(ArcCountingStrategy::Proportional);
+        let shares: Vec<usize> = owners
+            .iter()
+            .map(|owner| owner.deep_size_of_children(&mut context))
+            .collect();
+        assert_eq!(shares, vec![value_size / 3; 3]);
+
+        // The whole collection holds every reference, so it gets every share
+        let mut context = Context::with_arc_strategy(ArcCountingStrategy::Proportional);
+        assert_eq!(
+            owners.deep_size_of_with_context(&mut context),
+            size_of::<Vec<Arc<Vec<u8>>>>()
+                + owners.capacity() * size_of::<Arc<Vec<u8>>>()
+                + 3 * (value_size / 3)
+        );
+
+        // Cycles are only followed once, so the inner reference adds nothing
+        struct Node(Mutex<Option<Arc<Node>>>);
+        impl DeepSizeOf for Node {
+            fn deep_size_of_children(&self, context: &mut Context) -> usize {
+                self.0.deep_size_of_children(context)
+            }
+        }
+        let node = Arc::new(Node(Mutex::new(None)));
+        *node.0.lock().unwrap() = Some(node.clone());
+
+        let mut context = Context::with_arc_strategy(ArcCountingStrategy::Proportional);
+        assert_eq!(
+            node.deep_size_of_children(&mut context),
+            size_of::<Node>() / 2
+        );
+        node.0.lock().unwrap().take();
+    }
 }
 
 #[cfg(feature = "derive")]
@@ -182,4 +674,4008 @@ mod test_derive {
         );
         assert_eq!(variant_five.deep_size_of(), size_of::<ExampleEnum>());
     }
+
+    #[test]
+    fn test_approximate_bounds() {
+        use crate::Context;
+        use std::collections::BTreeMap;
+
+        #[derive(DeepSizeOf)]
+        struct Index {
+            name: String,
+            entries: BTreeMap<u64, String>,
+        }
+
+        // The fields are named like the accumulators of the generated code
+        #[derive(DeepSizeOf)]
+        enum Range {
+            Empty,
+            Bounded { lower: Vec<u8>, upper: Vec<u8> },
+            Indexed(Index),
+        }
+
+        let index = Index {
+            name: String::from("index"),
+            entries: (0..100).map(|i| (i, i.to_string())).collect(),
+        };
+        let size = index.deep_size_of_children(&mut Context::new());
+        let (lower, upper) = index.deep_size_of_children_approx(&mut Context::new());
+        assert!(lower <= size && size <= upper);
+        assert!(lower < upper);
+
+        let range = Range::Bounded {
+            lower: vec![0; 10],
+            upper: vec![0; 20],
+        };
+        assert_eq!(
+            range.deep_size_of_children_approx(&mut Context::new()),
+            (30, 30)
+        );
+        assert_eq!(
+            Range::Empty.deep_size_of_children_approx(&mut Context::new()),
+            (0, 0)
+        );
+        let (lower, upper) =
+            Range::Indexed(index).deep_size_of_children_approx(&mut Context::new());
+        assert!(lower < upper);
+    }
+
+    #[test]
+    fn test_size_tree() {
+        use crate::{measure_tree, SizeTree};
+
+        #[derive(DeepSizeOf)]
+        struct Marker;
+
+        #[derive(DeepSizeOf)]
+        enum Index {
+            Empty,
+            Keys(Vec<u64>),
+        }
+
+        #[derive(DeepSizeOf)]
+        struct Database {
+            data: Vec<String>,
+            index: Index,
+            marker: Marker,
+        }
+
+        let database = Database {
+            data: vec![String::from("first"), String::from("second")],
+            index: Index::Keys(vec![1, 2, 3, 4]),
+            marker: Marker,
+        };
+        let strings: usize = database.data.iter().map(|s| s.capacity()).sum();
+
+        let tree = measure_tree(&database);
+        assert_eq!(tree.size, database.deep_size_of());
+        assert_eq!(tree, database.deep_size_tree());
+        assert!(tree.name.ends_with("Database"));
+
+        let data_size = size_of::<Vec<String>>() + 2 * size_of::<String>() + strings;
+        let index_size = size_of::<Index>() + 4 * size_of::<u64>();
+        let leaf = |name: &str, size| SizeTree {
+            name: String::from(name),
+            size,
+            children: Vec::new(),
+        };
+        assert_eq!(
+            tree.children,
+            vec![
+                leaf("data", data_size),
+                SizeTree {
+                    name: String::from("index"),
+                    size: index_size,
+                    children: vec![leaf("0", size_of::<Vec<u64>>() + 4 * size_of::<u64>())],
+                },
+                leaf("marker", 0),
+            ]
+        );
+
+        let empty = measure_tree(&Index::Empty);
+        assert_eq!(empty.size, size_of::<Index>());
+        assert!(empty.children.is_empty());
+
+        let text = tree.to_string();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[1], format!("  data: {} bytes", data_size));
+        assert_eq!(
+            lines[3],
+            format!("    0: {} bytes", size_of::<Vec<u64>>() + 32)
+        );
+    }
+
+    #[test]
+    fn test_recursive_enum() {
+        #[derive(DeepSizeOf)]
+        enum Expr {
+            Number(i64),
+            Name(String),
+            Neg(Box<Expr>),
+            Add(Box<Expr>, Box<Expr>),
+            Call(Box<Expr>, Vec<Expr>),
+        }
+
+        let name = String::from("max");
+        let name_len = name.capacity();
+        let expr = Expr::Add(
+            Box::new(Expr::Neg(Box::new(Expr::Number(1)))),
+            Box::new(Expr::Call(
+                Box::new(Expr::Name(name)),
+                vec![Expr::Number(2), Expr::Number(3)],
+            )),
+        );
+
+        // Four boxed nodes, two nodes in the `Vec`, and the name
+        assert_eq!(
+            expr.deep_size_of(),
+            size_of::<Expr>() + 4 * size_of::<Expr>() + 2 * size_of::<Expr>() + name_len
+        );
+    }
+
+    #[test]
+    fn test_named_struct() {
+        #[derive(DeepSizeOf)]
+        struct Named {
+            id: u64,
+            name: String,
+            tags: Vec<String>,
+        }
+
+        let named = Named {
+            id: 1,
+            name: String::from("named"),
+            tags: vec![String::from("a"), String::from("bc")],
+        };
+        let tags: usize = named.tags.iter().map(String::capacity).sum();
+        assert_eq!(
+            named.deep_size_of(),
+            size_of::<Named>()
+                + named.name.capacity()
+                + named.tags.capacity() * size_of::<String>()
+                + tags
+        );
+    }
+
+    #[test]
+    fn test_unnamed_struct() {
+        #[derive(DeepSizeOf)]
+        struct Unnamed(u8, Box<[u8; 32]>, Option<Box<u64>>);
+
+        let unnamed = Unnamed(0, Box::new([0; 32]), Some(Box::new(0)));
+        assert_eq!(unnamed.deep_size_of(), size_of::<Unnamed>() + 32 + 8);
+
+        let unnamed = Unnamed(0, Box::new([0; 32]), None);
+        assert_eq!(unnamed.deep_size_of(), size_of::<Unnamed>() + 32);
+    }
+
+    #[test]
+    fn test_unit_struct() {
+        #[derive(DeepSizeOf)]
+        struct Unit;
+
+        #[derive(DeepSizeOf)]
+        struct Empty {}
+
+        #[derive(DeepSizeOf)]
+        struct EmptyTuple();
+
+        assert_eq!(Unit.deep_size_of(), 0);
+        assert_eq!(Empty {}.deep_size_of(), 0);
+        assert_eq!(EmptyTuple().deep_size_of(), 0);
+    }
+
+    #[test]
+    fn test_empty_enum() {
+        #[derive(DeepSizeOf)]
+        #[allow(dead_code)]
+        enum Never {}
+
+        fn size_of_never(never: Option<&Never>) -> usize {
+            never.map_or(0, |never| never.deep_size_of())
+        }
+        assert_eq!(size_of_never(None), 0);
+    }
+
+    #[test]
+    fn test_generic_struct() {
+        #[derive(DeepSizeOf)]
+        struct Pair<A, B> {
+            first: A,
+            second: B,
+        }
+
+        let pair = Pair {
+            first: 0u32,
+            second: vec![0u16; 10],
+        };
+        assert_eq!(
+            pair.deep_size_of(),
+            size_of::<Pair<u32, Vec<u16>>>() + 10 * size_of::<u16>()
+        );
+
+        let nested = Pair {
+            first: Pair {
+                first: Box::new(0u64),
+                second: (),
+            },
+            second: String::from("second"),
+        };
+        assert_eq!(
+            nested.deep_size_of(),
+            size_of::<Pair<Pair<Box<u64>, ()>, String>>() + 8 + nested.second.capacity()
+        );
+    }
+
+    #[test]
+    fn test_generic_enum() {
+        #[derive(DeepSizeOf)]
+        enum Either<L, R> {
+            Left(L),
+            Right { value: R },
+        }
+
+        let left: Either<Box<u32>, String> = Either::Left(Box::new(0));
+        assert_eq!(
+            left.deep_size_of(),
+            size_of::<Either<Box<u32>, String>>() + 4
+        );
+
+        let value = String::from("right side");
+        let capacity = value.capacity();
+        let right: Either<Box<u32>, String> = Either::Right { value };
+        assert_eq!(
+            right.deep_size_of(),
+            size_of::<Either<Box<u32>, String>>() + capacity
+        );
+    }
+
+    #[test]
+    fn test_lifetimes() {
+        #[derive(DeepSizeOf)]
+        struct Borrowed<'a, T> {
+            slice: &'a [T],
+            text: &'a str,
+            owned: Vec<T>,
+        }
+
+        let data = vec![0u32; 100];
+        let borrowed = Borrowed {
+            slice: &data,
+            text: "not counted",
+            owned: vec![1, 2, 3],
+        };
+        assert_eq!(
+            borrowed.deep_size_of(),
+            size_of::<Borrowed<u32>>() + borrowed.owned.capacity() * 4
+        );
+    }
+
+    #[test]
+    fn test_nested_derives() {
+        #[derive(DeepSizeOf)]
+        struct Inner {
+            values: Vec<u64>,
+        }
+
+        #[derive(DeepSizeOf)]
+        enum Middle {
+            Boxed(Box<Inner>),
+            Inline(Inner),
+        }
+
+        #[derive(DeepSizeOf)]
+        struct Outer {
+            middles: Vec<Middle>,
+        }
+
+        let outer = Outer {
+            middles: vec![
+                Middle::Boxed(Box::new(Inner { values: vec![0; 4] })),
+                Middle::Inline(Inner { values: vec![0; 8] }),
+            ],
+        };
+        assert_eq!(
+            outer.deep_size_of(),
+            size_of::<Outer>()
+                + 2 * size_of::<Middle>()
+                + size_of::<Inner>()
+                + (4 + 8) * size_of::<u64>()
+        );
+    }
+
+    #[test]
+    fn test_shared_pointers() {
+        use alloc::rc::Rc;
+        use std::sync::Arc;
+
+        #[derive(DeepSizeOf)]
+        struct Shared {
+            first: Arc<Vec<u8>>,
+            second: Arc<Vec<u8>>,
+            local: Rc<[u8; 16]>,
+            also_local: Rc<[u8; 16]>,
+        }
+
+        let arc = Arc::new(vec![0u8; 100]);
+        let rc = Rc::new([0u8; 16]);
+        let shared = Shared {
+            first: arc.clone(),
+            second: arc,
+            local: rc.clone(),
+            also_local: rc,
+        };
+        // Each shared value is only counted once
+        assert_eq!(
+            shared.deep_size_of(),
+            size_of::<Shared>() + size_of::<Vec<u8>>() + 100 + 16
+        );
+    }
+
+    #[test]
+    fn test_phantom_data() {
+        use core::marker::PhantomData;
+
+        // The derive bounds every type parameter by `DeepSizeOf`, even if
+        // it's only used in `PhantomData`
+        #[derive(DeepSizeOf)]
+        struct Typed<T> {
+            id: u32,
+            marker: PhantomData<T>,
+        }
+
+        let typed: Typed<String> = Typed {
+            id: 0,
+            marker: PhantomData,
+        };
+        assert_eq!(typed.deep_size_of(), size_of::<Typed<String>>());
+        assert_eq!(typed.deep_size_of(), size_of::<u32>());
+    }
+}
+
+#[cfg(feature = "slotmap")]
+mod slotmap_tests {
+    use crate::DeepSizeOf;
+    use alloc::string::{String, ToString};
+    use core::mem::size_of;
+    use core::num::NonZeroU32;
+    use slotmap::{DefaultKey, DenseSlotMap, SecondaryMap, SlotMap, SparseSecondaryMap};
+
+    #[test]
+    fn slotmap_churn() {
+        let mut map: SlotMap<DefaultKey, String> = SlotMap::new();
+        let keys: alloc::vec::Vec<_> = (0..100).map(|i| map.insert(i.to_string())).collect();
+        for key in keys.iter().step_by(2) {
+            map.remove(*key);
+        }
+        // Reinserting reuses vacant slots and bumps their versions
+        for i in 0..10 {
+            map.insert(i.to_string());
+        }
+        let children: usize = map.values().map(String::capacity).sum();
+
+        // A slot is a union of the value and a u32, plus a u32 version
+        assert_eq!(
+            map.deep_size_of(),
+            size_of::<SlotMap<DefaultKey, String>>()
+                + (map.capacity() + 1) * size_of::<(String, u32)>()
+                + children
+        );
+    }
+
+    #[test]
+    fn dense_slotmap_churn() {
+        let mut map: DenseSlotMap<DefaultKey, String> = DenseSlotMap::new();
+        let keys: alloc::vec::Vec<_> = (0..100).map(|i| map.insert(i.to_string())).collect();
+        for key in keys.iter().take(90) {
+            map.remove(*key);
+        }
+        let children: usize = map.values().map(String::capacity).sum();
+
+        assert_eq!(map.len(), 10);
+        assert_eq!(
+            map.deep_size_of(),
+            size_of::<DenseSlotMap<DefaultKey, String>>()
+                + map.capacity() * (size_of::<DefaultKey>() + size_of::<String>() + 8)
+                + children
+        );
+    }
+
+    #[test]
+    fn secondary_maps() {
+        let mut primary: SlotMap<DefaultKey, ()> = SlotMap::new();
+        let keys: alloc::vec::Vec<_> = (0..64).map(|_| primary.insert(())).collect();
+
+        let mut secondary = SecondaryMap::new();
+        let mut sparse = SparseSecondaryMap::new();
+        for key in keys.iter().step_by(4) {
+            secondary.insert(*key, String::from("0123456789"));
+            sparse.insert(*key, String::from("0123456789"));
+        }
+
+        assert_eq!(
+            secondary.deep_size_of(),
+            size_of::<SecondaryMap<DefaultKey, String>>()
+                + (secondary.capacity() + 1) * size_of::<Option<(String, NonZeroU32)>>()
+                + 16 * 10
+        );
+        assert_eq!(
+            sparse.deep_size_of(),
+            size_of::<SparseSecondaryMap<DefaultKey, String>>()
+                + sparse.capacity() * size_of::<(u32, (u32, String))>()
+                + 16 * 10
+        );
+    }
+}
+
+#[cfg(feature = "lru")]
+mod lru_tests {
+    use crate::DeepSizeOf;
+    use alloc::string::{String, ToString};
+    use core::mem::size_of;
+    use core::num::NonZeroUsize;
+    use lru::LruCache;
+
+    #[test]
+    fn cache_at_capacity() {
+        let mut cache = LruCache::new(NonZeroUsize::new(1000).unwrap());
+        for i in 0..2000u64 {
+            cache.put(i, i.to_string().repeat(4));
+        }
+        assert_eq!(cache.len(), 1000);
+
+        let strings: usize = cache.iter().map(|(_, v)| v.capacity()).sum();
+        let parts =
+            size_of::<LruCache<u64, String>>() + cache.len() * size_of::<(u64, String)>() + strings;
+
+        // Each entry adds two list pointers and a map slot of two pointers,
+        // which should be within twice the size of the stored pairs
+        let size = cache.deep_size_of();
+        assert!(size >= parts + cache.len() * 4 * size_of::<usize>());
+        assert!(size <= 2 * parts);
+    }
+
+    #[test]
+    fn unbounded_cache() {
+        let mut cache: LruCache<u32, u32> = LruCache::unbounded();
+        assert_eq!(
+            cache.deep_size_of(),
+            size_of::<LruCache<u32, u32>>() + 2 * size_of::<(u32, u32, usize, usize)>()
+        );
+        cache.put(1, 1);
+        assert!(cache.deep_size_of() > size_of::<LruCache<u32, u32>>());
+    }
+}
+
+#[cfg(all(feature = "priority-queue", feature = "indexmap"))]
+mod priority_queue_tests {
+    use crate::DeepSizeOf;
+    use alloc::string::{String, ToString};
+    use alloc::vec::Vec;
+    use indexmap::IndexMap;
+    use priority_queue::{DoublePriorityQueue, PriorityQueue};
+
+    #[test]
+    fn tracks_index_map() {
+        let mut queue = PriorityQueue::new();
+        let mut double = DoublePriorityQueue::new();
+        for i in 0..4000u32 {
+            queue.push(i.to_string().repeat(3), i % 17);
+            double.push(i.to_string().repeat(3), i % 17);
+        }
+
+        let mut map = IndexMap::with_capacity(queue.capacity());
+        for (item, priority) in queue.iter() {
+            map.insert(item.clone(), *priority);
+        }
+        let heap: Vec<usize> = Vec::with_capacity(queue.capacity());
+
+        // The queue holds an extra Vec<usize> of heap positions
+        let expected = map.deep_size_of() + 2 * heap.deep_size_of();
+        let size = queue.deep_size_of();
+        assert!(size * 100 >= expected * 95, "{} < {}", size, expected);
+        assert!(size * 100 <= expected * 105, "{} > {}", size, expected);
+
+        assert_eq!(
+            double.deep_size_of() - core::mem::size_of_val(&double),
+            size - core::mem::size_of_val(&queue)
+        );
+
+        let strings: usize = map.keys().map(String::capacity).sum();
+        assert!(size > strings);
+    }
+}
+
+#[cfg(feature = "petgraph")]
+mod petgraph_tests {
+    use crate::DeepSizeOf;
+    use alloc::string::{String, ToString};
+    use core::mem::size_of;
+    use petgraph::graph::{Edge, Graph, Node};
+    use petgraph::graphmap::DiGraphMap;
+    use petgraph::stable_graph::StableGraph;
+
+    fn build_graph(nodes: u32, edges: u32) -> Graph<String, String> {
+        let mut graph = Graph::with_capacity(nodes as usize, edges as usize);
+        let indices: alloc::vec::Vec<_> = (0..nodes)
+            .map(|i| graph.add_node(i.to_string().repeat(4)))
+            .collect();
+        for i in 0..edges {
+            let a = indices[(i % nodes) as usize];
+            let b = indices[(i * 7 % nodes) as usize];
+            graph.add_edge(a, b, String::from("edge"));
+        }
+        graph
+    }
+
+    #[test]
+    fn graph_scaling() {
+        let graph = build_graph(1000, 10_000);
+        let node_strings: usize = graph.node_weights().map(String::capacity).sum();
+        assert_eq!(
+            graph.deep_size_of(),
+            size_of::<Graph<String, String>>()
+                + 1000 * size_of::<Node<String>>()
+                + 10_000 * size_of::<Edge<String>>()
+                + node_strings
+                + 10_000 * 4
+        );
+
+        let more_edges = build_graph(1000, 20_000);
+        let more_nodes = build_graph(2000, 10_000);
+        assert!(
+            more_edges.deep_size_of() - graph.deep_size_of()
+                >= 10_000 * (size_of::<Edge<String>>() + 4)
+        );
+        assert!(
+            more_nodes.deep_size_of() - graph.deep_size_of() >= 1000 * size_of::<Node<String>>()
+        );
+    }
+
+    #[test]
+    fn stable_graph_removal() {
+        let mut graph: StableGraph<String, String> = build_graph(1000, 10_000).into();
+        let full = graph.deep_size_of();
+        let first = graph.node_indices().next().unwrap();
+        let removed = graph[first].capacity();
+        let edges = graph.edges(first).count()
+            + graph
+                .edges_directed(first, petgraph::Direction::Incoming)
+                .count();
+        graph.remove_node(first);
+
+        // The vacant slots are still allocated, but their weights are not
+        let size = graph.deep_size_of();
+        assert!(size < full);
+        assert!(full - size >= removed + edges * 4 / 2);
+    }
+
+    #[test]
+    fn graph_map() {
+        let mut graph: DiGraphMap<u32, String> = DiGraphMap::new();
+        for i in 0..10_000u32 {
+            graph.add_edge(i % 1000, i / 10 % 1000, i.to_string());
+        }
+        assert_eq!(graph.edge_count(), 10_000);
+        let small = graph.deep_size_of();
+        for i in 10_000..20_000u32 {
+            graph.add_edge(i % 1000, (i / 10 + 1) % 1000, i.to_string());
+        }
+        assert_eq!(graph.edge_count(), 20_000);
+        assert!(graph.deep_size_of() > small + 10_000 * size_of::<String>());
+    }
+}
+
+#[cfg(feature = "ndarray")]
+mod ndarray_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use ndarray::{s, ArcArray, Array2, ArrayD, ArrayView2, CowArray, Ix2, IxDyn};
+
+    #[test]
+    fn owned_array() {
+        let array = Array2::<f64>::zeros((30, 40));
+        assert_eq!(
+            array.deep_size_of(),
+            size_of::<Array2<f64>>() + 30 * 40 * size_of::<f64>()
+        );
+    }
+
+    #[test]
+    fn element_children() {
+        let array = ArrayD::from_elem(IxDyn(&[2, 3, 4]), String::from("element"));
+        assert_eq!(
+            array.deep_size_of(),
+            size_of::<ArrayD<String>>() + 24 * (size_of::<String>() + 7)
+        );
+    }
+
+    #[test]
+    fn shared_array() {
+        let array: ArcArray<f64, Ix2> = ArcArray::zeros((30, 40));
+        let clones = (array.clone(), array.clone(), array);
+        assert_eq!(
+            clones.deep_size_of(),
+            3 * size_of::<ArcArray<f64, Ix2>>() + 30 * 40 * size_of::<f64>()
+        );
+    }
+
+    #[test]
+    fn sliced_shared_array() {
+        let array: ArcArray<f64, Ix2> = ArcArray::zeros((30, 40));
+        let head = array.clone().slice_move(s![..10, ..]);
+        let tail = array.clone().slice_move(s![10.., ..]);
+        assert!(!array.is_unique());
+
+        // A slice with the same first element shares the buffer, but the
+        // buffer can't be found from a slice that starts elsewhere
+        let clones = (array, head, tail);
+        assert_eq!(
+            clones.deep_size_of(),
+            3 * size_of::<ArcArray<f64, Ix2>>() + (30 + 20) * 40 * size_of::<f64>()
+        );
+    }
+
+    #[test]
+    fn views() {
+        let array = Array2::<f64>::zeros((30, 40));
+        let view = array.slice(s![..10, ..]);
+        assert_eq!(view.deep_size_of(), size_of::<ArrayView2<f64>>());
+
+        let borrowed = CowArray::from(array.view());
+        let owned = CowArray::from(array.clone());
+        assert_eq!(borrowed.deep_size_of(), size_of::<CowArray<f64, Ix2>>());
+        assert_eq!(
+            owned.deep_size_of(),
+            size_of::<CowArray<f64, Ix2>>() + 30 * 40 * size_of::<f64>()
+        );
+    }
+}
+
+#[cfg(feature = "hashbrown")]
+mod hashbrown_tests {
+    use crate::DeepSizeOf;
+    use alloc::string::String;
+    use core::mem::size_of;
+    use hashbrown::{HashMap, HashSet};
+
+    #[test]
+    fn swiss_table() {
+        let mut map: HashMap<u8, u8> = HashMap::with_capacity(3);
+        map.insert(0, 1);
+        assert_eq!(
+            map.deep_size_of(),
+            size_of::<HashMap<u8, u8>>() + map.capacity() * 3
+        );
+
+        let mut set: HashSet<String> = HashSet::new();
+        set.insert(String::from("Hello World"));
+        assert_eq!(
+            set.deep_size_of(),
+            size_of::<HashSet<String>>() + set.capacity() * (size_of::<String>() + 1) + 11
+        );
+    }
+
+    #[test]
+    fn swiss_table_bounds() {
+        use crate::Context;
+
+        // 100 elements of capacity needs 128 buckets
+        let map: HashMap<u64, u64> = HashMap::with_capacity(100);
+        assert_eq!(
+            map.deep_size_of_children_approx(&mut Context::new()),
+            (map.capacity() * 17, 128 * 17 + 32)
+        );
+
+        let set: HashSet<String> = (0..100).map(|i| i.to_string()).collect();
+        let size = set.deep_size_of_children(&mut Context::new());
+        let (lower, upper) = set.deep_size_of_children_approx(&mut Context::new());
+        assert_eq!(lower, size);
+        assert!(size < upper);
+    }
+}
+
+#[cfg(feature = "nalgebra")]
+mod nalgebra_tests {
+    use super::*;
+    use nalgebra::{DMatrix, DVector, Matrix4, Vector3};
+
+    #[test]
+    fn static_matrices() {
+        #[derive(DeepSizeOf)]
+        struct Transform {
+            matrix: Matrix4<f32>,
+            offset: Vector3<f32>,
+            name: String,
+        }
+
+        let transform = Transform {
+            matrix: Matrix4::identity(),
+            offset: Vector3::zeros(),
+            name: String::from("camera"),
+        };
+        assert_eq!(transform.deep_size_of(), size_of::<Transform>() + 6);
+    }
+
+    #[test]
+    fn dynamic_matrices() {
+        let matrix = DMatrix::<f64>::zeros(1000, 1000);
+        assert_eq!(
+            matrix.deep_size_of(),
+            size_of::<DMatrix<f64>>() + 1000 * 1000 * size_of::<f64>()
+        );
+
+        let vector = DVector::<f32>::zeros(100);
+        assert_eq!(vector.deep_size_of(), size_of::<DVector<f32>>() + 400);
+        assert_eq!(
+            matrix.column(0).deep_size_of(),
+            size_of_val(&matrix.column(0))
+        );
+    }
+}
+
+#[cfg(feature = "nalgebra-sparse")]
+mod nalgebra_sparse_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use nalgebra_sparse::{CooMatrix, CsrMatrix};
+
+    #[test]
+    fn csr_matrix() {
+        let mut coo = CooMatrix::<f64>::new(100, 100);
+        for i in 0..100 {
+            coo.push(i, i, 1.0);
+            coo.push(i, (i * 7) % 100, 2.0);
+        }
+        let csr = CsrMatrix::from(&coo);
+        let nnz = csr.nnz();
+        assert_eq!(
+            csr.deep_size_of(),
+            size_of::<CsrMatrix<f64>>() + nnz * (8 + size_of::<usize>()) + 101 * size_of::<usize>()
+        );
+    }
+}
+
+#[cfg(feature = "tokio_sync")]
+mod tokio_sync_tests {
+    use crate::DeepSizeOf;
+    use alloc::string::String;
+    use alloc::sync::Arc;
+    use core::mem::{size_of, size_of_val};
+    use std::collections::HashMap;
+    use tokio::sync::{mpsc, watch, Mutex, RwLock};
+
+    #[test]
+    fn locks() {
+        let mut map = HashMap::new();
+        map.insert(1u32, String::from("Hello World"));
+        let expected = map.deep_size_of() - size_of::<HashMap<u32, String>>();
+
+        let shared = Arc::new(RwLock::new(map));
+        assert_eq!(
+            shared.deep_size_of(),
+            size_of::<usize>() + size_of::<RwLock<HashMap<u32, String>>>() + expected
+        );
+
+        // Contended locks can't be measured
+        let guard = shared.try_write().unwrap();
+        assert_eq!(
+            shared.deep_size_of(),
+            size_of::<usize>() + size_of::<RwLock<HashMap<u32, String>>>()
+        );
+        drop(guard);
+
+        let mutex = Mutex::new(String::from("abc"));
+        assert_eq!(mutex.deep_size_of(), size_of::<Mutex<String>>() + 3);
+
+        let guard = mutex.try_lock().unwrap();
+        assert_eq!(mutex.deep_size_of(), size_of::<Mutex<String>>());
+        drop(guard);
+        assert_eq!(mutex.deep_size_of(), size_of::<Mutex<String>>() + 3);
+    }
+
+    #[test]
+    fn bounded_channels() {
+        for &capacity in &[1, 16, 1024] {
+            let (sender, receiver) = mpsc::channel::<[u8; 32]>(capacity);
+            assert_eq!(sender.deep_size_of(), size_of::<mpsc::Sender<[u8; 32]>>());
+            assert_eq!(
+                receiver.deep_size_of(),
+                size_of::<mpsc::Receiver<[u8; 32]>>() + capacity * 32
+            );
+        }
+    }
+
+    #[test]
+    fn watch_channels() {
+        let (sender, receiver) = watch::channel(String::from("Hello World"));
+        let other = receiver.clone();
+        let handles = (sender, receiver, other);
+        assert_eq!(
+            handles.deep_size_of(),
+            size_of_val(&handles) + size_of::<String>() + 11
+        );
+    }
+}
+
+#[cfg(feature = "smartstring")]
+mod smartstring_tests {
+    use crate::DeepSizeOf;
+    use alloc::vec::Vec;
+    use core::mem::size_of;
+    use smartstring::alias::String as SmartString;
+    use smartstring::MAX_INLINE;
+
+    #[test]
+    fn inline_boundary() {
+        let inline = SmartString::from("a".repeat(MAX_INLINE).as_str());
+        assert!(inline.is_inline());
+        assert_eq!(inline.deep_size_of(), size_of::<SmartString>());
+
+        let boxed = SmartString::from("a".repeat(MAX_INLINE + 1).as_str());
+        assert!(!boxed.is_inline());
+        assert_eq!(
+            boxed.deep_size_of(),
+            size_of::<SmartString>() + boxed.capacity()
+        );
+    }
+
+    #[test]
+    fn mixed_vec() {
+        let strings: Vec<SmartString> = (0..10)
+            .map(|i| SmartString::from("a".repeat(i * 5).as_str()))
+            .collect();
+        let heap: usize = strings
+            .iter()
+            .filter(|s| !s.is_inline())
+            .map(|s| s.capacity())
+            .sum();
+        // Only the strings longer than MAX_INLINE (23) are on the heap
+        assert!(heap >= 25 + 30 + 35 + 40 + 45);
+        assert_eq!(
+            strings.deep_size_of(),
+            size_of::<Vec<SmartString>>() + strings.capacity() * size_of::<SmartString>() + heap
+        );
+    }
+}
+
+#[cfg(feature = "bstr")]
+mod bstr_tests {
+    use crate::DeepSizeOf;
+    use alloc::borrow::Cow;
+    use alloc::boxed::Box;
+    use bstr::{BStr, BString, ByteSlice};
+    use core::mem::size_of;
+    use std::collections::HashMap;
+
+    #[test]
+    fn byte_strings() {
+        let string = BString::from(&b"Hello\xFFWorld"[..]);
+        assert_eq!(
+            string.deep_size_of(),
+            size_of::<BString>() + string.capacity()
+        );
+
+        let boxed: Box<BStr> = Box::<[u8]>::from(string.as_bytes()).into();
+        assert_eq!(boxed.deep_size_of(), size_of::<Box<BStr>>() + 11);
+
+        let borrowed: Cow<BStr> = Cow::Borrowed(string.as_bstr());
+        let owned: Cow<BStr> = Cow::Owned(string.clone());
+        assert_eq!(borrowed.deep_size_of(), size_of::<Cow<BStr>>());
+        assert_eq!(owned.deep_size_of(), size_of::<Cow<BStr>>() + owned.len());
+    }
+
+    #[test]
+    fn map_of_byte_strings() {
+        let mut map = HashMap::new();
+        for i in 0..100u8 {
+            map.insert(BString::from(vec![i; 10]), BString::from(vec![i; 20]));
+        }
+        assert_eq!(
+            map.deep_size_of(),
+            size_of::<HashMap<BString, BString>>()
+                + map.capacity() * (2 * size_of::<BString>() + 1)
+                + 100 * 30
+        );
+    }
+}
+
+#[cfg(feature = "beef")]
+mod beef_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+
+    #[derive(DeepSizeOf)]
+    struct Names<'a> {
+        borrowed: beef::Cow<'a, str>,
+        owned: beef::Cow<'a, str>,
+        lean_borrowed: beef::lean::Cow<'a, str>,
+        lean_owned: beef::lean::Cow<'a, str>,
+        bytes: beef::Cow<'a, [u8]>,
+    }
+
+    #[test]
+    fn cows() {
+        let source = String::from("borrowed");
+        let names = Names {
+            borrowed: beef::Cow::borrowed(&source),
+            owned: beef::Cow::owned(String::from("owned string")),
+            lean_borrowed: beef::lean::Cow::borrowed(&source),
+            lean_owned: beef::lean::Cow::owned(String::from("lean")),
+            bytes: beef::Cow::owned(vec![0u8; 32]),
+        };
+        assert_eq!(names.deep_size_of(), size_of::<Names>() + 12 + 4 + 32);
+
+        let borrowed = Names {
+            borrowed: beef::Cow::borrowed(&source),
+            owned: beef::Cow::borrowed(&source),
+            lean_borrowed: beef::lean::Cow::borrowed(&source),
+            lean_owned: beef::lean::Cow::borrowed(&source),
+            bytes: beef::Cow::borrowed(source.as_bytes()),
+        };
+        assert_eq!(borrowed.deep_size_of(), size_of::<Names>());
+    }
+}
+
+#[cfg(feature = "heapless")]
+mod heapless_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+
+    #[test]
+    fn vecs() {
+        let mut vec: heapless::Vec<String, 8> = heapless::Vec::new();
+        vec.push(String::from("hello")).unwrap();
+        vec.push(String::from("world!")).unwrap();
+        let string_sizes: usize = vec.iter().map(String::capacity).sum();
+        assert_eq!(
+            vec.deep_size_of(),
+            size_of::<heapless::Vec<String, 8>>() + string_sizes
+        );
+    }
+
+    #[test]
+    fn strings() {
+        let mut string: heapless::String<32> = heapless::String::new();
+        string.push_str(&"a".repeat(32)).unwrap();
+        assert_eq!(string.deep_size_of(), size_of::<heapless::String<32>>());
+    }
+
+    #[test]
+    fn maps_and_deques() {
+        let mut map: heapless::index_map::FnvIndexMap<u32, Vec<u8>, 16> =
+            heapless::index_map::FnvIndexMap::new();
+        map.insert(1, vec![0; 10]).unwrap();
+        map.insert(2, vec![0; 20]).unwrap();
+        assert_eq!(
+            map.deep_size_of(),
+            size_of::<heapless::index_map::FnvIndexMap<u32, Vec<u8>, 16>>() + 30
+        );
+
+        let mut deque: heapless::Deque<Vec<u8>, 4> = heapless::Deque::new();
+        deque.push_back(vec![0; 10]).unwrap();
+        deque.push_front(vec![0; 5]).unwrap();
+        assert_eq!(
+            deque.deep_size_of(),
+            size_of::<heapless::Deque<Vec<u8>, 4>>() + 15
+        );
+    }
+}
+
+#[cfg(feature = "enum-map")]
+mod enum_map_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use enum_map::{enum_map, Enum, EnumMap};
+
+    #[derive(Enum)]
+    enum Channel {
+        Red,
+        Green,
+        Blue,
+    }
+
+    #[test]
+    fn enum_maps() {
+        let map: EnumMap<Channel, Vec<u32>> = enum_map! {
+            Channel::Red => vec![0; 10],
+            Channel::Green => vec![0; 20],
+            Channel::Blue => Vec::new(),
+        };
+        assert_eq!(
+            map.deep_size_of(),
+            size_of::<EnumMap<Channel, Vec<u32>>>() + 30 * 4
+        );
+    }
+}
+
+#[cfg(feature = "fixedbitset")]
+mod fixedbitset_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use fixedbitset::FixedBitSet;
+
+    #[test]
+    fn bit_sets() {
+        let mut set = FixedBitSet::with_capacity(4_000_000);
+        set.insert_range(1000..2000);
+        assert_eq!(set.deep_size_of(), size_of::<FixedBitSet>() + 4_000_000 / 8);
+
+        let set = FixedBitSet::with_capacity(65);
+        assert_eq!(
+            set.deep_size_of(),
+            size_of::<FixedBitSet>() + 2 * size_of::<usize>()
+        );
+
+        let set = FixedBitSet::new();
+        assert_eq!(set.deep_size_of(), size_of::<FixedBitSet>());
+    }
+}
+
+#[cfg(all(feature = "ahash", feature = "rustc-hash", feature = "fnv"))]
+mod hasher_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use std::collections::HashMap;
+
+    #[test]
+    fn hasher_builders() {
+        assert_eq!(
+            ahash::RandomState::new().deep_size_of(),
+            size_of::<ahash::RandomState>()
+        );
+        assert_eq!(rustc_hash::FxBuildHasher.deep_size_of(), 0);
+        assert_eq!(fnv::FnvBuildHasher::default().deep_size_of(), 0);
+    }
+
+    #[test]
+    fn maps_with_custom_hashers() {
+        let mut fx_map: rustc_hash::FxHashMap<u64, String> = Default::default();
+        let mut a_map: ahash::AHashMap<u64, String> = Default::default();
+        for i in 0..100 {
+            fx_map.insert(i, format!("{:010}", i));
+            a_map.insert(i, format!("{:010}", i));
+        }
+        let string_sizes: usize = fx_map.values().map(String::capacity).sum();
+
+        assert_eq!(
+            fx_map.deep_size_of(),
+            size_of::<rustc_hash::FxHashMap<u64, String>>()
+                + fx_map.capacity() * (size_of::<(u64, String)>() + 1)
+                + string_sizes
+        );
+        let a_map: &HashMap<u64, String, ahash::RandomState> = &a_map;
+        assert_eq!(
+            a_map.deep_size_of(),
+            size_of::<HashMap<u64, String, ahash::RandomState>>()
+                + a_map.capacity() * (size_of::<(u64, String)>() + 1)
+                + string_sizes
+        );
+    }
+}
+
+mod property_tests {
+    use crate::{Context, DeepSizeOf};
+    use core::mem::size_of;
+    use quickcheck::quickcheck;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    quickcheck! {
+        fn vec_counts_every_element(vec: Vec<u32>) -> bool {
+            vec.deep_size_of() >= size_of::<Vec<u32>>() + vec.len() * size_of::<u32>()
+        }
+
+        fn string_counts_its_capacity(string: String) -> bool {
+            string.deep_size_of() == size_of::<String>() + string.capacity()
+        }
+
+        fn nested_vecs_count_their_children(vecs: Vec<Vec<u8>>) -> bool {
+            let children: usize = vecs.iter().map(|vec| vec.deep_size_of()).sum();
+            vecs.deep_size_of()
+                == size_of::<Vec<Vec<u8>>>()
+                    + (vecs.capacity() - vecs.len()) * size_of::<Vec<u8>>()
+                    + children
+        }
+
+        fn cloning_an_arc_doesnt_change_its_size(vec: Vec<String>) -> bool {
+            let arc = Arc::new(vec);
+            let before = arc.deep_size_of();
+            let clone = Arc::clone(&arc);
+            before == arc.deep_size_of() && before == clone.deep_size_of()
+        }
+
+        fn shared_arcs_are_counted_once(vec: Vec<String>) -> bool {
+            let arc = Arc::new(vec);
+            let clone = Arc::clone(&arc);
+            let mut context = Context::new();
+            let total = arc.deep_size_of_with_context(&mut context)
+                + clone.deep_size_of_with_context(&mut context);
+            total == arc.deep_size_of() + size_of::<Arc<Vec<String>>>()
+        }
+
+        fn inserting_never_shrinks_a_map(entries: Vec<(u16, String)>) -> bool {
+            let mut map = HashMap::new();
+            let mut last = map.deep_size_of();
+            for (key, val) in entries {
+                // Replacing an existing value could free memory
+                map.entry(key).or_insert(val);
+                let size = map.deep_size_of();
+                if size < last {
+                    return false;
+                }
+                last = size;
+            }
+            true
+        }
+    }
+}
+
+#[cfg(feature = "lasso")]
+mod lasso_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use lasso::{Rodeo, Spur, ThreadedRodeo};
+
+    #[test]
+    fn interners() {
+        let mut rodeo = Rodeo::default();
+        let mut interned = 0;
+        for i in 0..5000 {
+            let string = format!("identifier_{}", i);
+            interned += string.len();
+            rodeo.get_or_intern(&string);
+        }
+        // Interning a string twice doesn't store it again
+        let key: Spur = rodeo.get_or_intern("identifier_0");
+        assert_eq!(key.deep_size_of(), size_of::<Spur>());
+
+        let size = rodeo.deep_size_of();
+        assert!(size >= size_of::<Rodeo>() + interned + 5000 * size_of::<&str>());
+        assert!(size <= size_of::<Rodeo>() + 2 * interned + 5000 * 64);
+
+        let reader = rodeo.into_reader();
+        assert!(reader.deep_size_of() >= interned + 5000 * size_of::<&str>());
+        let resolver = reader.into_resolver();
+        assert_eq!(
+            resolver.deep_size_of(),
+            size_of::<lasso::RodeoResolver>() + interned + 5000 * size_of::<&str>()
+        );
+    }
+
+    #[test]
+    fn threaded_interners() {
+        let rodeo = ThreadedRodeo::default();
+        let mut interned = 0;
+        for i in 0..5000 {
+            let string = format!("identifier_{}", i);
+            interned += string.len();
+            rodeo.get_or_intern(&string);
+        }
+
+        let size = rodeo.deep_size_of();
+        assert!(size >= size_of::<ThreadedRodeo>() + interned + 5000 * size_of::<&str>());
+        assert!(size <= size_of::<ThreadedRodeo>() + 2 * interned + 5000 * 64);
+    }
+}
+
+#[cfg(feature = "anyhow")]
+mod anyhow_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use std::fmt;
+
+    #[derive(Debug)]
+    struct LargeError([u8; 256]);
+
+    impl fmt::Display for LargeError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "large error ({} bytes)", self.0.len())
+        }
+    }
+
+    impl std::error::Error for LargeError {}
+
+    #[test]
+    fn errors() {
+        let header = size_of::<usize>() + size_of::<Option<std::backtrace::Backtrace>>();
+
+        let error = anyhow::Error::new(LargeError([0; 256]));
+        assert_eq!(
+            error.deep_size_of(),
+            size_of::<anyhow::Error>() + header + 256
+        );
+
+        let error = error.context(0u64);
+        assert_eq!(
+            error.deep_size_of(),
+            size_of::<anyhow::Error>() + 2 * header + 256 + 8 + size_of::<anyhow::Error>()
+        );
+    }
+}
+
+#[cfg(feature = "arrow")]
+mod arrow_tests {
+    use crate::{Context, DeepSizeOf};
+    use arrow_array::{ArrayRef, Int32Array, RecordBatch};
+    use core::mem::size_of;
+    use std::sync::Arc;
+
+    #[test]
+    fn buffers() {
+        let buffer = arrow_buffer::Buffer::from_vec(vec![0u64; 100]);
+        assert_eq!(
+            buffer.deep_size_of(),
+            size_of::<arrow_buffer::Buffer>() + 800
+        );
+
+        let sliced = buffer.slice(80);
+        let mut context = Context::new();
+        assert_eq!(buffer.deep_size_of_children(&mut context), 800);
+        assert_eq!(sliced.deep_size_of_children(&mut context), 0);
+    }
+
+    #[test]
+    fn sliced_arrays() {
+        let array = Int32Array::from((0..1000).collect::<Vec<i32>>());
+        let values_size = array.values().inner().capacity();
+        assert_eq!(values_size, 4000);
+
+        let first: ArrayRef = Arc::new(array.slice(0, 500));
+        let second: ArrayRef = Arc::new(array.slice(500, 500));
+
+        let mut context = Context::new();
+        let total = first.deep_size_of_with_context(&mut context)
+            + second.deep_size_of_with_context(&mut context);
+        let array_size = size_of::<Int32Array>() + size_of::<arrow_buffer::Buffer>();
+        assert_eq!(
+            total,
+            2 * size_of::<ArrayRef>() + 2 * array_size + values_size
+        );
+
+        let batch = RecordBatch::try_from_iter(vec![("first", first), ("second", second)]).unwrap();
+        assert_eq!(
+            batch.deep_size_of(),
+            size_of::<RecordBatch>() + 2 * size_of::<ArrayRef>() + 2 * array_size + values_size
+        );
+    }
+}
+
+#[cfg(feature = "halfbrown")]
+mod halfbrown_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use halfbrown::HashMap;
+
+    #[test]
+    fn vec_and_map_backends() {
+        let mut map: HashMap<u64, String> = HashMap::new();
+        for i in 0..32 {
+            map.insert(i, format!("{:08}", i));
+        }
+        assert!(map.is_vec());
+        let string_sizes: usize = map.values().map(String::capacity).sum();
+        assert_eq!(
+            map.deep_size_of(),
+            size_of::<HashMap<u64, String>>()
+                + map.capacity() * size_of::<(u64, String)>()
+                + string_sizes
+        );
+
+        map.insert(32, format!("{:08}", 32));
+        assert!(map.is_map());
+        let string_sizes: usize = map.values().map(String::capacity).sum();
+        assert_eq!(
+            map.deep_size_of(),
+            size_of::<HashMap<u64, String>>()
+                + map.capacity() * (size_of::<u64>() + size_of::<String>() + 1)
+                + string_sizes
+        );
+    }
+}
+
+#[cfg(feature = "thin-vec")]
+mod thin_vec_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use thin_vec::ThinVec;
+
+    #[test]
+    fn thin_vecs() {
+        let empty: ThinVec<String> = ThinVec::new();
+        assert_eq!(empty.deep_size_of(), size_of::<ThinVec<String>>());
+
+        let header = 2 * size_of::<usize>();
+        let mut small: ThinVec<String> = ThinVec::with_capacity(2);
+        small.push(String::from("hello"));
+        small.push(String::from("world"));
+        assert_eq!(
+            small.deep_size_of(),
+            size_of::<ThinVec<String>>() + header + 2 * size_of::<String>() + 10
+        );
+
+        let mut spilled: ThinVec<String> = ThinVec::new();
+        for i in 0..5 {
+            spilled.push(format!("{:04}", i));
+        }
+        assert!(spilled.capacity() > spilled.len());
+        let string_sizes: usize = spilled.iter().map(String::capacity).sum();
+        assert_eq!(
+            spilled.deep_size_of(),
+            size_of::<ThinVec<String>>()
+                + header
+                + spilled.capacity() * size_of::<String>()
+                + string_sizes
+        );
+    }
+}
+
+#[cfg(feature = "crossbeam-queue")]
+mod crossbeam_queue_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use crossbeam_queue::{ArrayQueue, SegQueue};
+
+    #[test]
+    fn array_queues() {
+        let queue: ArrayQueue<u64> = ArrayQueue::new(100);
+        queue.push(1).unwrap();
+        assert_eq!(
+            queue.deep_size_of(),
+            size_of::<ArrayQueue<u64>>() + 100 * 16
+        );
+
+        // Children of queued values aren't counted
+        let queue: ArrayQueue<String> = ArrayQueue::new(10);
+        queue.push("a".repeat(100)).unwrap();
+        assert_eq!(
+            queue.deep_size_of(),
+            size_of::<ArrayQueue<String>>() + 10 * (size_of::<String>() + 8)
+        );
+    }
+
+    #[test]
+    fn seg_queues() {
+        let queue: SegQueue<u64> = SegQueue::new();
+        assert_eq!(queue.deep_size_of(), size_of::<SegQueue<u64>>());
+
+        for i in 0..100 {
+            queue.push(i);
+        }
+        // Four blocks, each with a pointer and 31 slots
+        let block_size = 8 + 31 * 16;
+        assert_eq!(
+            queue.deep_size_of(),
+            size_of::<SegQueue<u64>>() + 4 * block_size
+        );
+        // The elements fill at least four blocks, and span at most five
+        assert_eq!(
+            queue.deep_size_of_children_approx(&mut crate::Context::new()),
+            (4 * block_size, 5 * block_size)
+        );
+    }
+}
+
+#[cfg(feature = "crossbeam-channel")]
+mod crossbeam_channel_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use crossbeam_channel::{bounded, unbounded, Receiver, Sender};
+
+    #[test]
+    fn bounded_channels() {
+        let (sender, receiver) = bounded::<u64>(64);
+        for i in 0..64 {
+            sender.send(i).unwrap();
+        }
+        assert!(sender.is_full());
+
+        assert_eq!(sender.deep_size_of(), size_of::<Sender<u64>>());
+        assert_eq!(
+            receiver.deep_size_of(),
+            size_of::<Receiver<u64>>() + 64 * 16
+        );
+    }
+
+    #[test]
+    fn unbounded_channels() {
+        let (sender, receiver) = unbounded();
+        sender.send(String::from("in flight")).unwrap();
+
+        // Messages in the channel can't be reached
+        assert_eq!(sender.deep_size_of(), size_of::<Sender<String>>());
+        assert_eq!(receiver.deep_size_of(), size_of::<Receiver<String>>());
+    }
+}
+
+#[cfg(feature = "arc-swap")]
+mod arc_swap_tests {
+    use crate::DeepSizeOf;
+    use arc_swap::{ArcSwap, ArcSwapOption};
+    use core::mem::size_of;
+    use std::sync::Arc;
+
+    #[derive(DeepSizeOf)]
+    struct Config {
+        current: ArcSwap<Vec<u8>>,
+        previous: Arc<Vec<u8>>,
+        pending: ArcSwapOption<Vec<u8>>,
+    }
+
+    #[test]
+    fn shared_arcs() {
+        let shared = Arc::new(vec![0u8; 1000]);
+        let config = Config {
+            current: ArcSwap::new(Arc::clone(&shared)),
+            previous: shared,
+            pending: ArcSwapOption::empty(),
+        };
+        assert_eq!(
+            config.deep_size_of(),
+            size_of::<Config>() + size_of::<Vec<u8>>() + 1000
+        );
+
+        config.pending.store(Some(Arc::new(vec![0u8; 500])));
+        assert_eq!(
+            config.deep_size_of(),
+            size_of::<Config>() + 2 * size_of::<Vec<u8>>() + 1500
+        );
+    }
+}
+
+#[cfg(feature = "once_cell")]
+mod once_cell_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use once_cell::{sync, unsync};
+
+    #[test]
+    fn once_cells() {
+        let cell: unsync::OnceCell<Vec<u8>> = unsync::OnceCell::new();
+        assert_eq!(cell.deep_size_of(), size_of::<unsync::OnceCell<Vec<u8>>>());
+        cell.set(vec![0; 100]).unwrap();
+        assert_eq!(
+            cell.deep_size_of(),
+            size_of::<unsync::OnceCell<Vec<u8>>>() + 100
+        );
+
+        let cell: sync::OnceCell<Vec<u8>> = sync::OnceCell::new();
+        assert_eq!(cell.deep_size_of(), size_of::<sync::OnceCell<Vec<u8>>>());
+        cell.set(vec![0; 100]).unwrap();
+        assert_eq!(
+            cell.deep_size_of(),
+            size_of::<sync::OnceCell<Vec<u8>>>() + 100
+        );
+    }
+
+    #[test]
+    fn lazies() {
+        let lazy: unsync::Lazy<Vec<u8>> = unsync::Lazy::new(|| panic!("forced"));
+        assert_eq!(lazy.deep_size_of(), size_of::<unsync::Lazy<Vec<u8>>>());
+
+        let lazy: sync::Lazy<Vec<u8>> = sync::Lazy::new(|| panic!("forced"));
+        assert_eq!(lazy.deep_size_of(), size_of::<sync::Lazy<Vec<u8>>>());
+
+        let lazy: sync::Lazy<Vec<u8>> = sync::Lazy::new(|| vec![0; 100]);
+        sync::Lazy::force(&lazy);
+        assert_eq!(lazy.deep_size_of(), size_of::<sync::Lazy<Vec<u8>>>() + 100);
+    }
+}
+
+#[cfg(feature = "generic-array")]
+mod generic_array_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use generic_array::typenum::{U32, U4};
+    use generic_array::GenericArray;
+
+    #[test]
+    fn generic_arrays() {
+        let digest: GenericArray<u8, U32> = GenericArray::default();
+        assert_eq!(digest.deep_size_of(), 32);
+
+        let strings: GenericArray<String, U4> = GenericArray::from_array([
+            "a".repeat(10),
+            "b".repeat(20),
+            String::new(),
+            "c".repeat(30),
+        ]);
+        assert_eq!(strings.deep_size_of(), 4 * size_of::<String>() + 60);
+    }
+}
+
+#[cfg(feature = "ipnet")]
+mod ipnet_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use ipnet::IpNet;
+    use std::collections::BTreeMap;
+
+    #[derive(DeepSizeOf)]
+    struct RouteInfo {
+        next_hop: std::net::IpAddr,
+        interface: String,
+    }
+
+    #[test]
+    fn routing_tables() {
+        let mut routes = BTreeMap::new();
+        for i in 0..60u8 {
+            let net: IpNet = format!("10.{}.0.0/16", i).parse().unwrap();
+            let info = RouteInfo {
+                next_hop: [10, i, 0, 1].into(),
+                interface: String::from("eth0"),
+            };
+            routes.insert(net, info);
+        }
+
+        let entry_size = size_of::<IpNet>() + size_of::<RouteInfo>();
+        let interfaces: usize = routes.values().map(|info| info.interface.capacity()).sum();
+        assert_eq!(
+            routes.deep_size_of(),
+            size_of::<BTreeMap<IpNet, RouteInfo>>()
+                + 60 * entry_size
+                + (60 / 6) * entry_size * 11
+                + interfaces
+        );
+    }
+}
+
+#[cfg(feature = "semver")]
+mod semver_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use semver::{Comparator, Version, VersionReq};
+
+    #[test]
+    fn versions() {
+        let releases: Vec<Version> = (0..10).map(|i| Version::new(1, i, 0)).collect();
+        assert_eq!(
+            releases.deep_size_of(),
+            size_of::<Vec<Version>>() + releases.capacity() * size_of::<Version>()
+        );
+
+        // "rc.1" is stored inline, while the long identifiers are allocated
+        let prereleases: Vec<Version> = ["1.0.0-rc.1", "1.0.0-alpha.build.7+nightly.2024"]
+            .iter()
+            .map(|s| Version::parse(s).unwrap())
+            .collect();
+        assert_eq!(
+            prereleases.deep_size_of(),
+            size_of::<Vec<Version>>()
+                + prereleases.capacity() * size_of::<Version>()
+                + "alpha.build.7".len()
+                + "nightly.2024".len()
+        );
+    }
+
+    #[test]
+    fn version_requirements() {
+        let req = VersionReq::parse(">=1.2.3-beta.long.1, <2").unwrap();
+        assert_eq!(
+            req.deep_size_of(),
+            size_of::<VersionReq>() + 2 * size_of::<Comparator>() + "beta.long.1".len()
+        );
+    }
+}
+
+#[cfg(feature = "http")]
+mod http_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use http::header::{HeaderMap, HeaderName, HeaderValue};
+    use http::{Method, StatusCode, Uri};
+
+    #[test]
+    fn header_maps() {
+        let mut headers = HeaderMap::new();
+        let mut names_and_values = 0;
+        for i in 0..20 {
+            let name = format!("x-custom-header-{}", i);
+            let value = format!("value-{}", i);
+            names_and_values += name.len() + value.len();
+            headers.insert(
+                HeaderName::from_bytes(name.as_bytes()).unwrap(),
+                HeaderValue::from_str(&value).unwrap(),
+            );
+        }
+        assert_eq!(headers.capacity(), 24);
+
+        let entry_size = size_of::<(u16, HeaderName, HeaderValue, Option<(usize, usize)>)>();
+        assert_eq!(
+            headers.deep_size_of(),
+            size_of::<HeaderMap>() + 32 * 4 + 24 * entry_size + names_and_values
+        );
+
+        // Additional values for a name are stored separately
+        let before = headers.deep_size_of();
+        headers.append("x-custom-header-0", HeaderValue::from_static("second"));
+        let extra_size = size_of::<(HeaderValue, [usize; 2], [usize; 2])>();
+        assert_eq!(headers.deep_size_of(), before + extra_size + "second".len());
+    }
+
+    #[test]
+    fn header_names() {
+        use http::header::CONTENT_TYPE;
+
+        assert_eq!(CONTENT_TYPE.deep_size_of(), size_of::<HeaderName>());
+        let parsed = HeaderName::from_bytes(b"Content-Type").unwrap();
+        assert_eq!(parsed.deep_size_of(), size_of::<HeaderName>());
+        let custom = HeaderName::from_bytes(b"X-Request-Id").unwrap();
+        assert_eq!(custom.deep_size_of(), size_of::<HeaderName>() + 12);
+    }
+
+    #[test]
+    fn uris() {
+        let text = "https://example.com/a/very/long/path/to/some/resource?query=value&other=1";
+        let uri: Uri = text.parse().unwrap();
+        assert_eq!(
+            uri.deep_size_of(),
+            size_of::<Uri>() + text.len() - "https://".len()
+        );
+
+        let uri: Uri = "custom://example.com/".parse().unwrap();
+        assert_eq!(
+            uri.deep_size_of(),
+            size_of::<Uri>() + "custom".len() + "example.com/".len()
+        );
+    }
+
+    #[test]
+    fn small_types() {
+        assert_eq!(Method::GET.deep_size_of(), size_of::<Method>());
+        assert_eq!(StatusCode::OK.deep_size_of(), size_of::<StatusCode>());
+    }
+}
+
+#[cfg(feature = "ropey")]
+mod ropey_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use ropey::Rope;
+
+    #[test]
+    fn ropes() {
+        let empty = Rope::new();
+        assert_eq!(empty.deep_size_of(), size_of::<Rope>() + 1024);
+
+        // Ropey documents about 10% overhead for freshly loaded text
+        let line = "The quick brown fox jumps over the lazy dog.\n";
+        let text = line.repeat(10 * 1024 * 1024 / line.len());
+        let rope = Rope::from_str(&text);
+        let size = rope.deep_size_of();
+        assert!(size > text.len() + text.len() / 20, "{}", size);
+        assert!(size < text.len() + text.len() / 5, "{}", size);
+
+        // Scattered edits leave partially filled nodes
+        let mut edited = rope.clone();
+        for i in 0..2000 {
+            edited.insert(i * 4000, "edit");
+        }
+        let edited_size = edited.deep_size_of();
+        assert!(edited_size > size);
+        assert!(edited_size < text.len() * 16 / 10, "{}", edited_size);
+
+        assert_eq!(
+            rope.slice(..100).deep_size_of(),
+            size_of::<ropey::RopeSlice>()
+        );
+    }
+}
+
+#[cfg(feature = "bimap")]
+mod bimap_tests {
+    use crate::DeepSizeOf;
+    use alloc::string::{String, ToString};
+    use bimap::{BiBTreeMap, BiHashMap};
+    use core::mem::size_of;
+
+    #[test]
+    fn bi_hash_maps() {
+        let mut map = BiHashMap::new();
+        let mut strings = 0;
+        for i in 0..100 {
+            let left = format!("left-{}", i);
+            let right = format!("right-{}", i);
+            strings += left.capacity() + right.capacity();
+            map.insert(left, right);
+        }
+
+        // One copy of each string, plus the two tables of `Rc` pairs
+        let rc_size = 2 * size_of::<usize>() + size_of::<String>();
+        assert_eq!(
+            map.deep_size_of(),
+            size_of::<BiHashMap<String, String>>()
+                + strings
+                + 100 * 2 * rc_size
+                + 2 * map.capacity() * (2 * size_of::<usize>() + 1)
+        );
+    }
+
+    #[test]
+    fn bi_btree_maps() {
+        let map: BiBTreeMap<u32, String> = (0..60).map(|i| (i, i.to_string())).collect();
+        let strings: usize = map.right_values().map(|s| s.capacity()).sum();
+
+        let pair_size = 2 * size_of::<usize>()
+            + size_of::<u32>()
+            + 2 * size_of::<usize>()
+            + size_of::<String>();
+        let entry_size = 2 * size_of::<usize>();
+        assert_eq!(
+            map.deep_size_of(),
+            size_of::<BiBTreeMap<u32, String>>()
+                + strings
+                + 60 * pair_size
+                + 2 * (60 * entry_size + (60 / 6) * entry_size * 11)
+        );
+    }
+}
+
+#[cfg(feature = "hashlink")]
+mod hashlink_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use hashlink::{LinkedHashMap, LruCache};
+    use std::collections::HashMap;
+
+    #[test]
+    fn linked_hash_maps() {
+        let mut linked = LinkedHashMap::new();
+        let mut plain = HashMap::new();
+        for i in 0..100u64 {
+            linked.insert(i, i * 2);
+            plain.insert(i, i * 2);
+        }
+        assert_eq!(linked.capacity(), plain.capacity());
+
+        // The table holds pointers to boxed nodes, which add two links to
+        // each entry, and there is one extra guard node
+        let node_size = size_of::<(u64, u64)>() + 2 * size_of::<usize>();
+        let linked_size = linked.deep_size_of() - size_of::<LinkedHashMap<u64, u64>>();
+        let plain_size = plain.deep_size_of() - size_of::<HashMap<u64, u64>>();
+        assert_eq!(
+            linked_size,
+            plain_size + linked.capacity() * size_of::<usize>()
+                - linked.capacity() * size_of::<(u64, u64)>()
+                + 101 * node_size
+        );
+    }
+
+    #[test]
+    fn lru_caches() {
+        let mut cache = LruCache::new(10);
+        for i in 0..50u32 {
+            cache.insert(i, vec![0u8; 100]);
+        }
+        assert_eq!(cache.len(), 10);
+
+        let node_size = size_of::<(u32, Vec<u8>)>() + 2 * size_of::<usize>();
+        assert_eq!(
+            cache.deep_size_of(),
+            size_of::<LruCache<u32, Vec<u8>>>()
+                + 10 * 100
+                + 14 * (size_of::<usize>() + 1)
+                + 11 * node_size
+        );
+    }
+}
+
+#[cfg(feature = "bumpalo")]
+mod bumpalo_tests {
+    use crate::DeepSizeOf;
+    use bumpalo::collections::{String, Vec};
+    use bumpalo::Bump;
+    use core::mem::size_of;
+
+    #[test]
+    fn bump_arenas() {
+        let bump = Bump::new();
+        let mut slices = alloc::vec::Vec::new();
+        for _ in 0..64 {
+            slices.push(&*bump.alloc_slice_fill_copy(64 * 1024, 0u8));
+        }
+
+        // The arena holds all of the data, while the references are cheap
+        let size = bump.deep_size_of();
+        assert!(size >= 4 * 1024 * 1024);
+        assert!(size <= 8 * 1024 * 1024 + size_of::<Bump>());
+        assert_eq!(
+            size,
+            size_of::<Bump>() + bump.allocated_bytes_including_metadata()
+        );
+        assert_eq!(
+            slices.deep_size_of(),
+            size_of::<alloc::vec::Vec<&[u8]>>() + slices.capacity() * size_of::<&[u8]>()
+        );
+    }
+
+    #[test]
+    fn bump_collections() {
+        let bump = Bump::new();
+        let mut numbers = Vec::new_in(&bump);
+        numbers.extend(0..1000u32);
+        assert_eq!(numbers.deep_size_of(), size_of::<Vec<u32>>());
+
+        let string = String::from_str_in("stored in the arena", &bump);
+        assert_eq!(string.deep_size_of(), size_of::<String>());
+
+        // Heap allocations owned by the elements are still counted
+        let mut boxes = Vec::new_in(&bump);
+        boxes.push(alloc::boxed::Box::new(0u64));
+        assert_eq!(
+            boxes.deep_size_of(),
+            size_of::<Vec<alloc::boxed::Box<u64>>>() + size_of::<u64>()
+        );
+    }
+}
+
+#[cfg(feature = "typed-arena")]
+mod typed_arena_tests {
+    use crate::DeepSizeOf;
+    use alloc::string::{String, ToString};
+    use core::mem::size_of;
+    use typed_arena::Arena;
+
+    #[test]
+    fn arenas() {
+        let empty: Arena<u64> = Arena::new();
+        assert_eq!(empty.deep_size_of(), size_of::<Arena<u64>>() + 1024);
+
+        let mut arena = Arena::new();
+        for i in 0..3000 {
+            arena.alloc(i.to_string());
+        }
+
+        // Chunks of 42, 84, 168, 336, 672, 1344, and 2688 `String`s
+        let capacity = 42 + 84 + 168 + 336 + 672 + 1344 + 2688;
+        assert_eq!(
+            arena.deep_size_of(),
+            size_of::<Arena<String>>()
+                + capacity * size_of::<String>()
+                + 6 * size_of::<alloc::vec::Vec<String>>()
+        );
+
+        // The strings' own allocations can be measured separately
+        let strings: usize = arena
+            .iter_mut()
+            .map(|string| string.deep_size_of_children(&mut crate::Context::new()))
+            .sum();
+        assert!(strings >= 3000);
+    }
+}
+
+#[cfg(feature = "memmap2")]
+mod memmap2_tests {
+    use crate::{Context, DeepSizeOf};
+    use core::mem::size_of;
+    use memmap2::{MmapMut, MmapOptions};
+
+    #[test]
+    fn mapped_regions() {
+        let mut map = MmapOptions::new().len(1 << 20).map_anon().unwrap();
+        map[..5].copy_from_slice(b"hello");
+        assert_eq!(map.deep_size_of(), size_of::<MmapMut>() + (1 << 20));
+
+        let map = map.make_read_only().unwrap();
+        let mut context = Context::new();
+        context.set_count_mapped_memory(false);
+        assert_eq!(
+            map.deep_size_of_with_context(&mut context),
+            size_of::<memmap2::Mmap>()
+        );
+    }
+}
+
+#[cfg(feature = "tinyvec")]
+mod tinyvec_tests {
+    use crate::DeepSizeOf;
+    use alloc::string::String;
+    use core::mem::size_of;
+    use tinyvec::{array_vec, TinyVec};
+
+    #[test]
+    fn tiny_vecs() {
+        let mut vec: TinyVec<[u32; 4]> = TinyVec::new();
+        vec.extend(0..4);
+        assert!(vec.is_inline());
+        assert_eq!(vec.deep_size_of(), size_of::<TinyVec<[u32; 4]>>());
+
+        // Moving to the heap adds the capacity of the new `Vec`
+        vec.push(4);
+        assert!(vec.is_heap());
+        assert_eq!(
+            vec.deep_size_of(),
+            size_of::<TinyVec<[u32; 4]>>() + vec.capacity() * size_of::<u32>()
+        );
+
+        let mut strings: TinyVec<[String; 2]> = TinyVec::new();
+        strings.push(String::from("inline"));
+        assert_eq!(
+            strings.deep_size_of(),
+            size_of::<TinyVec<[String; 2]>>() + strings[0].capacity()
+        );
+
+        let array = array_vec!([String; 2] => String::from("element"));
+        assert_eq!(
+            array.deep_size_of(),
+            size_of::<tinyvec::ArrayVec<[String; 2]>>() + array[0].capacity()
+        );
+    }
+}
+
+#[cfg(feature = "flume")]
+mod flume_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use flume::{Receiver, Sender};
+
+    #[derive(DeepSizeOf)]
+    struct Actor {
+        inbox: Receiver<u64>,
+        outbox: Sender<u64>,
+        name: String,
+    }
+
+    #[test]
+    fn actor_channels() {
+        let (inbox_sender, inbox) = flume::bounded(16);
+        let (outbox, _outbox_receiver) = flume::unbounded();
+        let actor = Actor {
+            inbox,
+            outbox,
+            name: String::from("worker"),
+        };
+        let name = actor.name.capacity();
+        assert_eq!(actor.deep_size_of(), size_of::<Actor>() + name);
+
+        for i in 0..10 {
+            inbox_sender.send(i).unwrap();
+        }
+        assert_eq!(actor.deep_size_of(), size_of::<Actor>() + name + 16 * 8);
+
+        // The sender doesn't count the queued messages
+        assert_eq!(inbox_sender.deep_size_of(), size_of::<Sender<u64>>());
+    }
+}
+
+#[cfg(feature = "im")]
+mod im_tests {
+    use crate::{Context, DeepSizeOf};
+    use core::mem::size_of;
+    use im::Vector;
+
+    #[test]
+    fn persistent_vectors() {
+        let small: Vector<u64> = (0..2).collect();
+        assert!(small.is_inline());
+        assert_eq!(small.deep_size_of(), size_of::<Vector<u64>>());
+
+        let large: Vector<u64> = (0..1000).collect();
+        let chunk_size = 64 * 8 + 4 * size_of::<usize>();
+        let leaves = large.leaves().count();
+        assert!(leaves >= 1000 / 64);
+        assert_eq!(
+            large.deep_size_of(),
+            size_of::<Vector<u64>>() + leaves * chunk_size
+        );
+
+        // Clones share their chunks, so only the modified chunk is new
+        let mut modified = large.clone();
+        modified.set(500, 0);
+        let mut context = Context::new();
+        let original_size = large.deep_size_of_with_context(&mut context);
+        let modified_size = modified.deep_size_of_with_context(&mut context);
+        assert_eq!(
+            original_size,
+            size_of::<Vector<u64>>() + leaves * chunk_size
+        );
+        assert_eq!(modified_size, size_of::<Vector<u64>>() + chunk_size);
+    }
+}
+
+#[cfg(all(feature = "glam", feature = "euclid"))]
+mod math_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+
+    // A unit type that doesn't implement `DeepSizeOf`
+    struct ScreenSpace;
+
+    #[derive(DeepSizeOf)]
+    struct Transform {
+        translation: glam::Vec3,
+        rotation: glam::Quat,
+        scale: glam::Vec3,
+        matrix: glam::Mat4,
+        bounds: euclid::Rect<f32, ScreenSpace>,
+        to_screen: euclid::Transform2D<f32, euclid::UnknownUnit, ScreenSpace>,
+        children: Vec<glam::Affine3A>,
+    }
+
+    #[test]
+    fn transforms() {
+        let transform = Transform {
+            translation: glam::Vec3::ZERO,
+            rotation: glam::Quat::IDENTITY,
+            scale: glam::Vec3::ONE,
+            matrix: glam::Mat4::IDENTITY,
+            bounds: euclid::Rect::zero(),
+            to_screen: euclid::Transform2D::identity(),
+            children: vec![glam::Affine3A::IDENTITY; 4],
+        };
+        assert_eq!(
+            transform.deep_size_of(),
+            size_of::<Transform>() + transform.children.capacity() * size_of::<glam::Affine3A>()
+        );
+    }
+}
+
+#[cfg(feature = "image")]
+mod image_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use image::{DynamicImage, Rgb32FImage, RgbaImage};
+
+    #[test]
+    fn image_buffers() {
+        let image = RgbaImage::new(1024, 1024);
+        assert_eq!(
+            image.deep_size_of(),
+            size_of::<RgbaImage>() + 4 * 1024 * 1024
+        );
+
+        let image = Rgb32FImage::new(16, 16);
+        assert_eq!(
+            image.deep_size_of(),
+            size_of::<Rgb32FImage>() + 3 * 4 * 16 * 16
+        );
+    }
+
+    #[test]
+    fn dynamic_images() {
+        let image = DynamicImage::new_rgba8(1024, 1024);
+        assert_eq!(
+            image.deep_size_of(),
+            size_of::<DynamicImage>() + 4 * 1024 * 1024
+        );
+
+        let image = DynamicImage::new_luma16(100, 100);
+        assert_eq!(
+            image.deep_size_of(),
+            size_of::<DynamicImage>() + 2 * 100 * 100
+        );
+    }
+}
+
+#[cfg(feature = "bytes")]
+mod bytes_tests {
+    use crate::DeepSizeOf;
+    use bytes::{Bytes, BytesMut};
+    use core::mem::size_of;
+
+    #[test]
+    fn shared_bytes() {
+        let bytes = Bytes::from(vec![0u8; 100]);
+        let clones = vec![bytes.clone(), bytes.clone(), bytes.slice(50..)];
+        assert_eq!(bytes.deep_size_of(), size_of::<Bytes>() + 100);
+        assert_eq!(
+            clones.deep_size_of(),
+            size_of::<Vec<Bytes>>() + clones.capacity() * size_of::<Bytes>() + 100
+        );
+
+        // Slices measured before the whole buffer don't hide the rest of it
+        let views = (
+            bytes.slice(0..0),
+            bytes.slice(..10),
+            bytes.slice(90..),
+            bytes,
+        );
+        assert_eq!(
+            views.deep_size_of(),
+            size_of::<(Bytes, Bytes, Bytes, Bytes)>() + 100
+        );
+
+        let buffer = BytesMut::with_capacity(64);
+        assert_eq!(
+            buffer.deep_size_of(),
+            size_of::<BytesMut>() + buffer.capacity()
+        );
+    }
+}
+
+#[cfg(feature = "prost")]
+mod prost_tests {
+    use crate::{deep_size_fields, Context, DeepSizeOf};
+    use core::mem::size_of;
+    use prost_types::{Any, Timestamp};
+
+    // Mimics the output of `prost-build` for a message with a `oneof`;
+    // `prost::alloc` is a re-export of `alloc`
+    struct Event {
+        id: u64,
+        name: alloc::string::String,
+        created: Option<Timestamp>,
+        payload: bytes::Bytes,
+        details: alloc::vec::Vec<Any>,
+        source: Option<event::Source>,
+    }
+
+    mod event {
+        pub enum Source {
+            Host(alloc::string::String),
+            Pid(u32),
+        }
+    }
+
+    deep_size_fields!(Event {
+        id,
+        name,
+        created,
+        payload,
+        details,
+        source
+    });
+
+    impl DeepSizeOf for event::Source {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            match self {
+                event::Source::Host(host) => host.deep_size_of_children(context),
+                event::Source::Pid(pid) => pid.deep_size_of_children(context),
+            }
+        }
+    }
+
+    #[test]
+    fn generated_messages() {
+        let event = Event {
+            id: 1,
+            name: String::from("startup"),
+            created: Some(Timestamp::default()),
+            payload: bytes::Bytes::from(vec![0u8; 256]),
+            details: vec![Any {
+                type_url: String::from("type.googleapis.com/Detail"),
+                value: vec![0u8; 32],
+            }],
+            source: Some(event::Source::Host(String::from("localhost"))),
+        };
+        assert_eq!(
+            event.deep_size_of(),
+            size_of::<Event>()
+                + event.name.capacity()
+                + 256
+                + event.details.capacity() * size_of::<Any>()
+                + event.details[0].type_url.capacity()
+                + event.details[0].value.capacity()
+                + 9
+        );
+        assert_eq!(
+            event::Source::Pid(1).deep_size_of(),
+            size_of::<event::Source>()
+        );
+    }
+
+    #[test]
+    fn struct_values() {
+        use prost_types::{value::Kind, ListValue, Value};
+
+        let string = Value {
+            kind: Some(Kind::StringValue(String::from("value"))),
+        };
+        let list = Value {
+            kind: Some(Kind::ListValue(ListValue {
+                values: vec![string],
+            })),
+        };
+        assert_eq!(list.deep_size_of(), 2 * size_of::<Value>() + 5);
+    }
+}
+
+#[cfg(feature = "serde_yaml")]
+mod serde_yaml_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use serde_yaml::value::{Tag, TaggedValue};
+    use serde_yaml::{Mapping, Value};
+
+    #[test]
+    fn nested_documents() {
+        let mut value = Value::Null;
+        for _ in 0..100 {
+            value = Value::Sequence(vec![value]);
+        }
+        assert_eq!(value.deep_size_of(), 101 * size_of::<Value>());
+
+        let document: Value = serde_yaml::from_str(
+            "
+            server:
+              hosts: [alpha, beta]
+              limits:
+                connections: 100
+                timeout: 2.5
+            ",
+        )
+        .unwrap();
+        let server = &document["server"];
+        let Value::Mapping(limits) = &server["limits"] else {
+            panic!("limits should be a mapping");
+        };
+        let limits_size = limits.capacity()
+            * (size_of::<(usize, Value, Value)>() + size_of::<usize>())
+            + "connections".len()
+            + "timeout".len();
+        assert_eq!(
+            server["limits"].deep_size_of(),
+            size_of::<Value>() + limits_size
+        );
+        assert!(document.deep_size_of() > size_of::<Value>() + limits_size + 5 + 4);
+    }
+
+    #[test]
+    fn large_strings() {
+        let text = "scalar ".repeat(100_000);
+        let mut mapping = Mapping::new();
+        mapping.insert(Value::from("text"), Value::from(text.clone()));
+        let value = Value::Mapping(mapping);
+        let Value::Mapping(mapping) = &value else {
+            unreachable!()
+        };
+        assert_eq!(
+            value.deep_size_of(),
+            size_of::<Value>()
+                + mapping.capacity() * (size_of::<(usize, Value, Value)>() + size_of::<usize>())
+                + 4
+                + text.len()
+        );
+    }
+
+    #[test]
+    fn tagged_values() {
+        let value = Value::Tagged(Box::new(TaggedValue {
+            tag: Tag::new("Thing"),
+            value: Value::from("contents"),
+        }));
+        assert_eq!(
+            value.deep_size_of(),
+            size_of::<Value>() + size_of::<TaggedValue>() + "!Thing".len() + 8
+        );
+    }
+}
+
+#[cfg(any(feature = "toml", feature = "toml_edit"))]
+const CARGO_TOML: &str = r#"
+[package]
+name = "example"
+version = "0.1.0"
+edition = "2021"
+authors = ["Someone <someone@example.com>"]
+description = "An example package"
+license = "MIT OR Apache-2.0"
+
+# Runtime dependencies
+[dependencies]
+serde = { version = "1", features = ["derive"] }
+tokio = { version = "1", features = ["rt", "macros", "net"] }
+log = "0.4"
+
+[dev-dependencies]
+criterion = "0.5"
+
+[features]
+default = ["std"]
+std = []
+
+[[bench]]
+name = "throughput"
+harness = false
+
+[profile.release]
+lto = true
+codegen-units = 1
+"#;
+
+#[cfg(feature = "toml")]
+mod toml_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use toml::{Table, Value};
+
+    #[test]
+    fn values() {
+        let value = Value::String(String::from("a string"));
+        assert_eq!(value.deep_size_of(), size_of::<Value>() + 8);
+
+        let mut table = Table::new();
+        table.insert(String::from("key"), Value::Integer(1));
+        assert_eq!(
+            table.deep_size_of(),
+            size_of::<Table>() + size_of::<String>() + size_of::<Value>() + 3
+        );
+    }
+
+    #[test]
+    fn documents() {
+        let document: Table = super::CARGO_TOML.parse().unwrap();
+        let size = document.deep_size_of();
+        assert!(size > size_of::<Table>() + 12 * (size_of::<String>() + size_of::<Value>()));
+        assert!(size > document["package"].deep_size_of() + document["features"].deep_size_of());
+    }
+}
+
+#[cfg(feature = "toml_edit")]
+mod toml_edit_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use toml_edit::{DocumentMut, Key, Value};
+
+    #[test]
+    fn decor() {
+        assert_eq!(Key::new("name").deep_size_of(), size_of::<Key>() + 4);
+
+        let mut value = Value::from("x");
+        value.decor_mut().set_prefix(" ");
+        value.decor_mut().set_suffix(" # a comment");
+        assert_eq!(value.deep_size_of(), size_of::<Value>() + 1 + 1 + 12);
+    }
+
+    #[test]
+    fn documents() {
+        let document: DocumentMut = super::CARGO_TOML.parse().unwrap();
+        let size = document.deep_size_of();
+        // The document keeps its keys, values, whitespace and comments
+        assert!(size > size_of::<DocumentMut>() + super::CARGO_TOML.len() / 2);
+
+        #[cfg(feature = "toml")]
+        {
+            let table: toml::Table = super::CARGO_TOML.parse().unwrap();
+            assert!(size > table.deep_size_of());
+        }
+    }
+}
+
+#[cfg(all(feature = "bit-set", feature = "bit-vec"))]
+mod bit_set_tests {
+    use crate::DeepSizeOf;
+    use bit_set::BitSet;
+    use bit_vec::BitVec;
+    use core::mem::size_of;
+
+    #[test]
+    fn bit_vecs() {
+        let bits = BitVec::from_elem(1000, false);
+        assert_eq!(
+            bits.deep_size_of(),
+            size_of::<BitVec>() + bits.capacity() / 8
+        );
+        assert!(bits.capacity() >= 1000);
+    }
+
+    #[test]
+    fn sparse_sets() {
+        let mut sparse = BitSet::new();
+        sparse.insert(1_000_000);
+        let mut dense = BitSet::new();
+        for i in 0..1_000 {
+            dense.insert(i);
+        }
+
+        // A single large value costs a bit for every smaller value
+        assert!(sparse.deep_size_of() >= size_of::<BitSet>() + 1_000_000 / 8);
+        assert!(dense.deep_size_of() < size_of::<BitSet>() + 1_000);
+        assert_eq!(
+            sparse.deep_size_of(),
+            size_of::<BitSet>()
+                + sparse
+                    .get_ref()
+                    .deep_size_of_children(&mut crate::Context::new())
+        );
+    }
+}
+
+#[cfg(feature = "rangemap")]
+mod rangemap_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use core::ops::Range;
+    use rangemap::{RangeInclusiveMap, RangeInclusiveSet, RangeMap, RangeSet};
+
+    fn range_map(len: u32) -> RangeMap<u32, String> {
+        let mut map = RangeMap::new();
+        for i in 0..len {
+            map.insert(i * 10..i * 10 + 5, format!("range {:05}", i));
+        }
+        map
+    }
+
+    #[test]
+    fn small_maps() {
+        let map = range_map(3);
+        let entry_size = size_of::<Range<u32>>() + size_of::<String>();
+        let strings: usize = map.iter().map(|(_, value)| value.capacity()).sum();
+        assert_eq!(
+            map.deep_size_of(),
+            size_of::<RangeMap<u32, String>>() + 3 * entry_size + strings
+        );
+
+        let mut set = RangeInclusiveSet::new();
+        set.insert(0u8..=10);
+        set.insert(20..=30);
+        assert_eq!(
+            set.deep_size_of(),
+            size_of::<RangeInclusiveSet<u8>>() + 2 * size_of::<core::ops::RangeInclusive<u8>>()
+        );
+    }
+
+    #[test]
+    fn scaling() {
+        let small = range_map(2_000).deep_size_of();
+        let large = range_map(4_000).deep_size_of();
+        assert!(small > 2_000 * (size_of::<Range<u32>>() + size_of::<String>() + 11));
+        assert!(large > 2 * small - 1_000 && large < 2 * small + 1_000);
+
+        let mut map = RangeInclusiveMap::new();
+        let mut set = RangeSet::new();
+        for i in 0..2_000u32 {
+            map.insert(i * 10..=i * 10 + 5, format!("range {:05}", i));
+            set.insert(i * 10..i * 10 + 5);
+        }
+        assert!(map.deep_size_of() > set.deep_size_of() + 2_000 * (size_of::<String>() + 11));
+    }
+}
+
+#[cfg(feature = "vec_map")]
+mod vec_map_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use vec_map::VecMap;
+
+    #[test]
+    fn sparse_keys() {
+        let mut map = VecMap::new();
+        map.insert(10_000, String::from("value"));
+        assert!(map.capacity() > 10_000);
+        assert_eq!(
+            map.deep_size_of(),
+            size_of::<VecMap<String>>() + map.capacity() * size_of::<Option<String>>() + 5
+        );
+
+        let mut dense = VecMap::new();
+        dense.insert(0, String::from("value"));
+        assert!(dense.deep_size_of() < map.deep_size_of() / 1_000);
+    }
+}
+
+#[cfg(all(feature = "zerovec", feature = "tinystr"))]
+mod zerovec_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use tinystr::{tinystr, TinyAsciiStr};
+    use zerovec::{VarZeroVec, ZeroVec};
+
+    #[test]
+    fn zero_vecs() {
+        let borrowed = ZeroVec::<u8>::new_borrowed(&[1, 2, 3, 4]);
+        assert_eq!(borrowed.deep_size_of(), size_of::<ZeroVec<u8>>());
+
+        let owned: ZeroVec<u32> = ZeroVec::alloc_from_slice(&[1, 2, 3, 4]);
+        assert!(owned.is_owned());
+        assert_eq!(
+            owned.deep_size_of(),
+            size_of::<ZeroVec<u32>>() + owned.owned_capacity().unwrap().get() * 4
+        );
+    }
+
+    #[test]
+    fn var_zero_vecs() {
+        let strings = ["alpha", "beta", "gamma"];
+        let owned: VarZeroVec<str> = VarZeroVec::from(&strings[..]);
+        assert!(owned.is_owned());
+        assert_eq!(
+            owned.deep_size_of(),
+            size_of::<VarZeroVec<str>>() + owned.as_bytes().len()
+        );
+        assert!(owned.as_bytes().len() >= 14);
+
+        let borrowed: VarZeroVec<str> = VarZeroVec::from(owned.as_slice());
+        assert!(!borrowed.is_owned());
+        assert_eq!(borrowed.deep_size_of(), size_of::<VarZeroVec<str>>());
+    }
+
+    #[test]
+    fn tiny_strings() {
+        let string: TinyAsciiStr<8> = tinystr!(8, "en-US");
+        assert_eq!(string.deep_size_of(), 8);
+    }
+}
+
+#[cfg(feature = "ustr")]
+mod ustr_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use ustr::{ustr, Ustr};
+
+    #[test]
+    fn interned_strings() {
+        let copies = vec![ustr("an interned string"); 1_000_000];
+        assert_eq!(
+            copies.deep_size_of(),
+            size_of::<Vec<Ustr>>() + copies.capacity() * size_of::<Ustr>()
+        );
+        assert!(crate::ustr_cache_size() >= "an interned string".len());
+    }
+}
+
+#[cfg(feature = "bytestring")]
+mod bytestring_tests {
+    use crate::DeepSizeOf;
+    use bytestring::ByteString;
+    use core::mem::size_of;
+
+    #[test]
+    fn shared_strings() {
+        let route = ByteString::from(String::from("/api/v1/users/{id}/settings"));
+        let clone = route.clone();
+
+        assert_eq!(route.deep_size_of(), size_of::<ByteString>() + 27);
+        assert_eq!(clone.deep_size_of(), size_of::<ByteString>() + 27);
+        assert_eq!(
+            (route, clone).deep_size_of(),
+            2 * size_of::<ByteString>() + 27
+        );
+    }
+}
+
+#[cfg(feature = "smallbox")]
+mod smallbox_tests {
+    use crate::DeepSizeOf;
+    use core::any::Any;
+    use core::mem::size_of;
+    use smallbox::space::{S1, S4};
+    use smallbox::{smallbox, SmallBox};
+
+    #[test]
+    fn inline_and_heap() {
+        let inline: SmallBox<String, S4> = SmallBox::new(String::from("inline"));
+        assert!(!inline.is_heap());
+        assert_eq!(inline.deep_size_of(), size_of::<SmallBox<String, S4>>() + 6);
+
+        let heap: SmallBox<[u64; 8], S1> = SmallBox::new([0; 8]);
+        assert!(heap.is_heap());
+        assert_eq!(
+            heap.deep_size_of(),
+            size_of::<SmallBox<[u64; 8], S1>>() + 64
+        );
+    }
+
+    #[test]
+    fn trait_objects() {
+        let inline: SmallBox<dyn Any, S4> = smallbox!(5u32);
+        assert!(!inline.is_heap());
+        assert_eq!(inline.deep_size_of(), size_of::<SmallBox<dyn Any, S4>>());
+
+        let heap: SmallBox<dyn Any, S4> = smallbox!([0u8; 100]);
+        assert!(heap.is_heap());
+        assert_eq!(
+            heap.deep_size_of(),
+            size_of::<SmallBox<dyn Any, S4>>() + 100
+        );
+
+        let slice: SmallBox<[String], S1> = smallbox!([String::from("a"), String::from("bc")]);
+        assert!(slice.is_heap());
+        assert_eq!(
+            slice.deep_size_of(),
+            size_of::<SmallBox<[String], S1>>() + 2 * size_of::<String>() + 3
+        );
+    }
+}
+
+#[cfg(feature = "sharded-slab")]
+mod sharded_slab_tests {
+    use crate::{sharded_slab_size, Context, DeepSizeOf};
+    use core::mem::size_of;
+    use sharded_slab::{Config, DefaultConfig, Slab};
+    use std::sync::Arc;
+
+    #[test]
+    fn fixed_overhead() {
+        let slab: Slab<String> = Slab::new();
+        let overhead = size_of::<Slab<String>>() + DefaultConfig::MAX_THREADS * size_of::<usize>();
+        assert_eq!(slab.deep_size_of(), overhead);
+    }
+
+    #[test]
+    fn threaded_inserts() {
+        let slab: Arc<Slab<String>> = Arc::new(Slab::new());
+        let threads: Vec<_> = (0..4)
+            .map(|thread| {
+                let slab = Arc::clone(&slab);
+                std::thread::spawn(move || {
+                    for i in 0..100 {
+                        slab.insert(format!("thread {} entry {:03}", thread, i))
+                            .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        let mut slab = Arc::try_unwrap(slab).unwrap();
+        let entries: usize = slab.unique_iter().map(String::capacity).sum();
+        let slot_size = size_of::<Option<String>>() + 2 * size_of::<usize>();
+        let size = sharded_slab_size(&mut slab, &mut Context::new());
+
+        let overhead = slab.deep_size_of();
+        assert!(size >= overhead + 400 * slot_size + entries);
+        // Each thread needs pages with 32 + 64 + 128 slots for 100 entries
+        assert!(size <= overhead + 4 * 224 * slot_size + entries);
+    }
+}
+
+#[cfg(feature = "aho-corasick")]
+mod aho_corasick_tests {
+    use crate::DeepSizeOf;
+    use aho_corasick::{dfa, nfa, AhoCorasick};
+    use core::mem::size_of;
+
+    fn patterns(len: usize) -> Vec<String> {
+        (0..len).map(|i| format!("keyword{}", i)).collect()
+    }
+
+    #[test]
+    fn matchers() {
+        let small = AhoCorasick::new(patterns(10)).unwrap();
+        let large = AhoCorasick::new(patterns(100_000)).unwrap();
+        assert_eq!(
+            small.deep_size_of(),
+            size_of::<AhoCorasick>() + small.memory_usage()
+        );
+        assert!(large.deep_size_of() > 100 * small.deep_size_of());
+        assert!(large.deep_size_of() > 100_000 * "keyword".len());
+    }
+
+    #[test]
+    fn automata() {
+        let nfa = nfa::noncontiguous::NFA::new(patterns(100)).unwrap();
+        let dfa = dfa::DFA::new(patterns(100)).unwrap();
+        assert!(nfa.deep_size_of() > size_of::<nfa::noncontiguous::NFA>() + 100);
+        assert!(dfa.deep_size_of() > size_of::<dfa::DFA>() + 100);
+    }
+}
+
+#[cfg(feature = "smallstr")]
+mod smallstr_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use smallstr::SmallString;
+
+    type Token = SmallString<[u8; 16]>;
+
+    #[test]
+    fn inline_boundary() {
+        let inline = Token::from("0123456789abcdef");
+        assert!(!inline.spilled());
+        assert_eq!(inline.deep_size_of(), size_of::<Token>());
+
+        let spilled = Token::from("0123456789abcdefg");
+        assert!(spilled.spilled());
+        assert_eq!(
+            spilled.deep_size_of(),
+            size_of::<Token>() + spilled.capacity()
+        );
+        assert!(spilled.capacity() >= 17);
+    }
+}
+
+#[cfg(feature = "string_cache")]
+mod string_cache_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use string_cache::DefaultAtom;
+
+    #[test]
+    fn atom_kinds() {
+        let inline = DefaultAtom::from("short");
+        assert!(inline.is_inline());
+        assert_eq!(inline.deep_size_of(), size_of::<DefaultAtom>());
+
+        // The empty string is the only atom in the default static set
+        let static_atom = DefaultAtom::pack_static(0);
+        assert!(static_atom.is_static());
+        assert_eq!(static_atom.deep_size_of(), size_of::<DefaultAtom>());
+
+        let dynamic = DefaultAtom::from("a dynamically interned string");
+        assert!(dynamic.is_dynamic());
+        assert_eq!(dynamic.deep_size_of(), size_of::<DefaultAtom>() + 29);
+    }
+
+    #[test]
+    fn shared_dynamic_atoms() {
+        let atom = DefaultAtom::from("a dynamically interned string");
+        let copies = vec![atom; 1000];
+        assert_eq!(
+            copies.deep_size_of(),
+            size_of::<Vec<DefaultAtom>>() + copies.capacity() * size_of::<DefaultAtom>() + 29
+        );
+    }
+}
+
+#[cfg(feature = "internment")]
+mod internment_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use internment::{ArcIntern, Intern};
+
+    #[test]
+    fn interned_values() {
+        let interned = Intern::new(String::from("interned"));
+        assert_eq!(interned.deep_size_of(), size_of::<Intern<String>>());
+
+        let shared = ArcIntern::new(String::from("shared"));
+        let holders = vec![shared.clone(); 100];
+        assert_eq!(
+            shared.deep_size_of(),
+            size_of::<ArcIntern<String>>() + size_of::<usize>() + size_of::<String>() + 6
+        );
+        assert_eq!(
+            holders.deep_size_of(),
+            size_of::<Vec<ArcIntern<String>>>()
+                + holders.capacity() * size_of::<ArcIntern<String>>()
+                + size_of::<usize>()
+                + size_of::<String>()
+                + 6
+        );
+    }
+}
+
+#[cfg(feature = "futures")]
+mod futures_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use futures::channel::{mpsc, oneshot};
+
+    #[test]
+    fn channel_handles() {
+        #[derive(DeepSizeOf)]
+        struct Connection {
+            name: String,
+            outgoing: mpsc::Sender<Vec<u8>>,
+            shutdown: oneshot::Receiver<()>,
+        }
+
+        let (outgoing, _receiver) = mpsc::channel(16);
+        let (_sender, shutdown) = oneshot::channel();
+        let connection = Connection {
+            name: String::from("peer"),
+            outgoing,
+            shutdown,
+        };
+        assert_eq!(connection.deep_size_of(), size_of::<Connection>() + 4);
+    }
+}
+
+#[cfg(feature = "ijson")]
+mod ijson_tests {
+    use crate::DeepSizeOf;
+    use core::convert::TryFrom;
+    use core::mem::size_of;
+    use ijson::{IArray, INumber, IObject, IString, IValue};
+
+    /// A lower bound for the heap size of a `serde_json::Value`, ignoring
+    /// the internal nodes of its maps.
+    fn json_value_size(value: &serde_json::Value) -> usize {
+        use serde_json::Value;
+        match value {
+            Value::Null | Value::Bool(_) | Value::Number(_) => 0,
+            Value::String(s) => s.capacity(),
+            Value::Array(arr) => {
+                arr.capacity() * size_of::<Value>() + arr.iter().map(json_value_size).sum::<usize>()
+            }
+            Value::Object(map) => map
+                .iter()
+                .map(|(key, value)| {
+                    size_of::<(String, Value)>() + key.capacity() + json_value_size(value)
+                })
+                .sum(),
+        }
+    }
+
+    #[test]
+    fn inline_values() {
+        assert_eq!(INumber::from(1234).deep_size_of(), size_of::<INumber>());
+        assert_eq!(
+            INumber::try_from(1.5).unwrap().deep_size_of(),
+            size_of::<INumber>()
+        );
+        assert_eq!(
+            INumber::from(u64::MAX).deep_size_of(),
+            size_of::<INumber>() + 8
+        );
+        assert_eq!(
+            INumber::try_from(0.1).unwrap().deep_size_of(),
+            size_of::<INumber>() + 8
+        );
+
+        assert_eq!(
+            IString::intern("short").deep_size_of(),
+            size_of::<IString>()
+        );
+        assert_eq!(IArray::new().deep_size_of(), size_of::<IArray>());
+        assert_eq!(IObject::new().deep_size_of(), size_of::<IObject>());
+    }
+
+    #[test]
+    fn interned_strings() {
+        let arr: IArray = (0..16)
+            .map(|_| IString::intern("a longer string"))
+            .collect();
+        assert_eq!(
+            arr.deep_size_of(),
+            size_of::<IArray>()
+                + 2 * size_of::<usize>()
+                + arr.capacity() * size_of::<IValue>()
+                + size_of::<usize>()
+                + 8
+                + 15
+        );
+    }
+
+    #[test]
+    fn smaller_than_serde_json() {
+        let mut document = String::from("[");
+        for i in 0..100 {
+            if i > 0 {
+                document.push(',');
+            }
+            document.push_str(&format!(
+                r#"{{"id": {}, "name": "user{}", "active": true, "score": {}.5, "tags": ["a", "b"]}}"#,
+                i, i, i
+            ));
+        }
+        document.push(']');
+
+        let json: serde_json::Value = serde_json::from_str(&document).unwrap();
+        let ivalue: IValue = serde_json::from_str(&document).unwrap();
+        assert_eq!(ivalue.len(), Some(100));
+
+        let json_size = size_of::<serde_json::Value>() + json_value_size(&json);
+        assert!(ivalue.deep_size_of() < json_size);
+    }
+}
+
+#[cfg(feature = "simd-json")]
+mod simd_json_tests {
+    use crate::DeepSizeOf;
+    use alloc::borrow::Cow;
+    use core::mem::{size_of, size_of_val};
+    use simd_json::{BorrowedValue, OwnedValue};
+
+    fn document(records: usize) -> Vec<u8> {
+        let mut document = String::from("[");
+        for i in 0..records {
+            if i > 0 {
+                document.push(',');
+            }
+            document.push_str(&format!(
+                r#"{{"id": {}, "name": "user {}", "bio": "line\nbreak"}}"#,
+                i, i
+            ));
+        }
+        document.push(']');
+        document.into_bytes()
+    }
+
+    #[test]
+    fn owned_document() {
+        let mut buffer = br#"{"name": "user"}"#.to_vec();
+        let value = simd_json::to_owned_value(&mut buffer).unwrap();
+        let obj = match &value {
+            OwnedValue::Object(obj) => obj,
+            _ => panic!("expected an object"),
+        };
+        assert_eq!(
+            value.deep_size_of(),
+            size_of::<OwnedValue>()
+                + size_of_val(&**obj)
+                + obj.capacity() * size_of::<(String, OwnedValue)>()
+                + "name".len()
+                + "user".len()
+        );
+
+        let mut buffer = document(1000);
+        let value = simd_json::to_owned_value(&mut buffer).unwrap();
+        let strings: usize = (0..1000)
+            .map(|i| "idnamebio".len() + format!("user {}", i).len() + "line\nbreak".len())
+            .sum();
+        assert!(value.deep_size_of() > 1000 * 3 * size_of::<(String, OwnedValue)>() + strings);
+    }
+
+    #[test]
+    fn borrowed_document() {
+        let mut buffer = document(100);
+        let value = simd_json::to_borrowed_value(&mut buffer).unwrap();
+        let owned = value.clone().into_static();
+
+        // Every string (including the escaped ones) is borrowed from the buffer
+        let borrowed_size = value.deep_size_of();
+        let owned_size = owned.deep_size_of();
+        let strings: usize = (0..100)
+            .map(|i| "idnamebio".len() + format!("user {}", i).len() + "line\nbreak".len())
+            .sum();
+        assert_eq!(owned_size - borrowed_size, strings);
+
+        let escaped = BorrowedValue::String(Cow::Owned(String::from("owned")));
+        assert_eq!(escaped.deep_size_of(), size_of::<BorrowedValue>() + 5);
+    }
+}
+
+#[cfg(feature = "tinyset")]
+mod tinyset_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use tinyset::{Set64, SetU32, SetU64};
+
+    #[test]
+    fn inline_sets() {
+        let small: SetU32 = [1, 2, 3].iter().copied().collect();
+        assert_eq!(small.deep_size_of(), size_of::<SetU32>());
+        let small: SetU64 = [1, 2, 3].iter().copied().collect();
+        assert_eq!(small.deep_size_of(), size_of::<SetU64>());
+        let small: Set64<u16> = [1, 2, 3].iter().copied().collect();
+        assert_eq!(small.deep_size_of(), size_of::<Set64<u16>>());
+        assert_eq!(Set64::<u16>::new().deep_size_of(), size_of::<Set64<u16>>());
+    }
+
+    #[test]
+    fn spilled_sets() {
+        let large: SetU32 = (0..1000).map(|i| i * 1_000_003).collect();
+        assert_eq!(large.deep_size_of(), large.mem_used());
+        assert!(large.deep_size_of() >= size_of::<SetU32>() + 1000 * size_of::<u32>());
+
+        let large: SetU64 = (0..1000).map(|i| i * 1_000_000_007).collect();
+        assert_eq!(large.deep_size_of(), large.mem_used());
+        assert!(large.deep_size_of() >= size_of::<SetU64>() + 1000 * size_of::<u64>());
+
+        let large: Set64<u64> = (0..1000).map(|i| i * 1_000_000_007).collect();
+        assert_eq!(
+            large.deep_size_of(),
+            size_of::<Set64<u64>>() + 1000 * size_of::<u64>()
+        );
+    }
+
+    #[test]
+    fn many_small_sets() {
+        let neighbors: Vec<SetU32> = (0..1_000_000)
+            .map(|i| [i, i + 1].iter().copied().collect())
+            .collect();
+        assert_eq!(
+            neighbors.deep_size_of(),
+            size_of::<Vec<SetU32>>() + neighbors.capacity() * size_of::<SetU32>()
+        );
+    }
+}
+
+#[cfg(feature = "hibitset")]
+mod hibitset_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use hibitset::{AtomicBitSet, BitSet};
+
+    const WORD: usize = size_of::<usize>();
+
+    #[test]
+    fn empty_sets() {
+        assert_eq!(BitSet::new().deep_size_of(), size_of::<BitSet>());
+
+        let bits = BitSet::BITS_PER_USIZE;
+        assert_eq!(
+            AtomicBitSet::new().deep_size_of(),
+            size_of::<AtomicBitSet>() + bits * WORD + bits * bits * 2 * WORD
+        );
+    }
+
+    #[test]
+    fn dense_range() {
+        let bits = BitSet::BITS_PER_USIZE as u32;
+        let mut set = BitSet::new();
+        for i in 0..bits * bits {
+            set.add(i);
+        }
+        // One word in the top two layers, and `bits` words on the bottom
+        assert_eq!(
+            set.deep_size_of(),
+            size_of::<BitSet>() + (2 + bits as usize) * WORD
+        );
+
+        let mut atomic = AtomicBitSet::new();
+        let empty_size = atomic.deep_size_of();
+        for i in 0..bits * bits {
+            atomic.add(i);
+        }
+        assert_eq!(atomic.deep_size_of(), empty_size + bits as usize * WORD);
+    }
+
+    #[test]
+    fn large_index() {
+        let bits = BitSet::BITS_PER_USIZE;
+        let index = (bits * bits * bits * bits - 1) as u32;
+        let mut set = BitSet::new();
+        set.add(index);
+        assert_eq!(
+            set.deep_size_of(),
+            size_of::<BitSet>() + (bits + bits * bits + bits * bits * bits) * WORD
+        );
+    }
+}
+
+#[cfg(feature = "generational-arena")]
+mod generational_arena_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use generational_arena::Arena;
+
+    #[test]
+    fn deleted_entries() {
+        let mut arena = Arena::with_capacity(8);
+        let indices: Vec<_> = (0..8).map(|i| arena.insert(vec![i as u8; 100])).collect();
+        let full_size = arena.deep_size_of();
+        assert!(full_size > size_of::<Arena<Vec<u8>>>() + 8 * 100);
+
+        for &index in &indices[..4] {
+            arena.remove(index);
+        }
+        // The vacant entries are still allocated, but their values are gone
+        assert_eq!(arena.capacity(), 8);
+        assert_eq!(arena.deep_size_of(), full_size - 4 * 100);
+
+        arena.insert(vec![0; 10]);
+        assert_eq!(arena.deep_size_of(), full_size - 4 * 100 + 10);
+    }
+}
+
+#[cfg(feature = "id-arena")]
+mod id_arena_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use id_arena::Arena;
+
+    #[test]
+    fn allocated_values() {
+        let mut arena = Arena::<String>::new();
+        for _ in 0..8 {
+            arena.alloc(String::from("value"));
+        }
+        assert_eq!(
+            arena.deep_size_of(),
+            size_of::<Arena<String>>() + 8 * size_of::<String>() + 8 * 5
+        );
+    }
+}
+
+#[cfg(feature = "weak-table")]
+mod weak_table_tests {
+    use crate::{Context, DeepSizeOf};
+    use core::mem::size_of;
+    use std::rc::{Rc, Weak};
+    use weak_table::{WeakHashSet, WeakValueHashMap};
+
+    #[test]
+    fn weak_hash_set() {
+        let live: Vec<Rc<String>> = (0..4).map(|i| Rc::new(i.to_string().repeat(10))).collect();
+        let mut set: WeakHashSet<Weak<String>> = live.iter().cloned().collect();
+        {
+            let dropped = Rc::new(String::from("dropped"));
+            set.insert(dropped);
+        }
+        let table_size = set.capacity() * (size_of::<(Weak<String>, u64)>() + 1);
+        assert_eq!(
+            set.deep_size_of(),
+            size_of::<WeakHashSet<Weak<String>>>() + table_size
+        );
+
+        let mut context = Context::new();
+        context.set_count_weak_targets(true);
+        assert_eq!(
+            set.deep_size_of_children(&mut context),
+            table_size + 4 * (size_of::<String>() + 10)
+        );
+        // The live values have already been counted
+        assert_eq!(live[0].deep_size_of_children(&mut context), 0);
+    }
+
+    #[test]
+    fn weak_value_hash_map() {
+        let live = Rc::new(vec![0u8; 100]);
+        let mut map: WeakValueHashMap<u32, Weak<Vec<u8>>> = WeakValueHashMap::new();
+        map.insert(1, live.clone());
+        map.insert(2, Rc::new(vec![0u8; 100]));
+
+        let table_size = map.capacity() * (size_of::<(u32, Weak<Vec<u8>>)>() + 1);
+        assert_eq!(
+            map.deep_size_of(),
+            size_of::<WeakValueHashMap<u32, Weak<Vec<u8>>>>() + table_size
+        );
+
+        let mut context = Context::new();
+        context.set_count_weak_targets(true);
+        assert_eq!(
+            map.deep_size_of_children(&mut context),
+            table_size + size_of::<Vec<u8>>() + 100
+        );
+    }
+}
+
+#[cfg(feature = "moka")]
+mod moka_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use moka::sync::Cache;
+
+    #[test]
+    fn filled_cache() {
+        let cache: Cache<u64, String> = Cache::new(10_000);
+        for i in 0..1000 {
+            cache.insert(i, "x".repeat(100));
+        }
+        cache.run_pending_tasks();
+        assert_eq!(cache.entry_count(), 1000);
+
+        // The entry metadata is about 27 words per entry
+        let payload = 1000 * (size_of::<u64>() + size_of::<String>() + 100);
+        let size = cache.deep_size_of() - size_of::<Cache<u64, String>>();
+        assert!(size > payload);
+        assert!(size < 3 * payload);
+    }
+}
+
+#[cfg(feature = "quick_cache")]
+mod quick_cache_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use quick_cache::sync::Cache;
+
+    #[test]
+    fn filled_cache() {
+        let cache: Cache<u64, String> = Cache::new(10_000);
+        for i in 0..1000 {
+            cache.insert(i, "x".repeat(100));
+        }
+        assert_eq!(cache.len(), 1000);
+
+        let payload = 1000 * (size_of::<u64>() + size_of::<String>() + 100);
+        let size = cache.deep_size_of() - size_of::<Cache<u64, String>>();
+        assert!(size > payload);
+        assert!(size < 2 * payload);
+    }
+}
+
+#[cfg(feature = "kstring")]
+mod kstring_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use kstring::backend::ArcStr;
+    use kstring::{KString, KStringBase, KStringRef};
+
+    const LONG: &str = "a string that is too long to be stored inline";
+
+    #[test]
+    fn representations() {
+        let inline = KString::from_ref("short");
+        assert_eq!(inline.deep_size_of(), size_of::<KString>());
+
+        // Static strings can't be told apart from owned ones
+        let singleton = KString::from_static(LONG);
+        assert_eq!(singleton.deep_size_of(), size_of::<KString>() + LONG.len());
+
+        let owned = KString::from_ref(LONG);
+        assert_eq!(owned.deep_size_of(), size_of::<KString>() + LONG.len());
+        let cloned = owned.clone();
+        assert_eq!(
+            vec![owned, cloned].deep_size_of(),
+            size_of::<Vec<KString>>() + 2 * size_of::<KString>() + 2 * LONG.len()
+        );
+
+        assert_eq!(
+            KStringRef::from_ref(LONG).deep_size_of(),
+            size_of::<KStringRef>()
+        );
+    }
+
+    #[test]
+    fn shared_strings() {
+        let shared = KStringBase::<ArcStr>::from_ref(LONG);
+        let strings = vec![shared.clone(), shared.clone(), shared];
+        assert_eq!(
+            strings.deep_size_of(),
+            size_of::<Vec<KStringBase<ArcStr>>>()
+                + 3 * size_of::<KStringBase<ArcStr>>()
+                + LONG.len()
+        );
+    }
+}
+
+#[cfg(feature = "sprs")]
+mod sprs_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use sprs::{CsMat, CsVec, TriMat};
+
+    #[test]
+    fn sparse_matrix() {
+        // A 1000x1000 matrix with 3 entries in each row
+        let mut triplets = TriMat::new((1000, 1000));
+        for row in 0..1000 {
+            for col in [row, (row + 1) % 1000, (row + 500) % 1000] {
+                triplets.add_triplet(row, col, 1.0f64);
+            }
+        }
+        let matrix: CsMat<f64> = triplets.to_csr();
+        assert_eq!(matrix.nnz(), 3000);
+        assert_eq!(
+            matrix.deep_size_of(),
+            size_of::<CsMat<f64>>()
+                + 3000 * (size_of::<usize>() + size_of::<f64>())
+                + 1001 * size_of::<usize>()
+        );
+    }
+
+    #[test]
+    fn sparse_vector() {
+        let vector = CsVec::new(100, vec![1, 10, 50], vec![String::from("a"); 3]);
+        assert_eq!(
+            vector.deep_size_of(),
+            size_of::<CsVec<String>>() + 3 * (size_of::<usize>() + size_of::<String>() + 1)
+        );
+    }
+}
+
+#[cfg(feature = "half")]
+mod half_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use half::{bf16, f16};
+
+    #[test]
+    fn half_vectors() {
+        let weights = vec![f16::from_f32(0.5); 1_000_000];
+        assert_eq!(
+            weights.deep_size_of(),
+            size_of::<Vec<f16>>() + 2 * 1_000_000
+        );
+
+        #[derive(DeepSizeOf)]
+        struct Layer {
+            weights: Vec<bf16>,
+            bias: bf16,
+        }
+        let layer = Layer {
+            weights: vec![bf16::ONE; 100],
+            bias: bf16::ZERO,
+        };
+        assert_eq!(layer.deep_size_of(), size_of::<Layer>() + 200);
+    }
+}
+
+#[cfg(feature = "fixed")]
+mod fixed_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use fixed::types::extra::U12;
+    use fixed::{FixedI32, FixedU16};
+
+    #[test]
+    fn fixed_point() {
+        // No bound on the fractional bits is needed to measure these.
+        fn taps_size<Frac>(taps: &Vec<FixedU16<Frac>>) -> usize {
+            taps.deep_size_of()
+        }
+
+        #[derive(DeepSizeOf)]
+        struct Filter {
+            taps: Vec<FixedU16<U12>>,
+            gain: FixedI32<U12>,
+        }
+        let filter = Filter {
+            taps: vec![FixedU16::from_num(1); 64],
+            gain: FixedI32::from_num(2),
+        };
+        assert_eq!(filter.deep_size_of(), size_of::<Filter>() + 64 * 2);
+        assert_eq!(
+            taps_size(&filter.taps),
+            size_of::<Vec<FixedU16<U12>>>() + 64 * 2
+        );
+    }
+}
+
+#[cfg(feature = "jiff")]
+mod jiff_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use jiff::civil::date;
+    use jiff::tz::TimeZone;
+    use jiff::{SignedDuration, Span, Timestamp, Zoned};
+
+    #[test]
+    fn timestamped() {
+        #[derive(DeepSizeOf)]
+        struct Event {
+            at: Timestamp,
+            local: Zoned,
+            duration: SignedDuration,
+            repeat: Span,
+        }
+
+        // A POSIX time zone is heap allocated and shared between clones,
+        // so it is not attributed to any single `Zoned`.
+        let tz = TimeZone::posix("EST5EDT,M3.2.0,M11.1.0").unwrap();
+        let local = date(2024, 7, 4).at(12, 0, 0, 0).to_zoned(tz).unwrap();
+        let event = Event {
+            at: local.timestamp(),
+            local,
+            duration: SignedDuration::from_mins(90),
+            repeat: Span::new().weeks(1),
+        };
+        assert_eq!(event.deep_size_of(), size_of::<Event>());
+
+        let events = vec![event.local.clone(); 100];
+        assert_eq!(
+            events.deep_size_of(),
+            size_of::<Vec<Zoned>>() + 100 * size_of::<Zoned>()
+        );
+    }
+}
+
+#[cfg(feature = "rkyv")]
+mod rkyv_tests {
+    use crate::{Context, DeepSizeOf};
+    use core::mem::size_of;
+    use rkyv::string::ArchivedString;
+    use rkyv::util::AlignedVec;
+    use rkyv::vec::ArchivedVec;
+
+    #[test]
+    fn buffer_counted_once() {
+        let names: Vec<String> = (0..100).map(|i| format!("name number {}", i)).collect();
+        let buffer = rkyv::to_bytes::<rkyv::rancor::Error>(&names).unwrap();
+        let archived = unsafe { rkyv::access_unchecked::<ArchivedVec<ArchivedString>>(&buffer) };
+        assert_eq!(archived.len(), 100);
+
+        assert_eq!(
+            buffer.deep_size_of(),
+            size_of::<AlignedVec>() + buffer.capacity()
+        );
+        assert_eq!(
+            archived.deep_size_of(),
+            size_of::<ArchivedVec<ArchivedString>>()
+        );
+
+        let mut context = Context::new();
+        let mut total = buffer.deep_size_of_children(&mut context);
+        total += archived.deep_size_of_children(&mut context);
+        for name in archived.iter() {
+            total += name.deep_size_of_children(&mut context);
+        }
+        assert_eq!(total, buffer.capacity());
+    }
+}
+
+#[cfg(feature = "geo-types")]
+mod geo_types_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use geo_types::{
+        Coord, Geometry, GeometryCollection, LineString, MultiPolygon, Point, Polygon,
+    };
+
+    fn ring(n: usize) -> LineString {
+        let mut coords = Vec::with_capacity(n);
+        for i in 0..n - 1 {
+            coords.push(Coord {
+                x: i as f64,
+                y: 0.0,
+            });
+        }
+        coords.push(coords[0]);
+        LineString(coords)
+    }
+
+    #[test]
+    fn polygons() {
+        let coord = size_of::<Coord>();
+        let polygon = Polygon::new(ring(100), vec![ring(10), ring(20)]);
+        let expected = size_of::<Polygon>() + 130 * coord + 2 * size_of::<LineString>();
+        assert_eq!(polygon.deep_size_of(), expected);
+
+        let solid = Polygon::new(ring(50), vec![]);
+        let multi = MultiPolygon(vec![polygon.clone(), solid]);
+        assert_eq!(
+            multi.deep_size_of(),
+            size_of::<MultiPolygon>()
+                + 2 * size_of::<Polygon>()
+                + 180 * coord
+                + 2 * size_of::<LineString>()
+        );
+
+        let collection = GeometryCollection(vec![
+            Geometry::Point(Point::new(0.0, 0.0)),
+            Geometry::Polygon(polygon),
+        ]);
+        assert_eq!(
+            collection.deep_size_of(),
+            size_of::<GeometryCollection>() + 2 * size_of::<Geometry>() + expected
+                - size_of::<Polygon>()
+        );
+    }
+
+    #[test]
+    fn depth_limit() {
+        use crate::depth_limited_deep_size_of;
+
+        // The line string and its `Vec` are each one level deep
+        let line = ring(10);
+        assert_eq!(depth_limited_deep_size_of(&line, 1), None);
+        assert_eq!(
+            depth_limited_deep_size_of(&line, 2),
+            Some(line.deep_size_of())
+        );
+    }
+}
+
+#[cfg(feature = "rstar")]
+mod rstar_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use rstar::{RTree, RTreeNode};
+
+    fn points(n: usize) -> RTree<[f64; 2]> {
+        let points = (0..n)
+            .map(|i| [(i % 1000) as f64, (i / 1000) as f64])
+            .collect();
+        RTree::bulk_load(points)
+    }
+
+    #[test]
+    fn linear_scaling() {
+        let small = points(1_000).deep_size_of();
+        let large = points(1_000_000).deep_size_of();
+
+        // Every point lives in a node slot, and the node overhead is bounded
+        let slot = size_of::<RTreeNode<[f64; 2]>>();
+        assert!(small >= 1_000 * slot && small <= 4 * 1_000 * slot);
+        assert!(large >= 1_000_000 * slot && large <= 4 * 1_000_000 * slot);
+
+        let ratio = large as f64 / small as f64;
+        assert!(ratio > 900.0 && ratio < 1100.0);
+    }
+
+    #[test]
+    fn node_bounds() {
+        use crate::Context;
+
+        let tree = points(10_000);
+        let size = tree.deep_size_of_children(&mut Context::new());
+        let (lower, upper) = tree.deep_size_of_children_approx(&mut Context::new());
+        assert!(lower < size && size < upper);
+    }
+}
+
+#[cfg(feature = "csv")]
+mod csv_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use csv::{ByteRecord, StringRecord};
+
+    #[test]
+    fn records() {
+        let empty = ByteRecord::new();
+        let header = empty.deep_size_of();
+        assert!(header > size_of::<ByteRecord>());
+
+        let short = StringRecord::from(vec!["a", "bc"]);
+        assert_eq!(short.deep_size_of(), header + 3 + 2 * size_of::<usize>());
+
+        let fields: Vec<String> = (0..50).map(|i| format!("field{:02}", i)).collect();
+        let long = ByteRecord::from(fields);
+        assert_eq!(
+            long.deep_size_of(),
+            header + 50 * 7 + 50 * size_of::<usize>()
+        );
+
+        let rows = vec![short.clone(), short];
+        assert_eq!(
+            rows.deep_size_of(),
+            size_of::<Vec<StringRecord>>() + 2 * (header + 3 + 2 * size_of::<usize>())
+        );
+    }
+}
+
+#[cfg(feature = "crossbeam-skiplist")]
+mod crossbeam_skiplist_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use crossbeam_skiplist::{SkipMap, SkipSet};
+
+    #[test]
+    fn skip_map() {
+        let map = SkipMap::new();
+        for i in 0..100_000u64 {
+            map.insert(i, i * 2);
+        }
+        let children = map.deep_size_of() - size_of::<SkipMap<u64, u64>>();
+
+        // A node holds the key and value, its reference count, and between
+        // 1 and 32 tower pointers.
+        let min_node = 2 * size_of::<u64>() + 2 * size_of::<usize>();
+        let max_node = 2 * size_of::<u64>() + 33 * size_of::<usize>();
+        assert!(children > 100_000 * min_node);
+        assert!(children < 100_000 * max_node);
+
+        let (lower, upper) = map.deep_size_of_children_approx(&mut crate::Context::new());
+        assert_eq!(lower, 100_000 * min_node);
+        assert_eq!(upper, 100_000 * max_node);
+
+        let set = SkipSet::new();
+        set.insert(String::from("key"));
+        assert_eq!(
+            set.deep_size_of(),
+            size_of::<SkipSet<String>>() + size_of::<String>() + 3 * size_of::<usize>() + 3
+        );
+    }
+}
+
+#[cfg(feature = "scc")]
+mod scc_tests {
+    use crate::DeepSizeOf;
+    use core::cmp::Ordering;
+    use core::mem::size_of;
+    use scc::{HashIndex, HashMap};
+
+    #[test]
+    fn concurrent_fill() {
+        let shared = HashMap::new();
+        std::thread::scope(|scope| {
+            for t in 0..4 {
+                let shared = &shared;
+                scope.spawn(move || {
+                    for i in (t * 2_500)..((t + 1) * 2_500) {
+                        shared.insert_sync(i, format!("{:05}", i)).unwrap();
+                    }
+                });
+            }
+        });
+        let single = HashMap::new();
+        for i in 0..10_000 {
+            single.insert_sync(i, format!("{:05}", i)).unwrap();
+        }
+        assert_eq!(shared.len(), single.len());
+
+        // The strings are counted exactly, the buckets depend on the capacity
+        let payload = 10_000 * (size_of::<i32>() + size_of::<String>() + 5);
+        let shared_size = shared.deep_size_of();
+        let single_size = single.deep_size_of();
+        assert!(shared_size > payload && single_size > payload);
+        // Concurrent inserts may trigger an extra resize
+        match shared.capacity().cmp(&single.capacity()) {
+            Ordering::Equal => assert_eq!(shared_size, single_size),
+            Ordering::Greater => assert!(shared_size > single_size),
+            Ordering::Less => assert!(shared_size < single_size),
+        }
+
+        let index = HashIndex::new();
+        for i in 0..10_000 {
+            index.insert_sync(i, format!("{:05}", i)).unwrap();
+        }
+        assert!(index.deep_size_of() > payload);
+    }
+}
+
+#[cfg(feature = "ringbuf")]
+mod ringbuf_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use ringbuf::traits::{Producer, Split};
+    use ringbuf::{HeapCons, HeapProd, HeapRb};
+
+    #[test]
+    fn ring_counted_once() {
+        let mut rb = HeapRb::<String>::new(16);
+        rb.try_push(String::from("queued")).unwrap();
+        assert_eq!(
+            rb.deep_size_of(),
+            size_of::<HeapRb<String>>() + 16 * size_of::<String>() + 6
+        );
+
+        #[derive(DeepSizeOf)]
+        struct Stream {
+            producer: HeapProd<f32>,
+            consumer: HeapCons<f32>,
+        }
+        let (mut producer, consumer) = HeapRb::<f32>::new(1024).split();
+        producer.try_push(0.5).unwrap();
+        let stream = Stream { producer, consumer };
+
+        let ring = size_of::<HeapRb<f32>>() + 1024 * size_of::<f32>();
+        assert_eq!(stream.deep_size_of(), size_of::<Stream>() + ring);
+        assert_eq!(
+            stream.producer.deep_size_of(),
+            size_of::<HeapProd<f32>>() + ring
+        );
+    }
+}
+
+#[cfg(feature = "hdrhistogram")]
+mod hdrhistogram_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use hdrhistogram::Histogram;
+
+    #[test]
+    fn precision() {
+        let sizes: Vec<usize> = (1..=3)
+            .map(|sigfig| {
+                let mut hist = Histogram::<u64>::new_with_bounds(1, 3_600_000_000, sigfig).unwrap();
+                hist.record(1_000).unwrap();
+                // Half of the sub-buckets in each bucket overlap the previous
+                // bucket, so the counts are laid out (buckets + 1) * half wide
+                let sub_buckets = (2 * 10usize.pow(u32::from(sigfig))).next_power_of_two();
+                let counts = (usize::from(hist.buckets()) + 1) * (sub_buckets / 2);
+                assert_eq!(counts, hist.distinct_values());
+                assert_eq!(
+                    hist.deep_size_of(),
+                    size_of::<Histogram<u64>>() + counts * size_of::<u64>()
+                );
+                hist.deep_size_of() - size_of::<Histogram<u64>>()
+            })
+            .collect();
+
+        // Each extra significant figure needs ~8x the sub-buckets, in fewer buckets
+        assert!(sizes[1] > 5 * sizes[0] && sizes[1] < 8 * sizes[0]);
+        assert!(sizes[2] > 5 * sizes[1] && sizes[2] < 8 * sizes[1]);
+
+        let small = Histogram::<u8>::new_with_bounds(1, 3_600_000_000, 3).unwrap();
+        assert_eq!(
+            small.deep_size_of() - size_of::<Histogram<u8>>(),
+            sizes[2] / size_of::<u64>()
+        );
+    }
+}
+
+#[cfg(feature = "bloomfilter")]
+mod bloomfilter_tests {
+    use crate::DeepSizeOf;
+    use bloomfilter::Bloom;
+    use core::mem::size_of;
+
+    #[test]
+    fn bit_counts() {
+        for &items in &[100_000usize, 10_000_000] {
+            let mut bloom = Bloom::new_for_fp_rate_with_seed(items, 0.01, &[0; 32]).unwrap();
+            bloom.set(&1u64);
+            let bytes = bloom.deep_size_of() - size_of::<Bloom<u64>>();
+
+            // m = -n ln(p) / ln(2)^2, plus a 45 byte header
+            let bits = -(items as f64) * 0.01f64.ln() / 2f64.ln().powi(2);
+            assert_eq!(bytes, 45 + (bits / 8.0).ceil() as usize);
+        }
+    }
+}
+
+#[cfg(feature = "growable-bloom-filter")]
+mod growable_bloom_filter_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use growable_bloom_filter::GrowableBloom;
+
+    #[test]
+    fn bit_counts() {
+        use crate::external_impls::growable_bloom_filter_impl::format_is_known;
+
+        let empty = GrowableBloom::new(0.01, 1_000);
+        assert!(format_is_known(&empty));
+        assert_eq!(empty.deep_size_of(), size_of::<GrowableBloom>());
+
+        for &items in &[100_000usize, 10_000_000] {
+            let mut bloom = GrowableBloom::new(0.01, items);
+            bloom.insert(1u64);
+            assert!(format_is_known(&bloom));
+            let bytes = bloom.deep_size_of() - size_of::<GrowableBloom>();
+
+            // One filter has been allocated, with log2(1/p) slices of
+            // n / ln(2) bits each, behind a boxed slice and slice count
+            let slices = 100f64.log2().ceil();
+            let slice_bits = (items as f64 / 2f64.ln()).ceil();
+            let buffer = ((slices * slice_bits) / 8.0).ceil() as usize;
+            assert_eq!(bytes, 2 * size_of::<usize>() + size_of::<u64>() + buffer);
+        }
+    }
+}
+
+#[cfg(feature = "fst")]
+mod fst_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use fst::{Map, Set};
+
+    #[test]
+    fn owned_and_borrowed() {
+        let set = Set::from_iter(["apple", "apricot", "banana", "cherry"]).unwrap();
+        let mut bytes = set.into_fst().into_inner();
+        bytes.shrink_to_fit();
+        let len = bytes.len();
+
+        let set = Set::new(bytes).unwrap();
+        assert_eq!(set.as_fst().as_bytes().len(), len);
+        assert_eq!(set.deep_size_of(), size_of::<Set<Vec<u8>>>() + len);
+
+        // Borrowed storage belongs to someone else
+        let borrowed = Set::new(set.as_fst().as_bytes()).unwrap();
+        assert_eq!(borrowed.deep_size_of(), size_of::<Set<&[u8]>>());
+
+        let map = Map::from_iter([("a", 1), ("b", 2)]).unwrap();
+        let capacity = map.as_fst().as_inner().capacity();
+        assert_eq!(map.deep_size_of(), size_of::<Map<Vec<u8>>>() + capacity);
+    }
+}
+
+#[cfg(feature = "indextree")]
+mod indextree_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use indextree::{Arena, Node};
+
+    fn document(nodes: usize) -> Arena<String> {
+        let mut arena = Arena::with_capacity(nodes);
+        let root = arena.new_node(String::from("root"));
+        for i in 1..nodes {
+            let mut label = format!("node{:05}", i);
+            label.shrink_to_fit();
+            let child = arena.new_node(label);
+            root.append(child, &mut arena);
+        }
+        arena
+    }
+
+    #[test]
+    fn scaling() {
+        let node = size_of::<Node<String>>();
+        let small = document(1_000);
+        let large = document(4_000);
+        assert_eq!(
+            small.deep_size_of(),
+            size_of::<Arena<String>>() + 1_000 * node + 4 + 999 * 9
+        );
+        assert_eq!(
+            large.deep_size_of(),
+            size_of::<Arena<String>>() + 4_000 * node + 4 + 3_999 * 9
+        );
+
+        // Removed nodes keep their slot, but drop their value
+        let mut arena = document(1_000);
+        let before = arena.deep_size_of();
+        let last = arena.iter_node_ids().last().unwrap();
+        last.remove(&mut arena);
+        assert_eq!(arena.deep_size_of(), before - 9);
+    }
+}
+
+#[cfg(feature = "ego-tree")]
+mod ego_tree_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use ego_tree::Tree;
+
+    fn document(nodes: usize) -> Tree<String> {
+        let mut tree = Tree::new(String::from("root"));
+        let mut root = tree.root_mut();
+        for i in 1..nodes {
+            let mut label = format!("node{:05}", i);
+            label.shrink_to_fit();
+            root.append(label);
+        }
+        tree
+    }
+
+    #[test]
+    fn scaling() {
+        let small = document(1_000).deep_size_of() - size_of::<Tree<String>>();
+        let large = document(4_000).deep_size_of() - size_of::<Tree<String>>();
+
+        // Four optional ids (two in a pair) and the value per node
+        let node = 5 * size_of::<usize>() + size_of::<String>();
+        assert_eq!(small, 1_000 * node + 4 + 999 * 9);
+        assert_eq!(large, 4_000 * node + 4 + 3_999 * 9);
+    }
+}
+
+#[cfg(feature = "qp-trie")]
+mod qp_trie_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use qp_trie::Trie;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn routes() {
+        let routes: Vec<(Vec<u8>, u32)> = (0..1_000u32)
+            .map(|i| (format!("/api/v1/{}", i).into_bytes(), i))
+            .collect();
+        let trie: Trie<Vec<u8>, u32> = routes.iter().cloned().collect();
+        let btree: BTreeMap<Vec<u8>, u32> = routes.into_iter().collect();
+
+        let trie_keys: usize = trie.keys().map(|key| key.capacity()).sum();
+        let btree_keys: usize = btree.keys().map(|key| key.capacity()).sum();
+        let trie_overhead = trie.deep_size_of() - size_of::<Trie<Vec<u8>, u32>>() - trie_keys;
+        let btree_overhead =
+            btree.deep_size_of() - size_of::<BTreeMap<Vec<u8>, u32>>() - btree_keys;
+        assert!(trie_overhead > btree_overhead);
+
+        // The estimate counts a branch for every leaf, which is the most
+        // there can be
+        let (lower, upper) = trie.deep_size_of_children_approx(&mut crate::Context::new());
+        assert_eq!(upper - lower, trie_overhead / 2);
+        assert_eq!(upper, trie.deep_size_of() - size_of::<Trie<Vec<u8>, u32>>());
+    }
+}
+
+#[cfg(feature = "radix_trie")]
+mod radix_trie_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use radix_trie::{Trie, TrieCommon};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn routes() {
+        let routes: Vec<(String, u32)> = (0..1_000u32)
+            .map(|i| (format!("/api/v1/{}", i), i))
+            .collect();
+        let trie: Trie<String, u32> = routes.iter().cloned().collect();
+        let btree: BTreeMap<String, u32> = routes.into_iter().collect();
+
+        let trie_keys: usize = trie.keys().map(|key| key.capacity()).sum();
+        let btree_keys: usize = btree.keys().map(|key| key.capacity()).sum();
+        let trie_overhead = trie.deep_size_of() - size_of::<Trie<String, u32>>() - trie_keys;
+        let btree_overhead = btree.deep_size_of() - size_of::<BTreeMap<String, u32>>() - btree_keys;
+        assert!(trie_overhead > btree_overhead);
+    }
+}
+
+#[cfg(feature = "hickory-proto")]
+mod hickory_proto_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use hickory_proto::rr::rdata::{A, CNAME, TXT};
+    use hickory_proto::rr::{Name, RData, Record};
+
+    #[test]
+    fn zone() {
+        // Short names are stored inline
+        let short = Name::from_ascii("www.example.com.").unwrap();
+        assert_eq!(short.deep_size_of(), size_of::<Name>());
+
+        // 14 + 7 + 3 + 7 + 3 bytes of labels
+        let long = Name::from_ascii("static-content.example.com.edgenet.net.").unwrap();
+        assert_eq!(long.deep_size_of(), size_of::<Name>() + 34);
+
+        let mut zone = Vec::with_capacity(3_000);
+        for i in 0..3_000u32 {
+            let name = Name::from_ascii(format!("host{:04}.example.com.", i)).unwrap();
+            let data = match i % 3 {
+                0 => RData::A(A::new(10, 0, (i / 256) as u8, i as u8)),
+                1 => RData::TXT(TXT::new(vec![String::from("v=spf1 -all")])),
+                _ => RData::CNAME(CNAME(long.clone())),
+            };
+            zone.push(Record::from_rdata(name, 300, data));
+        }
+
+        let txt = size_of::<Box<[u8]>>() + 11;
+        assert_eq!(
+            zone.deep_size_of(),
+            size_of::<Vec<Record>>() + 3_000 * size_of::<Record>() + 1_000 * (txt + 34)
+        );
+    }
+}
+
+#[cfg(feature = "ciborium")]
+mod ciborium_tests {
+    use crate::DeepSizeOf;
+    use ciborium::value::Value;
+    use core::mem::size_of;
+
+    #[test]
+    fn nested_document() {
+        let firmware = Value::Tag(24, Box::new(Value::Bytes(vec![0xAB; 100_000])));
+        let document = Value::Map(vec![
+            (
+                Value::Text(String::from("device")),
+                Value::Text(String::from("sensor-7")),
+            ),
+            (Value::Text(String::from("firmware")), firmware),
+            (
+                Value::Text(String::from("readings")),
+                Value::Array(vec![Value::Integer(1.into()), Value::Float(2.5)]),
+            ),
+        ]);
+
+        let value = size_of::<Value>();
+        let expected = value // the document itself
+            + 3 * 2 * value // map entries
+            + 6 + 8 // "device", "sensor-7"
+            + 8 + value + 100_000 // "firmware", boxed bytes
+            + 8 + 2 * value; // "readings", array
+        assert_eq!(document.deep_size_of(), expected);
+    }
+}
+
+#[cfg(feature = "serde_cbor")]
+mod serde_cbor_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use serde_cbor::Value;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn nested_document() {
+        let firmware = Value::Tag(24, Box::new(Value::Bytes(vec![0xAB; 100_000])));
+        let mut map = BTreeMap::new();
+        map.insert(Value::Text(String::from("firmware")), firmware);
+        map.insert(
+            Value::Text(String::from("readings")),
+            Value::Array(vec![Value::Integer(1), Value::Float(2.5)]),
+        );
+        let document = Value::Map(map);
+
+        // Small maps fit in a single btree node
+        let value = size_of::<Value>();
+        let expected = value // the document itself
+            + 2 * 2 * value // map entries
+            + 8 + value + 100_000 // "firmware", boxed bytes
+            + 8 + 2 * value; // "readings", array
+        assert_eq!(document.deep_size_of(), expected);
+    }
+}
+
+#[cfg(feature = "elsa")]
+mod elsa_tests {
+    use crate::DeepSizeOf;
+    use core::mem::size_of;
+    use elsa::{FrozenBTreeMap, FrozenMap, FrozenVec};
+
+    #[test]
+    fn interner() {
+        let strings = FrozenVec::new();
+        let first: &String = strings.push_get(Box::new(String::from("first")));
+        strings.push(Box::new(String::from("second")));
+        assert_eq!(first, "first");
+        assert_eq!(
+            strings.deep_size_of(),
+            size_of::<FrozenVec<Box<String>>>()
+                + 2 * (size_of::<Box<String>>() + size_of::<String>())
+                + 11
+        );
+
+        let map = FrozenMap::new();
+        let btree = FrozenBTreeMap::new();
+        for i in 0..100 {
+            map.insert(format!("key{}", i), Box::new(i));
+            btree.insert(format!("key{}", i), Box::new(i));
+        }
+        assert_eq!(map.len(), 100);
+
+        // The tables and the boxed values are estimated from the length,
+        // since the entries can't be read, so the keys' strings are missing
+        let entry_size = size_of::<(String, Box<i32>)>();
+        assert_eq!(
+            map.deep_size_of(),
+            size_of::<FrozenMap<String, Box<i32>>>() + 100 * (entry_size + 1 + 4)
+        );
+        assert_eq!(
+            btree.deep_size_of(),
+            size_of::<FrozenBTreeMap<String, Box<i32>>>()
+                + 100 * (entry_size + 4)
+                + (100 / 6) * entry_size * 11
+        );
+    }
 }
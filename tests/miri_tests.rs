@@ -0,0 +1,92 @@
+//! Tests of the pointer tracking in `Context`, which can be run under Miri:
+//!
+//! ```sh
+//! MIRIFLAGS="-Zmiri-strict-provenance" cargo +nightly miri test --test miri_tests
+//! ```
+
+use deepsize::{ArcCountingStrategy, Context, DeepSizeOf};
+use std::mem::size_of;
+use std::rc::Rc;
+use std::sync::Arc;
+
+#[test]
+fn shared_arcs() {
+    let shared: Arc<[u32]> = vec![1, 2, 3].into();
+    let first = Arc::clone(&shared);
+    let second = Arc::clone(&shared);
+
+    let mut context = Context::new();
+    assert_eq!(
+        first.deep_size_of_with_context(&mut context),
+        size_of::<Arc<[u32]>>() + 3 * size_of::<u32>()
+    );
+    assert_eq!(
+        second.deep_size_of_with_context(&mut context),
+        size_of::<Arc<[u32]>>()
+    );
+    assert_eq!(first.deep_size_of(), second.deep_size_of());
+}
+
+#[test]
+fn shared_rcs() {
+    let shared: Rc<str> = Rc::from("shared");
+    let clones = vec![shared.clone(), shared.clone(), shared];
+    assert_eq!(
+        clones.deep_size_of(),
+        size_of::<Vec<Rc<str>>>() + clones.capacity() * size_of::<Rc<str>>() + 6
+    );
+}
+
+#[test]
+fn many_pointers() {
+    // Enough pointers to move the tracked sets out of their inline storage
+    let arcs: Vec<Arc<u64>> = (0..32).map(Arc::new).collect();
+    let rcs: Vec<Rc<u64>> = (0..32).map(Rc::new).collect();
+
+    let mut context = Context::new();
+    for _ in 0..2 {
+        for arc in &arcs {
+            arc.deep_size_of_children(&mut context);
+        }
+        for rc in &rcs {
+            rc.deep_size_of_children(&mut context);
+        }
+    }
+    for arc in &arcs {
+        assert_eq!(arc.deep_size_of_children(&mut context), 0);
+    }
+    for rc in &rcs {
+        assert_eq!(rc.deep_size_of_children(&mut context), 0);
+    }
+}
+
+#[test]
+fn dropped_pointers() {
+    // A new allocation may reuse the address of a dropped one, so contexts
+    // shouldn't outlive the values that they have measured; this only
+    // checks that tracking a dropped pointer is sound.
+    let mut context = Context::new();
+    let arc = Arc::new(vec![0u8; 16]);
+    let weak = Arc::downgrade(&arc);
+    arc.deep_size_of_with_context(&mut context);
+    drop(arc);
+    assert!(weak.upgrade().is_none());
+    assert_eq!(
+        weak.deep_size_of_with_context(&mut context),
+        size_of::<std::sync::Weak<Vec<u8>>>()
+    );
+}
+
+#[test]
+fn proportional_arcs() {
+    let arcs: Vec<Arc<u64>> = (0..16).map(Arc::new).collect();
+    let clones = arcs.to_vec();
+
+    let mut context = Context::with_arc_strategy(ArcCountingStrategy::Proportional);
+    let total: usize = arcs
+        .iter()
+        .chain(clones.iter())
+        .map(|arc| arc.deep_size_of_children(&mut context))
+        .sum();
+    assert_eq!(total, 16 * size_of::<u64>());
+}